@@ -0,0 +1,178 @@
+//! Compare `AATreeMap` against other map implementations for lookups, inserts, ordered
+//! scans and bound queries. Run with `cargo bench -p aatree-benchmarks --features compare`.
+//!
+//! All benchmarks are run for every key distribution from [`Distribution::ALL`]. To
+//! measure the performance for your own key distribution, add it there and re-run the
+//! benchmarks.
+
+use aatree::AATreeMap;
+use aatree_benchmarks::{misses, Distribution, AMOUNTS};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use indexmap::IndexMap;
+use std::{collections::BTreeMap, time::Duration};
+
+/// All combinations of key distributions and amounts.
+fn inputs() -> impl Iterator<Item = (Distribution, u64)> {
+	Distribution::ALL
+		.iter()
+		.flat_map(|dist| AMOUNTS.iter().map(move |amount| (*dist, *amount)))
+}
+
+/// The keys to look up in the maps, half of which are missing.
+fn probes(keys: &[u64]) -> Vec<u64> {
+	keys.iter()
+		.copied()
+		.zip(misses(keys))
+		.flat_map(|(hit, miss)| [hit, miss])
+		.collect()
+}
+
+fn bench_lookup(c: &mut Criterion) {
+	let mut g = c.benchmark_group("Compare Lookup");
+	g.sample_size(100).measurement_time(Duration::from_secs(10));
+	for (dist, amount) in inputs() {
+		let keys = dist.keys(amount);
+		let probes = probes(&keys);
+
+		let aa: AATreeMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		g.bench_with_input(
+			BenchmarkId::new(format!("AATree_{dist}"), amount),
+			&probes,
+			|b, probes| b.iter(|| probes.iter().filter_map(|k| aa.get(k)).count())
+		);
+
+		let bt: BTreeMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		g.bench_with_input(
+			BenchmarkId::new(format!("BTree_{dist}"), amount),
+			&probes,
+			|b, probes| b.iter(|| probes.iter().filter_map(|k| bt.get(k)).count())
+		);
+
+		let im: IndexMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		g.bench_with_input(
+			BenchmarkId::new(format!("IndexMap_{dist}"), amount),
+			&probes,
+			|b, probes| b.iter(|| probes.iter().filter_map(|k| im.get(k)).count())
+		);
+	}
+	g.finish();
+}
+
+fn bench_insert(c: &mut Criterion) {
+	let mut g = c.benchmark_group("Compare Insert");
+	g.sample_size(50).measurement_time(Duration::from_secs(10));
+	for (dist, amount) in inputs() {
+		let keys = dist.keys(amount);
+		g.bench_with_input(
+			BenchmarkId::new(format!("AATree_{dist}"), amount),
+			&keys,
+			|b, keys| {
+				b.iter(|| keys.iter().map(|k| (*k, *k)).collect::<AATreeMap<_, _>>())
+			}
+		);
+		g.bench_with_input(
+			BenchmarkId::new(format!("BTree_{dist}"), amount),
+			&keys,
+			|b, keys| {
+				b.iter(|| keys.iter().map(|k| (*k, *k)).collect::<BTreeMap<_, _>>())
+			}
+		);
+		g.bench_with_input(
+			BenchmarkId::new(format!("IndexMap_{dist}"), amount),
+			&keys,
+			|b, keys| {
+				b.iter(|| keys.iter().map(|k| (*k, *k)).collect::<IndexMap<_, _>>())
+			}
+		);
+	}
+	g.finish();
+}
+
+fn bench_scan(c: &mut Criterion) {
+	let mut g = c.benchmark_group("Compare Ordered Scan");
+	g.sample_size(100).measurement_time(Duration::from_secs(10));
+	for (dist, amount) in inputs() {
+		let keys = dist.keys(amount);
+
+		let aa: AATreeMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		g.bench_function(BenchmarkId::new(format!("AATree_{dist}"), amount), |b| {
+			b.iter(|| aa.values().sum::<u64>())
+		});
+
+		let bt: BTreeMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		g.bench_function(BenchmarkId::new(format!("BTree_{dist}"), amount), |b| {
+			b.iter(|| bt.values().sum::<u64>())
+		});
+
+		// IndexMap iterates in insertion order, so it needs to be sorted once first
+		let mut im: IndexMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		im.sort_keys();
+		g.bench_function(BenchmarkId::new(format!("IndexMap_{dist}"), amount), |b| {
+			b.iter(|| im.values().sum::<u64>())
+		});
+	}
+	g.finish();
+}
+
+fn bench_bound(c: &mut Criterion) {
+	let mut g = c.benchmark_group("Compare Bound Query");
+	g.sample_size(100).measurement_time(Duration::from_secs(10));
+	for (dist, amount) in inputs() {
+		let keys = dist.keys(amount);
+		let probes = probes(&keys);
+
+		let aa: AATreeMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		g.bench_with_input(
+			BenchmarkId::new(format!("AATree_{dist}"), amount),
+			&probes,
+			|b, probes| {
+				b.iter(|| {
+					for k in probes {
+						black_box(aa.first_key_value_at_or_after(k));
+					}
+				})
+			}
+		);
+
+		let bt: BTreeMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		g.bench_with_input(
+			BenchmarkId::new(format!("BTree_{dist}"), amount),
+			&probes,
+			|b, probes| {
+				b.iter(|| {
+					for k in probes {
+						black_box(bt.range(k ..).next());
+					}
+				})
+			}
+		);
+
+		// IndexMap has no ordered lookups, but a sorted IndexMap can be binary searched
+		let mut im: IndexMap<u64, u64> = keys.iter().map(|k| (*k, *k)).collect();
+		im.sort_keys();
+		g.bench_with_input(
+			BenchmarkId::new(format!("IndexMap_{dist}"), amount),
+			&probes,
+			|b, probes| {
+				b.iter(|| {
+					for k in probes {
+						let (mut lo, mut hi) = (0, im.len());
+						while lo < hi {
+							let mid = (lo + hi) / 2;
+							if im.get_index(mid).unwrap().0 < k {
+								lo = mid + 1;
+							} else {
+								hi = mid;
+							}
+						}
+						black_box(im.get_index(lo));
+					}
+				})
+			}
+		);
+	}
+	g.finish();
+}
+
+criterion_group!(benches, bench_lookup, bench_insert, bench_scan, bench_bound);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+use aatree::{frozen::Layout, AATreeMap, FrozenAATreeMap};
+use aatree_benchmarks::{misses, Distribution, AMOUNTS};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::time::Duration;
+
+/// The van Emde Boas layout only pays off once the keys no longer fit into the cache.
+const LARGE_AMOUNT: u64 = 1_000_000;
+
+fn frozen_get(map: &FrozenAATreeMap<u64, u64>, test: &[u64]) {
+	for i in test {
+		criterion::black_box(map.get(i));
+	}
+}
+
+fn bench_frozen(c: &mut Criterion) {
+	let mut g = c.benchmark_group("Frozen");
+	g.sample_size(150).measurement_time(Duration::from_secs(20));
+	for dist in Distribution::ALL {
+		for amount in AMOUNTS.iter().copied().chain([LARGE_AMOUNT]) {
+			let keys = dist.keys(amount);
+			let misses = misses(&keys);
+			let map: AATreeMap<u64, u64> = keys.iter().map(|key| (*key, *key)).collect();
+			for (name, layout) in
+				[("Sorted", Layout::Sorted), ("VebPacked", Layout::VebPacked)]
+			{
+				let frozen = map.clone().freeze_with_layout(layout);
+				for (kind, test) in [("hit", &keys), ("miss", &misses)] {
+					let id =
+						BenchmarkId::new(format!("{}_{}_{}", name, dist, kind), amount);
+					g.bench_with_input(id, &(&frozen, test), |b, (f, t)| {
+						b.iter(|| frozen_get(f, t))
+					});
+				}
+			}
+		}
+	}
+	g.finish();
+}
+
+criterion_group!(benches, bench_frozen);
+criterion_main!(benches);
@@ -1,50 +1,45 @@
 use aatree::AATreeSet;
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use aatree_benchmarks::{Distribution, AMOUNTS};
+use criterion::{
+	criterion_group, criterion_main, measurement::Measurement, BenchmarkGroup,
+	BenchmarkId, Criterion
+};
 use std::{collections::BTreeSet, time::Duration};
 
 macro_rules! benchmark {
-	($ty:ty, $amount:expr, asc) => {
-		benchmark!(@internal, $ty, $amount, 0..$amount, asc);
-	};
-	($ty:ty, $amount:expr, desc) => {
-		benchmark!(@internal, $ty, $amount, (0..$amount).rev(), desc);
-	};
-	(@internal, $ty:ty, $amount:expr, $iter:expr, $order:ident) => {
+	($ty:ident) => {
 		paste::item! {
-			fn [<$ty:lower _insert_ $amount _ $order>]() -> $ty<u64> {
+			fn [<$ty:lower _insert>](keys: &[u64]) -> $ty<u64> {
 				let mut container = $ty::default();
-				for i in $iter {
-					container.insert(i);
+				for i in keys {
+					container.insert(*i);
 				}
 				container
 			}
+			fn [<bench_ $ty:lower _insert>]<M: Measurement>(g: &mut BenchmarkGroup<M>, id: BenchmarkId, keys: &[u64]) {
+				g.bench_with_input(id, keys, |b, keys| b.iter(|| [<$ty:lower _insert>](keys)));
+			}
 		}
 	};
-	($group:literal = [$(($name:literal: $ty:ty, $amount:expr, $order:ident)),+]) => {
-		$(benchmark!($ty, $amount, $order);)+
+	($group:literal = [$($name:literal: $ty:ident),+]) => {
+		$(benchmark!($ty);)+
 		paste::item! {
 			fn [<bench_ $group:lower>](c: &mut Criterion) {
 				let mut g = c.benchmark_group($group);
 				g.sample_size(150).measurement_time(Duration::from_secs(20));
-				$(g.bench_function(BenchmarkId::new(format!("{}_{}", $name, stringify!($order)), $amount), |b| b.iter([<$ty:lower _insert_ $amount _ $order>]));)+
+				for dist in Distribution::ALL {
+					for amount in AMOUNTS {
+						let keys = dist.keys(amount);
+						$([<bench_ $ty:lower _insert>](&mut g, BenchmarkId::new(format!("{}_{}", $name, dist), amount), &keys);)+
+					}
+				}
 				g.finish();
 			}
 		}
 	};
 }
 
-benchmark!(
-	"Insert" = [
-		("AATree": AATreeSet, 10000, asc),
-		("AATree": AATreeSet, 10000, desc),
-		("AATree": AATreeSet, 100000, asc),
-		("AATree": AATreeSet, 100000, desc),
-		("BTree": BTreeSet, 10000, asc),
-		("BTree": BTreeSet, 10000, desc),
-		("BTree": BTreeSet, 100000, asc),
-		("BTree": BTreeSet, 100000, desc)
-	]
-);
+benchmark!("Insert" = ["AATree": AATreeSet, "BTree": BTreeSet]);
 
 criterion_group!(benches, bench_insert);
 criterion_main!(benches);
@@ -1,4 +1,5 @@
 use aatree::AATreeSet;
+use aatree_benchmarks::{misses, Distribution, AMOUNTS};
 use criterion::{
 	criterion_group, criterion_main, measurement::Measurement, BatchSize, BenchmarkGroup,
 	BenchmarkId, Criterion
@@ -6,57 +7,48 @@ use criterion::{
 use std::{collections::BTreeSet, time::Duration};
 
 macro_rules! benchmark {
-	($ty:ty, $amount:expr, hit) => {
-		benchmark!(@internal, $ty, $amount, (0..$amount).map(|x| x*2), (0..$amount).map(|x| x*2), hit);
-	};
-	($ty:ty, $amount:expr, miss) => {
-		benchmark!(@internal, $ty, $amount, (0..$amount).map(|x| x*2), (0..$amount).map(|x| x*2+1), miss);
-	};
-	(@internal, $ty:ty, $amount:expr, $iter_fill:expr, $iter_test:expr, $success:ident) => {
+	($ty:ident) => {
 		paste::item! {
-			fn [<$ty:lower _remove_ $amount _ $success>](container: &mut $ty<u64>, test: &[u64]) {
+			fn [<$ty:lower _remove>](container: &mut $ty<u64>, test: &[u64]) {
 				for i in test {
 					container.remove(i);
 				}
 			}
-			fn [<bench_ $ty:lower _remove_ $amount _ $success>]<M: Measurement>(g: &mut BenchmarkGroup<M>, id: BenchmarkId) {
-				let container: $ty<u64> = $iter_fill.collect();
-				let test: Vec<u64> = $iter_test.collect();
+			fn [<bench_ $ty:lower _remove>]<M: Measurement>(g: &mut BenchmarkGroup<M>, id: BenchmarkId, fill: &[u64], test: &[u64]) {
+				let container: $ty<u64> = fill.iter().copied().collect();
 				g.bench_with_input(id, &(container, test), |b, (c, t)| {
 					b.iter_batched_ref(
 						|| (c.clone(), t),
-						|(c, t)| [<$ty:lower _remove_ $amount _ $success>](c, t),
+						|(c, t)| [<$ty:lower _remove>](c, t),
 						BatchSize::SmallInput
 					)
 				});
 			}
 		}
 	};
-	($group:literal = [$(($name:literal: $ty:ty, $amount:expr, $success:ident)),+]) => {
-		$(benchmark!($ty, $amount, $success);)+
+	($group:literal = [$($name:literal: $ty:ident),+]) => {
+		$(benchmark!($ty);)+
 		paste::item! {
 			fn [<bench_ $group:lower>](c: &mut Criterion) {
 				let mut g = c.benchmark_group($group);
 				g.sample_size(150).measurement_time(Duration::from_secs(20));
-				$([<bench_ $ty:lower _remove_ $amount _ $success>](&mut g, BenchmarkId::new(format!("{}_{}", $name, stringify!($success)), $amount));)+
+				for dist in Distribution::ALL {
+					for amount in AMOUNTS {
+						let keys = dist.keys(amount);
+						let misses = misses(&keys);
+						$(
+							[<bench_ $ty:lower _remove>](&mut g, BenchmarkId::new(format!("{}_{}_hit", $name, dist), amount), &keys, &keys);
+							[<bench_ $ty:lower _remove>](&mut g, BenchmarkId::new(format!("{}_{}_miss", $name, dist), amount), &keys, &misses);
+						)+
+					}
+				}
 				g.finish();
 			}
 		}
 	};
 }
 
-benchmark!(
-	"Remove" = [
-		("AATree": AATreeSet, 10000, hit),
-		("AATree": AATreeSet, 10000, miss),
-		("AATree": AATreeSet, 100000, hit),
-		("AATree": AATreeSet, 100000, miss),
-		("BTree": BTreeSet, 10000, hit),
-		("BTree": BTreeSet, 10000, miss),
-		("BTree": BTreeSet, 100000, hit),
-		("BTree": BTreeSet, 100000, miss)
-	]
-);
+benchmark!("Remove" = ["AATree": AATreeSet, "BTree": BTreeSet]);
 
 criterion_group!(benches, bench_remove);
 criterion_main!(benches);
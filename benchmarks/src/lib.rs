@@ -0,0 +1,159 @@
+//! Deterministic key distributions for the benchmarks.
+//!
+//! Inserting ascending keys only exercises the rightmost path of a tree, which is the
+//! best case for most implementations. All benchmarks are therefore run for every
+//! [`Distribution`]. The keys are generated from a fixed seed, so every run of the
+//! benchmarks uses exactly the same keys.
+//!
+//! All generated keys are even, so adding one to any key yields a key that is guaranteed
+//! to be missing. See [`misses`].
+
+use std::fmt::{self, Display, Formatter};
+
+/// The amounts of keys that all benchmarks are run with.
+pub const AMOUNTS: [u64; 2] = [10000, 100000];
+
+/// A distribution of keys, in the order they are inserted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Distribution {
+	/// Strictly ascending keys.
+	Ascending,
+	/// Strictly descending keys.
+	Descending,
+	/// Keys drawn uniformly at random from a key space 16 times larger than the amount
+	/// of keys, so there are few duplicates.
+	Uniform,
+	/// Keys from 16 clusters that are spread over the key space. Every key continues a
+	/// randomly chosen cluster with a small gap, like time series from several sources.
+	Clustered,
+	/// Keys drawn from a Zipf distribution with exponent 1, so that a few hot keys make
+	/// up most of the keys. The hot keys are scattered over the key space.
+	Zipfian,
+	/// Ascending keys, where every key is swapped with a random one of the following 32
+	/// keys, like timestamps that arrive slightly out of order.
+	Jittered
+}
+
+impl Distribution {
+	/// All distributions.
+	pub const ALL: [Self; 6] = [
+		Self::Ascending,
+		Self::Descending,
+		Self::Uniform,
+		Self::Clustered,
+		Self::Zipfian,
+		Self::Jittered
+	];
+
+	/// Generate `amount` keys following this distribution. Some distributions contain
+	/// duplicate keys.
+	pub fn keys(self, amount: u64) -> Vec<u64> {
+		let mut rng = SplitMix64(0x5EED);
+		let keys: Vec<u64> = match self {
+			Self::Ascending => (0 .. amount).collect(),
+			Self::Descending => (0 .. amount).rev().collect(),
+			Self::Uniform => (0 .. amount).map(|_| rng.below(amount * 16)).collect(),
+			Self::Clustered => {
+				let mut cursors: Vec<u64> =
+					(0 .. 16).map(|_| rng.below(1 << 40)).collect();
+				(0 .. amount)
+					.map(|_| {
+						let cursor = &mut cursors[rng.below(16) as usize];
+						*cursor += 1 + rng.below(4);
+						*cursor
+					})
+					.collect()
+			},
+			Self::Zipfian => (0 .. amount)
+				.map(|_| {
+					// inverse transform sampling of the continuous approximation
+					let rank = (amount as f64).powf(rng.unit()) as u64 - 1;
+					// an odd multiplier scatters the ranks over the key space
+					rank.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 2
+				})
+				.collect(),
+			Self::Jittered => {
+				let mut keys: Vec<u64> = (0 .. amount).collect();
+				for i in 0 .. keys.len() {
+					let window = (keys.len() - i).min(32) as u64;
+					keys.swap(i, i + rng.below(window) as usize);
+				}
+				keys
+			}
+		};
+		keys.into_iter().map(|key| key * 2).collect()
+	}
+}
+
+impl Display for Distribution {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Ascending => "asc",
+			Self::Descending => "desc",
+			Self::Uniform => "uniform",
+			Self::Clustered => "clustered",
+			Self::Zipfian => "zipfian",
+			Self::Jittered => "jittered"
+		})
+	}
+}
+
+/// Returns a key for every key in `keys` that is guaranteed not to be part of `keys`.
+pub fn misses(keys: &[u64]) -> Vec<u64> {
+	keys.iter().map(|key| key + 1).collect()
+}
+
+/// The SplitMix64 generator. It is not suitable for anything but generating benchmark
+/// inputs, but it is small, fast and needs no dependencies.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+	fn next(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	/// A random number in `0 .. n`.
+	fn below(&mut self, n: u64) -> u64 {
+		((self.next() as u128 * n as u128) >> 64) as u64
+	}
+
+	/// A random number in `[0, 1)`.
+	fn unit(&mut self) -> f64 {
+		(self.next() >> 11) as f64 / (1u64 << 53) as f64
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{misses, Distribution, AMOUNTS};
+	use std::collections::BTreeSet;
+
+	#[test]
+	fn test_distributions() {
+		for dist in Distribution::ALL {
+			for amount in AMOUNTS {
+				let keys = dist.keys(amount);
+				assert_eq!(keys.len() as u64, amount);
+				assert_eq!(keys, dist.keys(amount), "{dist} is not deterministic");
+				assert!(keys.iter().all(|key| key % 2 == 0));
+
+				let unique: BTreeSet<u64> = keys.iter().copied().collect();
+				assert!(misses(&keys).iter().all(|key| !unique.contains(key)));
+				if dist == Distribution::Zipfian {
+					// most keys are duplicates of the few hot keys
+					assert!(unique.len() < keys.len() / 2, "{dist}: {}", unique.len());
+				} else {
+					assert!(
+						unique.len() > keys.len() * 9 / 10,
+						"{dist}: {}",
+						unique.len()
+					);
+				}
+			}
+		}
+	}
+}
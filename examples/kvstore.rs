@@ -0,0 +1,338 @@
+//! A small ordered key-value store with a line-based text protocol.
+//!
+//! Keys and values are unsigned 64-bit integers. All modifications are appended to a
+//! journal file, and the `SNAPSHOT` command persists the whole map in the binary frozen
+//! encoding and truncates the journal. On startup, the store is restored from the
+//! snapshot and the journal.
+//!
+//! ```none
+//! cargo run --example kvstore -- [--data DIR] [--listen ADDR]
+//! ```
+//!
+//! Without `--listen`, commands are read from stdin. With `--listen`, the store accepts
+//! TCP connections on the given address and serves one connection at a time. Type `HELP`
+//! for a list of commands.
+
+use aatree::{
+	frozen::FrozenView,
+	journal::{replay, Journal, JournalOp, JournaledMap},
+	AATreeMap
+};
+use std::{
+	env,
+	fmt::Write as _,
+	fs::{self, File, OpenOptions},
+	io::{self, BufRead, BufReader, BufWriter, Write},
+	net::TcpListener,
+	path::{Path, PathBuf}
+};
+
+const HELP: &str = "\
+SET <key> <value>     insert or replace a value
+GET <key>             print the value of a key
+DEL <key>             remove a key
+FLOOR <key>           print the last entry at or before a key
+CEIL <key>            print the first entry at or after a key
+RANGE <from> <to>     print all entries with from <= key < to
+SCAN <cursor> <count> print up to count entries starting at cursor, and the next cursor
+LEN                   print the number of entries
+CLEAR                 remove all entries
+SNAPSHOT              persist the store and truncate the journal
+QUIT                  close the connection";
+
+/// A journal that appends every operation as a line of text to a file.
+struct FileJournal {
+	file: Option<BufWriter<File>>
+}
+
+impl Journal<u64, u64> for FileJournal {
+	fn record(&mut self, op: JournalOp<&u64, &u64>) {
+		let file = match &mut self.file {
+			Some(file) => file,
+			None => return
+		};
+		let result = match op {
+			JournalOp::Insert(key, value) => writeln!(file, "SET {key} {value}"),
+			JournalOp::Remove(key) => writeln!(file, "DEL {key}"),
+			JournalOp::Clear => writeln!(file, "CLEAR")
+		};
+		result
+			.and_then(|_| file.flush())
+			.expect("failed to write the journal");
+	}
+}
+
+struct Store {
+	map: JournaledMap<u64, u64, FileJournal>,
+	data: Option<PathBuf>
+}
+
+impl Store {
+	/// Create a store that is not persisted.
+	fn in_memory() -> Self {
+		Self {
+			map: JournaledMap::new(FileJournal { file: None }),
+			data: None
+		}
+	}
+
+	/// Restore a store from the snapshot and journal in `data`.
+	fn open(data: &Path) -> io::Result<Self> {
+		fs::create_dir_all(data)?;
+		let snapshot = match fs::read(data.join("snapshot.bin")) {
+			Ok(bytes) => FrozenView::<u64, u64>::from_bytes(&bytes)
+				.map_err(|err| {
+					io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+				})?
+				.to_frozen(),
+			Err(err) if err.kind() == io::ErrorKind::NotFound => {
+				AATreeMap::new().freeze()
+			},
+			Err(err) => return Err(err)
+		};
+
+		let journal_path = data.join("journal.log");
+		let mut ops = Vec::new();
+		if journal_path.exists() {
+			for line in BufReader::new(File::open(&journal_path)?).lines() {
+				ops.extend(parse_journal_line(&line?));
+			}
+		}
+		let map = replay(snapshot, ops);
+
+		let file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(journal_path)?;
+		Ok(Self {
+			map: JournaledMap::from_snapshot(map, FileJournal {
+				file: Some(BufWriter::new(file))
+			}),
+			data: Some(data.to_owned())
+		})
+	}
+
+	/// Write a snapshot of the map and truncate the journal.
+	fn snapshot(&mut self) -> io::Result<String> {
+		let data = match &self.data {
+			Some(data) => data,
+			None => return Ok("ERR the store is not persisted".into())
+		};
+		let bytes = (*self.map).clone().freeze().to_bytes();
+		let tmp = data.join("snapshot.bin.tmp");
+		fs::write(&tmp, &bytes)?;
+		fs::rename(tmp, data.join("snapshot.bin"))?;
+		if let Some(file) = &mut self.map.journal_mut().file {
+			file.get_mut().set_len(0)?;
+		}
+		Ok(format!("OK {} bytes", bytes.len()))
+	}
+
+	/// Execute a single command and return the response.
+	fn execute(&mut self, line: &str) -> io::Result<String> {
+		let mut words = line.split_whitespace();
+		let command = words.next().unwrap_or_default().to_ascii_uppercase();
+		let args: Result<Vec<u64>, _> = words.map(str::parse).collect();
+		let args = match args {
+			Ok(args) => args,
+			Err(err) => return Ok(format!("ERR {err}"))
+		};
+
+		Ok(match (command.as_str(), args.as_slice()) {
+			("SET", &[key, value]) => match self.map.insert(key, value) {
+				Some(old) => format!("OK (was {old})"),
+				None => "OK".into()
+			},
+			("GET", &[key]) => match self.map.get(&key) {
+				Some(value) => value.to_string(),
+				None => "NIL".into()
+			},
+			("DEL", &[key]) => match self.map.remove(&key) {
+				Some(_) => "OK".into(),
+				None => "NIL".into()
+			},
+			("FLOOR", &[key]) => entry(self.map.last_key_value_at_or_before(&key)),
+			("CEIL", &[key]) => entry(self.map.first_key_value_at_or_after(&key)),
+			("RANGE", &[from, to]) => entries(self.map.range(from .. to), true),
+			("SCAN", &[cursor, count]) => {
+				let mut iter = self.map.range(cursor ..);
+				let page = iter.by_ref().take(count as usize);
+				let mut response = entries(page, false);
+				match iter.next() {
+					Some((next, _)) => write!(response, "NEXT {next}").unwrap(),
+					None => response.push_str("END")
+				}
+				response
+			},
+			("LEN", &[]) => self.map.len().to_string(),
+			("CLEAR", &[]) => {
+				self.map.clear();
+				"OK".into()
+			},
+			("SNAPSHOT", &[]) => self.snapshot()?,
+			("HELP", &[]) => HELP.into(),
+			_ => "ERR unknown command or wrong number of arguments, try HELP".into()
+		})
+	}
+}
+
+fn parse_journal_line(line: &str) -> Option<JournalOp<u64, u64>> {
+	let mut words = line.split_whitespace();
+	let op = match (words.next()?, words.next(), words.next()) {
+		("SET", Some(key), Some(value)) => {
+			JournalOp::Insert(key.parse().ok()?, value.parse().ok()?)
+		},
+		("DEL", Some(key), None) => JournalOp::Remove(key.parse().ok()?),
+		("CLEAR", None, None) => JournalOp::Clear,
+		_ => return None
+	};
+	Some(op)
+}
+
+fn entry(entry: Option<(&u64, &u64)>) -> String {
+	match entry {
+		Some((key, value)) => format!("{key} {value}"),
+		None => "NIL".into()
+	}
+}
+
+/// Format all entries, one per line, optionally followed by a line containing `END`.
+fn entries<'a>(iter: impl Iterator<Item = (&'a u64, &'a u64)>, end: bool) -> String {
+	let mut response = String::new();
+	for (key, value) in iter {
+		writeln!(response, "{key} {value}").unwrap();
+	}
+	if end {
+		response.push_str("END");
+	}
+	response
+}
+
+/// Serve commands read from `input` until it is closed or `QUIT` is received.
+fn serve(
+	store: &mut Store,
+	input: impl BufRead,
+	mut output: impl Write
+) -> io::Result<()> {
+	for line in input.lines() {
+		let line = line?;
+		if line.trim().is_empty() {
+			continue;
+		}
+		if line.trim().eq_ignore_ascii_case("QUIT") {
+			break;
+		}
+		writeln!(output, "{}", store.execute(&line)?)?;
+		output.flush()?;
+	}
+	Ok(())
+}
+
+fn main() -> io::Result<()> {
+	let mut data = None;
+	let mut listen = None;
+	let mut args = env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--data" => data = args.next().map(PathBuf::from),
+			"--listen" => listen = args.next(),
+			_ => {
+				eprintln!("Usage: kvstore [--data DIR] [--listen ADDR]");
+				std::process::exit(1);
+			}
+		}
+	}
+
+	let mut store = match data {
+		Some(data) => Store::open(&data)?,
+		None => Store::in_memory()
+	};
+	match listen {
+		Some(addr) => {
+			let listener = TcpListener::bind(addr)?;
+			eprintln!("Listening on {}", listener.local_addr()?);
+			for stream in listener.incoming() {
+				let stream = stream?;
+				let input = BufReader::new(stream.try_clone()?);
+				if let Err(err) = serve(&mut store, input, stream) {
+					eprintln!("Connection failed: {err}");
+				}
+			}
+			Ok(())
+		},
+		None => serve(&mut store, io::stdin().lock(), io::stdout().lock())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Store;
+	use std::{env, fs, process};
+
+	fn run(store: &mut Store, commands: &[&str]) -> Vec<String> {
+		commands
+			.iter()
+			.map(|command| store.execute(command).unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn test_commands() {
+		let mut store = Store::in_memory();
+		let responses = run(&mut store, &[
+			"SET 3 30",
+			"set 1 10",
+			"SET 2 20",
+			"SET 2 21",
+			"GET 2",
+			"GET 4",
+			"FLOOR 5",
+			"CEIL 0",
+			"RANGE 2 4",
+			"SCAN 0 2",
+			"SCAN 3 2",
+			"DEL 1",
+			"LEN",
+			"SET x 1",
+			"SNAPSHOT"
+		]);
+		assert_eq!(responses, [
+			"OK",
+			"OK",
+			"OK",
+			"OK (was 20)",
+			"21",
+			"NIL",
+			"3 30",
+			"1 10",
+			"2 21\n3 30\nEND",
+			"1 10\n2 21\nNEXT 3",
+			"3 30\nEND",
+			"OK",
+			"2",
+			"ERR invalid digit found in string",
+			"ERR the store is not persisted"
+		]);
+	}
+
+	#[test]
+	fn test_persistence() {
+		let data = env::temp_dir().join(format!("aatree-kvstore-{}", process::id()));
+		let _ = fs::remove_dir_all(&data);
+
+		let mut store = Store::open(&data).unwrap();
+		run(&mut store, &[
+			"SET 1 10", "SET 2 20", "SNAPSHOT", "DEL 1", "SET 3 30"
+		]);
+		drop(store);
+
+		let mut store = Store::open(&data).unwrap();
+		assert_eq!(run(&mut store, &["RANGE 0 10"]), ["2 20\n3 30\nEND"]);
+		run(&mut store, &["CLEAR", "SET 4 40"]);
+		drop(store);
+
+		let mut store = Store::open(&data).unwrap();
+		assert_eq!(run(&mut store, &["RANGE 0 10"]), ["4 40\nEND"]);
+		fs::remove_dir_all(&data).unwrap();
+	}
+}
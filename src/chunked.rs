@@ -0,0 +1,480 @@
+//! An experimental set that stores multiple elements per node.
+
+use crate::{iter::AAIter, node::AANode};
+use core::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt::{self, Debug},
+	iter::{FromIterator, FusedIterator},
+	slice
+};
+
+/// A sorted array of up to `N` elements, stored in a single node of the tree.
+///
+/// Chunks in a tree are never empty and their elements never overlap, so they are
+/// ordered by their first element.
+#[derive(Clone)]
+struct Chunk<T, const N: usize> {
+	items: [Option<T>; N],
+	len: usize
+}
+
+/// Access an occupied slot of a chunk.
+fn occupied<T>(item: &Option<T>) -> &T {
+	item.as_ref().expect("chunk slot should be occupied")
+}
+
+impl<T, const N: usize> Chunk<T, N> {
+	fn empty() -> Self {
+		Self {
+			items: [(); N].map(|_| None),
+			len: 0
+		}
+	}
+
+	fn first(&self) -> &T {
+		occupied(&self.items[0])
+	}
+
+	fn last(&self) -> &T {
+		occupied(&self.items[self.len - 1])
+	}
+
+	/// Find `value` in this chunk, or the index where it would have to be inserted.
+	///
+	/// Chunks are small, so instead of a binary search, this counts all elements smaller
+	/// than `value` without any data-dependent branches.
+	fn search<Q>(&self, value: &Q) -> Result<usize, usize>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let items = &self.items[.. self.len];
+		let idx = items
+			.iter()
+			.map(|item| matches!(item, Some(item) if item.borrow() < value) as usize)
+			.sum();
+		match items.get(idx) {
+			Some(Some(item)) if Borrow::<Q>::borrow(item) == value => Ok(idx),
+			_ => Err(idx)
+		}
+	}
+
+	fn insert(&mut self, idx: usize, value: T) {
+		self.items[self.len] = Some(value);
+		self.items[idx ..= self.len].rotate_right(1);
+		self.len += 1;
+	}
+
+	fn remove(&mut self, idx: usize) -> Option<T> {
+		let value = self.items[idx].take();
+		self.items[idx .. self.len].rotate_left(1);
+		self.len -= 1;
+		value
+	}
+
+	/// Insert `value` at `idx` into this full chunk and move the upper half of the
+	/// elements into a new chunk, which is returned.
+	fn split_insert(&mut self, idx: usize, value: T) -> Self {
+		let mid = (N + 1) / 2;
+		let from = if idx < mid { mid - 1 } else { mid };
+		let mut upper = Self::empty();
+		for (dst, src) in (from .. N).enumerate() {
+			upper.items[dst] = self.items[src].take();
+		}
+		upper.len = N - from;
+		self.len = from;
+		if idx < mid {
+			self.insert(idx, value);
+		} else {
+			upper.insert(idx - mid, value);
+		}
+		upper
+	}
+}
+
+impl<T, const N: usize> From<T> for Chunk<T, N> {
+	fn from(value: T) -> Self {
+		let mut chunk = Self::empty();
+		chunk.insert(0, value);
+		chunk
+	}
+}
+
+/// Chunks are compared by their first element. This allows looking up and removing the
+/// node of a chunk that contains only a single element.
+impl<T, const N: usize> Borrow<T> for Chunk<T, N> {
+	fn borrow(&self) -> &T {
+		self.first()
+	}
+}
+
+impl<T: Ord, const N: usize> PartialEq for Chunk<T, N> {
+	fn eq(&self, other: &Self) -> bool {
+		self.first() == other.first()
+	}
+}
+
+impl<T: Ord, const N: usize> Eq for Chunk<T, N> {}
+
+impl<T: Ord, const N: usize> PartialOrd for Chunk<T, N> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T: Ord, const N: usize> Ord for Chunk<T, N> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.first().cmp(other.first())
+	}
+}
+
+/// A set based on an AA-Tree that stores up to `N` elements in every node.
+///
+/// The nodes are balanced exactly like the nodes of an [`AATreeSet`](crate::AATreeSet).
+/// However, storing several elements in a node reduces the number of nodes and thereby
+/// the number of pointers that have to be followed when searching the tree, which makes
+/// better use of the CPU cache. With `N = 1`, this behaves like an `AATreeSet`.
+///
+/// A full node is split into two halves when inserting into it, and a node is removed
+/// once its last element is removed. Nodes are not merged, so a set that had many
+/// elements removed can end up with sparsely populated nodes.
+///
+/// This type is an experiment to evaluate whether the cache efficiency gap to
+/// [`BTreeSet`](alloc::collections::BTreeSet) can be closed. Its API is therefore kept
+/// minimal and might change in the future.
+///
+/// # Example
+///
+/// ```rust
+/// use aatree::chunked::ChunkedAATreeSet;
+///
+/// let mut set: ChunkedAATreeSet<u32, 4> = (0 .. 10).rev().collect();
+/// assert!(set.contains(&3));
+/// assert!(set.remove(&3));
+/// assert!(!set.contains(&3));
+/// assert_eq!(set.len(), 9);
+/// assert_eq!(set.iter().copied().collect::<Vec<_>>(), [
+/// 	0, 1, 2, 4, 5, 6, 7, 8, 9
+/// ]);
+/// ```
+#[derive(Clone)]
+pub struct ChunkedAATreeSet<T, const N: usize = 8> {
+	root: AANode<Chunk<T, N>>,
+	len: usize
+}
+
+impl<T, const N: usize> Default for ChunkedAATreeSet<T, N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Debug, const N: usize> Debug for ChunkedAATreeSet<T, N> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_set().entries(self.iter()).finish()
+	}
+}
+
+impl<T, const N: usize> ChunkedAATreeSet<T, N> {
+	/// Construct a new, empty set.
+	///
+	/// # Panics
+	///
+	/// Panics if `N` is zero.
+	pub const fn new() -> Self {
+		assert!(N > 0, "chunks must have room for at least one element");
+		Self {
+			root: AANode::new(),
+			len: 0
+		}
+	}
+
+	/// Returns the number of elements in the set.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns `true` if the set contains no elements.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Clears the set, removing all elements.
+	pub fn clear(&mut self) {
+		self.root = AANode::new();
+		self.len = 0;
+	}
+
+	/// Creates an iterator over this set that visits the elements in ascending order.
+	pub fn iter(&self) -> Iter<'_, T, N> {
+		Iter {
+			// the number of chunks is unknown, but it is only used for the size hint
+			chunks: AAIter::new(&self.root, self.len),
+			items: [].iter(),
+			len: self.len
+		}
+	}
+
+	/// Returns the smallest element of the set.
+	pub fn first(&self) -> Option<&T> {
+		let mut node = self.root.as_ref()?;
+		while let Some(left) = node.left_child.as_ref() {
+			node = left;
+		}
+		Some(node.content.first())
+	}
+
+	/// Returns the largest element of the set.
+	pub fn last(&self) -> Option<&T> {
+		let mut node = self.root.as_ref()?;
+		while let Some(right) = node.right_child.as_ref() {
+			node = right;
+		}
+		Some(node.content.last())
+	}
+
+	/// Returns the chunk that contains `value` if it is part of this set.
+	fn chunk<Q>(&self, value: &Q) -> Option<&Chunk<T, N>>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let mut node = self.root.as_ref()?;
+		loop {
+			let child = if value < node.content.first().borrow() {
+				&node.left_child
+			} else if value > node.content.last().borrow() {
+				&node.right_child
+			} else {
+				return Some(&node.content);
+			};
+			node = child.as_ref()?;
+		}
+	}
+
+	/// Returns `true` if the set contains an element equal to `value`.
+	pub fn contains<Q>(&self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.chunk(value)
+			.map(|chunk| chunk.search(value).is_ok())
+			.unwrap_or(false)
+	}
+}
+
+impl<T: Ord, const N: usize> ChunkedAATreeSet<T, N> {
+	#[cfg(test)]
+	#[track_caller]
+	fn assert_valid(&self) {
+		self.root.assert_valid();
+		let mut len = 0;
+		let mut last: Option<&T> = None;
+		for chunk in AAIter::<'_, _, &Chunk<T, N>>::new(&self.root, usize::MAX) {
+			assert!(chunk.len > 0, "chunks must not be empty");
+			assert!(chunk.items[chunk.len ..].iter().all(Option::is_none));
+			for item in &chunk.items[.. chunk.len] {
+				let item = occupied(item);
+				assert!(last.map(|last| last < item).unwrap_or(true));
+				last = Some(item);
+			}
+			len += chunk.len;
+		}
+		assert_eq!(len, self.len);
+	}
+
+	/// Adds a value to the set. Returns `false` if the set already contained an equal
+	/// element.
+	pub fn insert(&mut self, value: T) -> bool {
+		let mut traverse = match self.root.traverse_mut() {
+			Some(traverse) => traverse,
+			None => {
+				self.root = Chunk::from(value).into();
+				self.len = 1;
+				return true;
+			}
+		};
+		loop {
+			let chunk = traverse.peek();
+			traverse = if &value < chunk.first() && traverse.has_left_child() {
+				traverse.turn_left().unwrap()
+			} else if &value > chunk.last() && traverse.has_right_child() {
+				traverse.turn_right().unwrap()
+			} else {
+				break;
+			};
+		}
+
+		let chunk = traverse.into_content();
+		let idx = match chunk.search(&value) {
+			Ok(_) => return false,
+			Err(idx) => idx
+		};
+		self.len += 1;
+		if chunk.len < N {
+			chunk.insert(idx, value);
+		} else {
+			let upper = chunk.split_insert(idx, value);
+			self.root.insert(upper);
+		}
+		true
+	}
+
+	/// Removes a value from the set. Returns `true` if the value was part of the set.
+	pub fn remove<Q>(&mut self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let mut traverse = match self.root.traverse_mut() {
+			Some(traverse) => traverse,
+			None => return false
+		};
+		loop {
+			let chunk = traverse.peek();
+			let next = if value < chunk.first().borrow() {
+				traverse.turn_left()
+			} else if value > chunk.last().borrow() {
+				traverse.turn_right()
+			} else {
+				break;
+			};
+			traverse = match next {
+				Ok(next) => next,
+				Err(_) => return false
+			};
+		}
+
+		let chunk = traverse.into_content();
+		let idx = match chunk.search(value) {
+			Ok(idx) => idx,
+			Err(_) => return false
+		};
+		self.len -= 1;
+		if chunk.len > 1 {
+			chunk.remove(idx);
+		} else {
+			self.root.remove::<Q, T>(value);
+		}
+		true
+	}
+}
+
+impl<T: Ord, const N: usize> FromIterator<T> for ChunkedAATreeSet<T, N> {
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		let mut set = Self::new();
+		set.extend(iter);
+		set
+	}
+}
+
+impl<T: Ord, const N: usize> Extend<T> for ChunkedAATreeSet<T, N> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+		for value in iter {
+			self.insert(value);
+		}
+	}
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ChunkedAATreeSet<T, N> {
+	type Item = &'a T;
+	type IntoIter = Iter<'a, T, N>;
+
+	fn into_iter(self) -> Iter<'a, T, N> {
+		self.iter()
+	}
+}
+
+/// The iterator returned by [`ChunkedAATreeSet::iter`].
+pub struct Iter<'a, T, const N: usize> {
+	chunks: AAIter<'a, Chunk<T, N>, &'a Chunk<T, N>>,
+	items: slice::Iter<'a, Option<T>>,
+	len: usize
+}
+
+impl<T, const N: usize> Debug for Iter<'_, T, N> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Iter").finish_non_exhaustive()
+	}
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<&'a T> {
+		loop {
+			if let Some(item) = self.items.next() {
+				self.len -= 1;
+				return item.as_ref();
+			}
+			let chunk = self.chunks.next()?;
+			self.items = chunk.items[.. chunk.len].iter();
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<T, const N: usize> ExactSizeIterator for Iter<'_, T, N> {}
+
+impl<T, const N: usize> FusedIterator for Iter<'_, T, N> {}
+
+#[cfg(test)]
+mod tests {
+	use super::ChunkedAATreeSet;
+	use alloc::{collections::BTreeSet, vec::Vec};
+
+	fn check<const N: usize>() {
+		let mut set = ChunkedAATreeSet::<u32, N>::new();
+		let mut reference = BTreeSet::new();
+		let mut state = 42u32;
+		for step in 0 .. 3000 {
+			state = state.wrapping_mul(1103515245).wrapping_add(12345);
+			let value = (state >> 16) % 500;
+			if step % 3 == 2 {
+				assert_eq!(set.remove(&value), reference.remove(&value));
+			} else {
+				assert_eq!(set.insert(value), reference.insert(value));
+			}
+			assert_eq!(set.contains(&value), reference.contains(&value));
+			if step % 100 == 0 {
+				set.assert_valid();
+			}
+		}
+		set.assert_valid();
+		assert_eq!(set.len(), reference.len());
+		assert_eq!(set.first(), reference.iter().next());
+		assert_eq!(set.last(), reference.iter().next_back());
+		assert!(set.iter().eq(reference.iter()));
+
+		for value in reference {
+			assert!(set.remove(&value));
+		}
+		set.assert_valid();
+		assert!(set.is_empty());
+		assert_eq!(set.iter().next(), None);
+	}
+
+	#[test]
+	fn test_chunked() {
+		check::<1>();
+		check::<2>();
+		check::<3>();
+		check::<8>();
+		check::<16>();
+	}
+
+	#[test]
+	fn test_chunked_sequential() {
+		let set: ChunkedAATreeSet<u32, 4> = (0 .. 100).collect();
+		set.assert_valid();
+		assert_eq!(
+			set.iter().copied().collect::<Vec<_>>(),
+			(0 .. 100).collect::<Vec<_>>()
+		);
+	}
+}
@@ -0,0 +1,222 @@
+//! A multiset that can be iterated in descending order of the counts of its elements.
+
+use crate::{AATreeMap, AATreeSet};
+use core::{
+	borrow::Borrow,
+	cmp::Reverse,
+	fmt::{self, Debug},
+	iter::FromIterator
+};
+
+/// A set that counts how often each element was inserted, and can be iterated in
+/// descending order of these counts.
+///
+/// Internally, this type keeps two trees: One maps every element to its count, and the
+/// other contains all `(count, element)` pairs, ordered by descending count and ascending
+/// element. Every update modifies both trees in O(log n). Elements are cloned only when
+/// they are inserted for the first time.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::CountedSet;
+/// let mut words = CountedSet::new();
+/// for word in "the cat and the dog and the bird".split_whitespace() {
+/// 	words.increment(word);
+/// }
+/// assert_eq!(words.count("the"), 3);
+/// assert_eq!(words.most_common(), Some((&"the", 3)));
+/// assert_eq!(words.iter().collect::<Vec<_>>(), [
+/// 	(&"the", 3),
+/// 	(&"and", 2),
+/// 	(&"bird", 1),
+/// 	(&"cat", 1),
+/// 	(&"dog", 1)
+/// ]);
+/// ```
+#[derive(Clone)]
+pub struct CountedSet<T> {
+	counts: AATreeMap<T, usize>,
+	by_count: AATreeSet<(Reverse<usize>, T)>,
+	total: usize
+}
+
+impl<T> Default for CountedSet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Debug> Debug for CountedSet<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+impl<T> CountedSet<T> {
+	/// Construct a new, empty set.
+	pub const fn new() -> Self {
+		Self {
+			counts: AATreeMap::new(),
+			by_count: AATreeSet::new(),
+			total: 0
+		}
+	}
+
+	/// Returns the number of distinct elements in the set.
+	pub fn len(&self) -> usize {
+		self.counts.len()
+	}
+
+	/// Returns `true` if the set contains no elements.
+	pub fn is_empty(&self) -> bool {
+		self.counts.is_empty()
+	}
+
+	/// Returns the sum of the counts of all elements.
+	pub fn total(&self) -> usize {
+		self.total
+	}
+
+	/// Removes all elements from the set.
+	pub fn clear(&mut self) {
+		self.counts.clear();
+		self.by_count.clear();
+		self.total = 0;
+	}
+
+	/// Creates an iterator that visits all elements and their counts in descending order
+	/// of their counts. Elements with the same count are visited in ascending order.
+	pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+		self.by_count
+			.iter()
+			.map(|(Reverse(count), value)| (value, *count))
+	}
+
+	/// Returns the element with the highest count. If multiple elements share the
+	/// highest count, the smallest of them is returned.
+	pub fn most_common(&self) -> Option<(&T, usize)> {
+		self.iter().next()
+	}
+}
+
+impl<T: Ord> CountedSet<T> {
+	/// Returns the count of `value`, which is zero if it is not part of the set.
+	pub fn count<Q>(&self, value: &Q) -> usize
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.counts.get(value).copied().unwrap_or(0)
+	}
+
+	/// Increments the count of `value` by one. Returns the new count.
+	pub fn increment(&mut self, value: T) -> usize
+	where
+		T: Clone
+	{
+		self.total += 1;
+		if let Some(count) = self.counts.get_mut(&value) {
+			let (_, value) = self
+				.by_count
+				.take(&(Reverse(*count), value))
+				.expect("counts out of sync");
+			*count += 1;
+			self.by_count.insert((Reverse(*count), value));
+			return *count;
+		}
+		self.by_count.insert((Reverse(1), value.clone()));
+		self.counts.insert(value, 1);
+		1
+	}
+
+	/// Decrements the count of `value` by one, and removes it from the set once it
+	/// reaches zero. Returns the new count.
+	pub fn decrement<Q>(&mut self, value: &Q) -> usize
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let (value, count) = match self.counts.remove_entry(value) {
+			Some(entry) => entry,
+			None => return 0
+		};
+		let key = (Reverse(count), value);
+		let (_, other) = self.by_count.take(&key).expect("counts out of sync");
+		let (_, value) = key;
+		self.total -= 1;
+		let count = count - 1;
+		if count > 0 {
+			self.by_count.insert((Reverse(count), other));
+			self.counts.insert(value, count);
+		}
+		count
+	}
+
+	/// Removes `value` from the set, regardless of its count. Returns the count it had.
+	pub fn remove<Q>(&mut self, value: &Q) -> usize
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let (value, count) = match self.counts.remove_entry(value) {
+			Some(entry) => entry,
+			None => return 0
+		};
+		self.by_count.remove(&(Reverse(count), value));
+		self.total -= count;
+		count
+	}
+}
+
+impl<T: Ord + Clone> Extend<T> for CountedSet<T> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+		for value in iter {
+			self.increment(value);
+		}
+	}
+}
+
+impl<T: Ord + Clone> FromIterator<T> for CountedSet<T> {
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		let mut set = Self::new();
+		set.extend(iter);
+		set
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CountedSet;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_counted_set() {
+		let mut set: CountedSet<char> = "abracadabra".chars().collect();
+		assert_eq!(set.len(), 5);
+		assert_eq!(set.total(), 11);
+		let counts: Vec<_> = set.iter().collect();
+		assert_eq!(counts, [
+			(&'a', 5),
+			(&'b', 2),
+			(&'r', 2),
+			(&'c', 1),
+			(&'d', 1)
+		]);
+
+		assert_eq!(set.decrement(&'a'), 4);
+		assert_eq!(set.decrement(&'c'), 0);
+		assert_eq!(set.decrement(&'x'), 0);
+		assert_eq!(set.remove(&'b'), 2);
+		assert_eq!(set.increment('d'), 2);
+		assert_eq!(set.total(), 8);
+		let counts: Vec<_> = set.iter().collect();
+		assert_eq!(counts, [(&'a', 4), (&'d', 2), (&'r', 2)]);
+		assert_eq!(set.count(&'c'), 0);
+		assert_eq!(set.most_common(), Some((&'a', 4)));
+
+		set.clear();
+		assert!(set.is_empty());
+		assert_eq!(set.most_common(), None);
+	}
+}
@@ -0,0 +1,201 @@
+//! Formatting adapters for [`AATreeSet`] and [`AATreeMap`].
+
+use crate::{AATreeMap, AATreeSet};
+use alloc::string::ToString;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// Write the entries of a collection like the `Debug` implementations of [`AATreeSet`]
+/// and [`AATreeMap`], but omit all except the first and last `n` entries.
+fn write_truncated<I, F>(
+	f: &mut Formatter<'_>,
+	(open, close): (&str, &str),
+	iter: I,
+	len: usize,
+	n: usize,
+	mut write_entry: F
+) -> fmt::Result
+where
+	I: Iterator,
+	F: FnMut(&mut Formatter<'_>, I::Item) -> fmt::Result
+{
+	let skipped = len.saturating_sub(n.saturating_mul(2));
+	f.write_str(open)?;
+	for (i, entry) in iter.enumerate() {
+		if skipped > 0 && i >= n && i < len - n {
+			if i == n {
+				if i > 0 {
+					f.write_str(", ")?;
+				}
+				write!(f, "... {skipped} more ...")?;
+			}
+			continue;
+		}
+		if i > 0 {
+			f.write_str(", ")?;
+		}
+		write_entry(f, entry)?;
+	}
+	f.write_str(close)
+}
+
+/// Debug adapter that prints only the first and last entries of a collection. Returned
+/// by [`AATreeSet::debug_truncated`] and [`AATreeMap::debug_truncated`].
+///
+/// This type implements both [`Debug`] and [`Display`] with identical output.
+pub struct DebugTruncated<'a, C> {
+	inner: &'a C,
+	n: usize
+}
+
+impl<'a, C> DebugTruncated<'a, C> {
+	pub(crate) fn new(inner: &'a C, n: usize) -> Self {
+		Self { inner, n }
+	}
+}
+
+impl<C> Debug for DebugTruncated<'_, C>
+where
+	Self: Display
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		Display::fmt(self, f)
+	}
+}
+
+impl<T: Debug> Display for DebugTruncated<'_, AATreeSet<T>> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let set = self.inner;
+		write_truncated(f, ("[", "]"), set.iter(), set.len(), self.n, |f, v| {
+			v.fmt(f)
+		})
+	}
+}
+
+impl<K: Debug, V: Debug> Display for DebugTruncated<'_, AATreeMap<K, V>> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let map = self.inner;
+		write_truncated(f, ("{", "}"), map.iter(), map.len(), self.n, |f, (k, v)| {
+			k.fmt(f)?;
+			f.write_str(": ")?;
+			v.fmt(f)
+		})
+	}
+}
+
+/// Display adapter for sets with a custom separator. Returned by
+/// [`AATreeSet::display_with`].
+pub struct DisplaySet<'a, T> {
+	set: &'a AATreeSet<T>,
+	sep: &'a str
+}
+
+impl<'a, T> DisplaySet<'a, T> {
+	pub(crate) fn new(set: &'a AATreeSet<T>, sep: &'a str) -> Self {
+		Self { set, sep }
+	}
+}
+
+impl<T: Display> Display for DisplaySet<'_, T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		for (i, v) in self.set.iter().enumerate() {
+			if i > 0 {
+				f.write_str(self.sep)?;
+			}
+			v.fmt(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl<T: Display> Debug for DisplaySet<'_, T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{:?}", self.to_string())
+	}
+}
+
+/// Display adapter for maps with custom separators. Returned by
+/// [`AATreeMap::display_with`].
+pub struct DisplayMap<'a, K, V> {
+	map: &'a AATreeMap<K, V>,
+	kv_sep: &'a str,
+	entry_sep: &'a str
+}
+
+impl<'a, K, V> DisplayMap<'a, K, V> {
+	pub(crate) fn new(
+		map: &'a AATreeMap<K, V>,
+		kv_sep: &'a str,
+		entry_sep: &'a str
+	) -> Self {
+		Self {
+			map,
+			kv_sep,
+			entry_sep
+		}
+	}
+}
+
+impl<K: Display, V: Display> Display for DisplayMap<'_, K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		for (i, (k, v)) in self.map.iter().enumerate() {
+			if i > 0 {
+				f.write_str(self.entry_sep)?;
+			}
+			k.fmt(f)?;
+			f.write_str(self.kv_sep)?;
+			v.fmt(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl<K: Display, V: Display> Debug for DisplayMap<'_, K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{:?}", self.to_string())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{AATreeMap, AATreeSet};
+	use alloc::{format, string::ToString};
+
+	#[test]
+	fn test_display_with() {
+		let set = AATreeSet::from(["b", "c", "a"]);
+		assert_eq!(set.display_with(", ").to_string(), "a, b, c");
+		assert_eq!(format!("{:?}", set.display_with("|")), r#""a|b|c""#);
+		assert_eq!(AATreeSet::<u8>::new().display_with(", ").to_string(), "");
+
+		let map = AATreeMap::from([(2, 'b'), (1, 'a')]);
+		assert_eq!(map.display_with("=", ";").to_string(), "1=a;2=b");
+	}
+
+	#[test]
+	fn test_debug_truncated_set() {
+		let set: AATreeSet<u32> = (0 .. 10).collect();
+		assert_eq!(
+			set.debug_truncated(2).to_string(),
+			"[0, 1, ... 6 more ..., 8, 9]"
+		);
+		assert_eq!(set.debug_truncated(0).to_string(), "[... 10 more ...]");
+		assert_eq!(set.debug_truncated(5).to_string(), format!("{set:?}"));
+		assert_eq!(
+			set.debug_truncated(usize::MAX).to_string(),
+			format!("{set:?}")
+		);
+	}
+
+	#[test]
+	fn test_debug_truncated_map() {
+		let map: AATreeMap<u32, char> = (0 .. 5).zip('a' ..).collect();
+		assert_eq!(
+			format!("{:?}", map.debug_truncated(1)),
+			"{0: 'a', ... 3 more ..., 4: 'e'}"
+		);
+		assert_eq!(
+			AATreeMap::<u8, u8>::new().debug_truncated(1).to_string(),
+			"{}"
+		);
+	}
+}
@@ -0,0 +1,372 @@
+//! A binary encoding of [`FrozenAATreeMap`] that can be queried in place, e.g. after
+//! memory-mapping a file, without deserializing it first.
+//!
+//! The encoding consists of a 24 byte header, followed by all keys and then all values,
+//! each encoded with a fixed size using their [`Codec`]. The header contains the magic
+//! bytes `AAFZ`, the format version, the encoded sizes of keys and values, and the
+//! number of entries, with all integers in little endian.
+//!
+//! The encoding is a stable format: Encodings written by an older version of this crate
+//! can always be read by newer versions. Incompatible changes increment
+//! [`FORMAT_VERSION`].
+
+use super::FrozenAATreeMap;
+use crate::sorted::SortedIterator;
+use alloc::vec::Vec;
+use core::{
+	convert::TryInto,
+	fmt::{self, Debug, Display, Formatter},
+	iter::FusedIterator,
+	marker::PhantomData,
+	ops::{Bound, Range, RangeBounds}
+};
+
+const MAGIC: [u8; 4] = *b"AAFZ";
+/// The version of the encoding written by [`FrozenAATreeMap::to_bytes`].
+/// [`FrozenView::from_bytes`] accepts all versions up to and including this one.
+pub const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = 24;
+
+/// A fixed-size binary encoding for keys and values of a [`FrozenView`].
+pub trait Codec: Sized {
+	/// The number of bytes of the encoding.
+	const SIZE: usize;
+
+	/// Encode `self` into `buf`, which is exactly [`SIZE`](Self::SIZE) bytes long.
+	fn encode(&self, buf: &mut [u8]);
+
+	/// Decode a value from `buf`, which is exactly [`SIZE`](Self::SIZE) bytes long.
+	fn decode(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_codec {
+	($($ty:ty),*) => {
+		$(
+			impl Codec for $ty {
+				const SIZE: usize = core::mem::size_of::<$ty>();
+
+				fn encode(&self, buf: &mut [u8]) {
+					buf.copy_from_slice(&self.to_le_bytes());
+				}
+
+				fn decode(buf: &[u8]) -> Self {
+					Self::from_le_bytes(buf.try_into().unwrap())
+				}
+			}
+		)*
+	};
+}
+
+impl_codec!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl<const N: usize> Codec for [u8; N] {
+	const SIZE: usize = N;
+
+	fn encode(&self, buf: &mut [u8]) {
+		buf.copy_from_slice(self);
+	}
+
+	fn decode(buf: &[u8]) -> Self {
+		buf.try_into().unwrap()
+	}
+}
+
+/// The error returned when the bytes passed to [`FrozenView::from_bytes`] are not a valid
+/// encoding.
+#[derive(Debug)]
+pub struct FromBytesError(&'static str);
+
+impl Display for FromBytesError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "Invalid frozen map encoding: {}", self.0)
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromBytesError {}
+
+impl<K: Codec, V: Codec> FrozenAATreeMap<K, V> {
+	/// Encode this map so that it can be queried in place using [`FrozenView`].
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{frozen::FrozenView, AATreeMap};
+	/// let map = AATreeMap::from([(1u32, 10u64), (2, 20), (3, 30)]).freeze();
+	/// let bytes = map.to_bytes();
+	///
+	/// let view = FrozenView::<u32, u64>::from_bytes(&bytes).unwrap();
+	/// assert_eq!(view.get(&2), Some(20));
+	/// assert_eq!(view.range(2 ..).collect::<Vec<_>>(), [(2, 20), (3, 30)]);
+	/// assert_eq!(view.to_frozen(), map);
+	/// ```
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let len = self.len();
+		let mut buf = Vec::with_capacity(HEADER_LEN + len * (K::SIZE + V::SIZE));
+		buf.extend_from_slice(&MAGIC);
+		buf.extend_from_slice(&[FORMAT_VERSION, 0, 0, 0]);
+		buf.extend_from_slice(&(K::SIZE as u32).to_le_bytes());
+		buf.extend_from_slice(&(V::SIZE as u32).to_le_bytes());
+		buf.extend_from_slice(&(len as u64).to_le_bytes());
+		encode_all(&mut buf, self.keys());
+		encode_all(&mut buf, self.values());
+		buf
+	}
+}
+
+fn encode_all<T: Codec>(buf: &mut Vec<u8>, items: &[T]) {
+	let start = buf.len();
+	buf.resize(start + items.len() * T::SIZE, 0);
+	if T::SIZE == 0 {
+		return;
+	}
+	for (item, chunk) in items.iter().zip(buf[start ..].chunks_exact_mut(T::SIZE)) {
+		item.encode(chunk);
+	}
+}
+
+/// A read-only view of a [`FrozenAATreeMap`] that was encoded using
+/// [`to_bytes`](FrozenAATreeMap::to_bytes). All keys and values are decoded on demand.
+///
+/// Only the structure of the encoding is validated when the view is created. If the keys
+/// are not sorted, lookups return unspecified results, but never panic.
+pub struct FrozenView<'a, K, V> {
+	keys: &'a [u8],
+	values: &'a [u8],
+	len: usize,
+	_ty: PhantomData<(K, V)>
+}
+
+impl<K, V> Clone for FrozenView<'_, K, V> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<K, V> Copy for FrozenView<'_, K, V> {}
+
+impl<K: Codec + Debug, V: Codec + Debug> Debug for FrozenView<'_, K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+/// Returns the format version of an encoded map, or an error if the bytes do not start
+/// with a header.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::{frozen::{self, FORMAT_VERSION}, AATreeMap};
+/// let bytes = AATreeMap::from([(1u8, 1u8)]).freeze().to_bytes();
+/// assert_eq!(frozen::format_version(&bytes).unwrap(), FORMAT_VERSION);
+/// ```
+pub fn format_version(bytes: &[u8]) -> Result<u8, FromBytesError> {
+	if bytes.len() < HEADER_LEN {
+		return Err(FromBytesError("missing header"));
+	}
+	if bytes[0 .. 4] != MAGIC {
+		return Err(FromBytesError("invalid magic bytes"));
+	}
+	Ok(bytes[4])
+}
+
+impl<'a, K: Codec, V: Codec> FrozenView<'a, K, V> {
+	/// Create a view of an encoded map, validating its header.
+	pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, FromBytesError> {
+		let version = format_version(bytes)?;
+		if !(1 ..= FORMAT_VERSION).contains(&version) {
+			return Err(FromBytesError("unsupported version"));
+		}
+		let (header, body) = bytes.split_at(HEADER_LEN);
+		let read_u32 =
+			|i: usize| u32::from_le_bytes(header[i .. i + 4].try_into().unwrap());
+		if read_u32(8) as usize != K::SIZE {
+			return Err(FromBytesError("key size does not match codec"));
+		}
+		if read_u32(12) as usize != V::SIZE {
+			return Err(FromBytesError("value size does not match codec"));
+		}
+		let len = u64::from_le_bytes(header[16 .. 24].try_into().unwrap());
+		let len: usize = len
+			.try_into()
+			.map_err(|_| FromBytesError("length too large"))?;
+		let keys_len = len
+			.checked_mul(K::SIZE)
+			.ok_or(FromBytesError("length too large"))?;
+		let values_len = len
+			.checked_mul(V::SIZE)
+			.ok_or(FromBytesError("length too large"))?;
+		if keys_len.checked_add(values_len) != Some(body.len()) {
+			return Err(FromBytesError("length does not match header"));
+		}
+		let (keys, values) = body.split_at(keys_len);
+		Ok(Self {
+			keys,
+			values,
+			len,
+			_ty: PhantomData
+		})
+	}
+
+	/// Returns the number of elements in the map.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns `true` if the map contains no elements.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	fn key_at(&self, idx: usize) -> K {
+		K::decode(&self.keys[idx * K::SIZE .. (idx + 1) * K::SIZE])
+	}
+
+	fn value_at(&self, idx: usize) -> V {
+		V::decode(&self.values[idx * V::SIZE .. (idx + 1) * V::SIZE])
+	}
+
+	/// Decode all entries into a [`FrozenAATreeMap`].
+	pub fn to_frozen(&self) -> FrozenAATreeMap<K, V> {
+		FrozenAATreeMap::from_sorted_unchecked(self.iter())
+	}
+
+	/// Creates an iterator over this map that visits all entries with the keys in
+	/// ascending order.
+	pub fn iter(&self) -> ViewIter<'a, K, V> {
+		ViewIter {
+			view: *self,
+			idx: 0 .. self.len
+		}
+	}
+}
+
+impl<'a, K: Codec + Ord, V: Codec> FrozenView<'a, K, V> {
+	/// Returns the index of the first key for which `pred` returns `false`.
+	fn partition_point<P>(&self, mut pred: P) -> usize
+	where
+		P: FnMut(&K) -> bool
+	{
+		let (mut lo, mut hi) = (0, self.len);
+		while lo < hi {
+			let mid = lo + (hi - lo) / 2;
+			if pred(&self.key_at(mid)) {
+				lo = mid + 1;
+			} else {
+				hi = mid;
+			}
+		}
+		lo
+	}
+
+	/// Returns the value corresponding to the key.
+	pub fn get(&self, key: &K) -> Option<V> {
+		let idx = self.partition_point(|k| k < key);
+		(idx < self.len && &self.key_at(idx) == key).then(|| self.value_at(idx))
+	}
+
+	/// Check if a key is contained within this map.
+	pub fn contains_key(&self, key: &K) -> bool {
+		let idx = self.partition_point(|k| k < key);
+		idx < self.len && &self.key_at(idx) == key
+	}
+
+	/// Creates an iterator over all entries with keys within `range`, in ascending order.
+	pub fn range<R>(&self, range: R) -> ViewIter<'a, K, V>
+	where
+		R: RangeBounds<K>
+	{
+		let start = match range.start_bound() {
+			Bound::Included(q) => self.partition_point(|k| k < q),
+			Bound::Excluded(q) => self.partition_point(|k| k <= q),
+			Bound::Unbounded => 0
+		};
+		let end = match range.end_bound() {
+			Bound::Included(q) => self.partition_point(|k| k <= q),
+			Bound::Excluded(q) => self.partition_point(|k| k < q),
+			Bound::Unbounded => self.len
+		};
+		ViewIter {
+			view: *self,
+			idx: start .. end.max(start)
+		}
+	}
+}
+
+/// An iterator over the decoded entries of a [`FrozenView`].
+pub struct ViewIter<'a, K, V> {
+	view: FrozenView<'a, K, V>,
+	idx: Range<usize>
+}
+
+impl<K, V> Debug for ViewIter<'_, K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ViewIter").field("idx", &self.idx).finish()
+	}
+}
+
+impl<K: Codec, V: Codec> Iterator for ViewIter<'_, K, V> {
+	type Item = (K, V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let idx = self.idx.next()?;
+		Some((self.view.key_at(idx), self.view.value_at(idx)))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.idx.size_hint()
+	}
+}
+
+impl<K: Codec, V: Codec> DoubleEndedIterator for ViewIter<'_, K, V> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		let idx = self.idx.next_back()?;
+		Some((self.view.key_at(idx), self.view.value_at(idx)))
+	}
+}
+
+impl<K: Codec, V: Codec> ExactSizeIterator for ViewIter<'_, K, V> {}
+
+impl<K: Codec, V: Codec> FusedIterator for ViewIter<'_, K, V> {}
+
+impl<K: Codec, V: Codec> SortedIterator for ViewIter<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+	use super::FrozenView;
+	use crate::FrozenAATreeMap;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_roundtrip() {
+		let map: FrozenAATreeMap<i64, [u8; 3]> =
+			(-50 .. 50).map(|i| (i * 3, [i as u8, 0, 1])).collect();
+		let bytes = map.to_bytes();
+		let view = FrozenView::<i64, [u8; 3]>::from_bytes(&bytes).unwrap();
+		assert_eq!(view.len(), 100);
+		assert_eq!(view.to_frozen(), map);
+		for k in -160 .. 160 {
+			assert_eq!(view.get(&k), map.get(&k).copied());
+			assert_eq!(
+				view.range(k .. k + 10).collect::<Vec<_>>(),
+				map.range(k .. k + 10)
+					.map(|(k, v)| (*k, *v))
+					.collect::<Vec<_>>()
+			);
+		}
+	}
+
+	#[test]
+	fn test_invalid() {
+		let bytes = FrozenAATreeMap::<u32, u8>::from_iter([(1, 2), (3, 4)]).to_bytes();
+		assert!(FrozenView::<u32, u8>::from_bytes(&bytes).is_ok());
+		assert!(FrozenView::<u64, u8>::from_bytes(&bytes).is_err());
+		assert!(FrozenView::<u32, u16>::from_bytes(&bytes).is_err());
+		assert!(FrozenView::<u32, u8>::from_bytes(&bytes[.. bytes.len() - 1]).is_err());
+		assert!(FrozenView::<u32, u8>::from_bytes(&bytes[.. 10]).is_err());
+		let mut bytes = bytes;
+		bytes[0] = b'X';
+		assert!(FrozenView::<u32, u8>::from_bytes(&bytes).is_err());
+	}
+}
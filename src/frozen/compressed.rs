@@ -0,0 +1,483 @@
+//! A frozen map for byte-like keys that stores its keys front-coded.
+//!
+//! The keys are split into blocks of [`BLOCK_LEN`] consecutive keys. The first key of
+//! every block is stored in full, and its offset is recorded in a sampled index that is
+//! used for binary search. Every other key only stores the length of the prefix it shares
+//! with the previous key, followed by the remaining suffix. All lengths are encoded as
+//! LEB128 varints.
+
+use super::FrozenAATreeMap;
+use crate::{sorted::SortedIterator, AATreeMap};
+use alloc::{string::String, vec::Vec};
+use core::{
+	fmt::{self, Debug, Formatter},
+	iter::FusedIterator,
+	marker::PhantomData,
+	mem, slice
+};
+
+/// The number of keys in every block. Only the first key of a block is stored in full.
+pub const BLOCK_LEN: usize = 16;
+
+/// A key that is a sequence of bytes and ordered like one, so that it can be stored in a
+/// [`CompressedFrozenMap`].
+pub trait ByteKey: Ord + Sized {
+	/// Returns the bytes of this key. Comparing the bytes must yield the same order as
+	/// comparing the keys.
+	fn as_bytes(&self) -> &[u8];
+
+	/// Reconstruct a key from the bytes returned by [`as_bytes`](Self::as_bytes).
+	fn from_bytes(bytes: Vec<u8>) -> Self;
+}
+
+impl ByteKey for Vec<u8> {
+	fn as_bytes(&self) -> &[u8] {
+		self
+	}
+
+	fn from_bytes(bytes: Vec<u8>) -> Self {
+		bytes
+	}
+}
+
+impl ByteKey for String {
+	fn as_bytes(&self) -> &[u8] {
+		self.as_bytes()
+	}
+
+	fn from_bytes(bytes: Vec<u8>) -> Self {
+		String::from_utf8(bytes).expect("front-coded key is not valid UTF-8")
+	}
+}
+
+/// An immutable map like [`FrozenAATreeMap`] whose byte-like keys are stored with
+/// shared-prefix compression, which saves a lot of memory for keys like URLs or paths.
+///
+/// Lookups perform a binary search over the first key of every block, followed by a
+/// linear scan over at most [`BLOCK_LEN`] keys of one block, which compares every byte
+/// of the query at most once. The keys are no longer stored individually, so they are
+/// reconstructed when iterating over the map.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::AATreeMap;
+/// let map: AATreeMap<String, usize> = (0 .. 100)
+/// 	.map(|i| (format!("https://example.org/users/{i}/profile"), i))
+/// 	.collect();
+/// let compressed = map.freeze_compressed();
+/// assert_eq!(
+/// 	compressed.get("https://example.org/users/42/profile"),
+/// 	Some(&42)
+/// );
+/// assert_eq!(compressed.get("https://example.org/users/42"), None);
+///
+/// let stats = compressed.memory_stats();
+/// assert!(stats.compression_ratio() < 0.5);
+/// ```
+#[derive(Clone)]
+pub struct CompressedFrozenMap<K, V> {
+	/// The front-coded keys.
+	data: Vec<u8>,
+	/// The offset of the first key of every block in `data`.
+	blocks: Vec<usize>,
+	values: Vec<V>,
+	/// The total length of all keys before compression.
+	key_bytes: usize,
+	_key: PhantomData<K>
+}
+
+/// The memory used by the keys of a [`CompressedFrozenMap`], returned by
+/// [`memory_stats`](CompressedFrozenMap::memory_stats).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemoryStats {
+	key_bytes: usize,
+	compressed_key_bytes: usize,
+	index_bytes: usize
+}
+
+impl MemoryStats {
+	/// Returns the total length of all keys without compression, not counting the
+	/// overhead of storing every key in its own allocation.
+	pub fn key_bytes(&self) -> usize {
+		self.key_bytes
+	}
+
+	/// Returns the length of the front-coded keys, including the encoded lengths.
+	pub fn compressed_key_bytes(&self) -> usize {
+		self.compressed_key_bytes
+	}
+
+	/// Returns the size of the sampled index of the first key of every block.
+	pub fn index_bytes(&self) -> usize {
+		self.index_bytes
+	}
+
+	/// Returns the memory used by the compressed keys and the index, divided by the
+	/// total length of all keys. If there are no key bytes at all, i.e. the map is empty
+	/// or all keys are empty, this returns `1.0` instead of dividing by zero.
+	pub fn compression_ratio(&self) -> f64 {
+		if self.key_bytes == 0 {
+			return 1.0;
+		}
+		(self.compressed_key_bytes + self.index_bytes) as f64 / self.key_bytes as f64
+	}
+}
+
+impl<K: ByteKey, V> AATreeMap<K, V> {
+	/// Turn this map into an immutable [`CompressedFrozenMap`] in O(n), which stores its
+	/// keys with shared-prefix compression.
+	pub fn freeze_compressed(self) -> CompressedFrozenMap<K, V> {
+		CompressedFrozenMap::from_sorted_unchecked(self)
+	}
+}
+
+impl<K: ByteKey, V> FrozenAATreeMap<K, V> {
+	/// Compress the keys of this map into a [`CompressedFrozenMap`] in O(n).
+	pub fn compress(self) -> CompressedFrozenMap<K, V> {
+		CompressedFrozenMap::from_sorted_unchecked(self)
+	}
+}
+
+impl<K, V> CompressedFrozenMap<K, V> {
+	/// Returns the number of elements in the map.
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	/// Returns `true` if the map contains no elements.
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+
+	/// Returns all values of this map as a slice, in the order of their keys.
+	pub fn values(&self) -> &[V] {
+		&self.values
+	}
+
+	/// Returns the memory used by the keys of this map, with and without compression.
+	pub fn memory_stats(&self) -> MemoryStats {
+		MemoryStats {
+			key_bytes: self.key_bytes,
+			compressed_key_bytes: self.data.len(),
+			index_bytes: self.blocks.len() * mem::size_of::<usize>()
+		}
+	}
+
+	fn decoder(&self) -> Decoder<'_> {
+		Decoder {
+			data: &self.data,
+			buf: Vec::new(),
+			idx: 0
+		}
+	}
+}
+
+impl<K: ByteKey, V> CompressedFrozenMap<K, V> {
+	/// Build a map from entries with strictly ascending keys.
+	fn from_sorted_unchecked<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = (K, V)>
+	{
+		let mut data = Vec::new();
+		let mut blocks = Vec::new();
+		let mut values = Vec::new();
+		let mut key_bytes = 0;
+		let mut prev = Vec::new();
+		for (idx, (key, value)) in iter.into_iter().enumerate() {
+			let key = key.as_bytes();
+			key_bytes += key.len();
+			let shared = if idx % BLOCK_LEN == 0 {
+				blocks.push(data.len());
+				0
+			} else {
+				let shared = common_prefix(&prev, key);
+				write_varint(&mut data, shared);
+				shared
+			};
+			write_varint(&mut data, key.len() - shared);
+			data.extend_from_slice(&key[shared ..]);
+			prev.clear();
+			prev.extend_from_slice(key);
+			values.push(value);
+		}
+		Self {
+			data,
+			blocks,
+			values,
+			key_bytes,
+			_key: PhantomData
+		}
+	}
+
+	/// Returns the index of the entry with the key `key`.
+	fn search(&self, key: &[u8]) -> Option<usize> {
+		let block = self
+			.blocks
+			.partition_point(|&offset| read_bytes(&self.data, offset).0 <= key)
+			.checked_sub(1)?;
+		let (first, mut offset) = read_bytes(&self.data, self.blocks[block]);
+		// the length of the common prefix of the current key and `key`
+		let mut lcp = common_prefix(first, key);
+		if lcp == first.len() && lcp == key.len() {
+			return Some(block * BLOCK_LEN);
+		}
+
+		let end = self.len().min((block + 1) * BLOCK_LEN);
+		for idx in block * BLOCK_LEN + 1 .. end {
+			let (shared, next) = read_varint(&self.data, offset);
+			let (suffix, next) = read_bytes(&self.data, next);
+			offset = next;
+			// the current key is smaller than `key`, so if it shares more than `lcp` bytes
+			// with the previous key, it is also smaller than `key`, and if it shares less,
+			// it is greater than `key`
+			if shared > lcp {
+				continue;
+			}
+			if shared < lcp {
+				return None;
+			}
+			let rest = &key[lcp ..];
+			let common = common_prefix(suffix, rest);
+			lcp += common;
+			match (suffix.get(common), rest.get(common)) {
+				(None, None) => return Some(idx),
+				(Some(a), Some(b)) if a < b => {},
+				(None, Some(_)) => {},
+				_ => return None
+			}
+		}
+		None
+	}
+
+	/// Returns a reference to the value corresponding to the key.
+	pub fn get<Q>(&self, key: &Q) -> Option<&V>
+	where
+		Q: AsRef<[u8]> + ?Sized
+	{
+		self.values.get(self.search(key.as_ref())?)
+	}
+
+	/// Check if a key is contained within this map.
+	pub fn contains_key<Q>(&self, key: &Q) -> bool
+	where
+		Q: AsRef<[u8]> + ?Sized
+	{
+		self.search(key.as_ref()).is_some()
+	}
+
+	/// Creates an iterator over this map that visits all entries with the keys in
+	/// ascending order. Every key is reconstructed into a new allocation.
+	pub fn iter(&self) -> CompressedIter<'_, K, V> {
+		CompressedIter {
+			keys: self.decoder(),
+			values: self.values.iter(),
+			_key: PhantomData
+		}
+	}
+
+	/// Decompress this map back into a [`FrozenAATreeMap`] in O(n).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(String::from("a"), 1), (String::from("ab"), 2)]).freeze();
+	/// assert_eq!(map.clone().compress().decompress(), map);
+	/// ```
+	pub fn decompress(self) -> FrozenAATreeMap<K, V> {
+		let mut keys = Decoder {
+			data: &self.data,
+			buf: Vec::new(),
+			idx: 0
+		};
+		let entries = self.values.into_iter().map(|value| {
+			let key = keys.next().expect("missing key");
+			(K::from_bytes(key.to_vec()), value)
+		});
+		FrozenAATreeMap::from_sorted_unchecked(entries)
+	}
+}
+
+impl<K: ByteKey + Debug, V: Debug> Debug for CompressedFrozenMap<K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+impl<'a, K: ByteKey, V> IntoIterator for &'a CompressedFrozenMap<K, V> {
+	type Item = (K, &'a V);
+	type IntoIter = CompressedIter<'a, K, V>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// Reconstructs the front-coded keys one after another.
+#[derive(Clone)]
+struct Decoder<'a> {
+	data: &'a [u8],
+	/// The previous key.
+	buf: Vec<u8>,
+	/// The index of the next key.
+	idx: usize
+}
+
+impl Decoder<'_> {
+	fn next(&mut self) -> Option<&[u8]> {
+		if self.data.is_empty() {
+			return None;
+		}
+		let mut offset = 0;
+		let shared = if self.idx % BLOCK_LEN == 0 {
+			0
+		} else {
+			let (shared, next) = read_varint(self.data, offset);
+			offset = next;
+			shared
+		};
+		let (suffix, next) = read_bytes(self.data, offset);
+		self.buf.truncate(shared);
+		self.buf.extend_from_slice(suffix);
+		self.data = &self.data[next ..];
+		self.idx += 1;
+		Some(&self.buf)
+	}
+}
+
+/// An iterator over the entries of a [`CompressedFrozenMap`].
+pub struct CompressedIter<'a, K, V> {
+	keys: Decoder<'a>,
+	values: slice::Iter<'a, V>,
+	_key: PhantomData<fn() -> K>
+}
+
+impl<K, V> Clone for CompressedIter<'_, K, V> {
+	fn clone(&self) -> Self {
+		Self {
+			keys: self.keys.clone(),
+			values: self.values.clone(),
+			_key: PhantomData
+		}
+	}
+}
+
+impl<K, V> Debug for CompressedIter<'_, K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CompressedIter")
+			.field("remaining", &self.values.len())
+			.finish_non_exhaustive()
+	}
+}
+
+impl<'a, K: ByteKey, V> Iterator for CompressedIter<'a, K, V> {
+	type Item = (K, &'a V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let value = self.values.next()?;
+		let key = self.keys.next().expect("missing key");
+		Some((K::from_bytes(key.to_vec()), value))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.values.size_hint()
+	}
+}
+
+impl<K: ByteKey, V> ExactSizeIterator for CompressedIter<'_, K, V> {}
+
+impl<K: ByteKey, V> FusedIterator for CompressedIter<'_, K, V> {}
+
+impl<K: ByteKey, V> SortedIterator for CompressedIter<'_, K, V> {}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+	a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: usize) {
+	while value >= 0x80 {
+		data.push(value as u8 | 0x80);
+		value >>= 7;
+	}
+	data.push(value as u8);
+}
+
+/// Read a varint at `offset` and return it together with the offset after it.
+fn read_varint(data: &[u8], mut offset: usize) -> (usize, usize) {
+	let mut value = 0;
+	let mut shift = 0;
+	loop {
+		let byte = data[offset];
+		offset += 1;
+		value |= ((byte & 0x7F) as usize) << shift;
+		if byte < 0x80 {
+			return (value, offset);
+		}
+		shift += 7;
+	}
+}
+
+/// Read a length-prefixed byte string at `offset` and return it together with the offset
+/// after it.
+fn read_bytes(data: &[u8], offset: usize) -> (&[u8], usize) {
+	let (len, offset) = read_varint(data, offset);
+	(&data[offset .. offset + len], offset + len)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::BLOCK_LEN;
+	use crate::AATreeMap;
+	use alloc::{format, string::String, vec::Vec};
+
+	#[test]
+	fn test_lookups() {
+		for len in [0, 1, 2, BLOCK_LEN - 1, BLOCK_LEN, BLOCK_LEN + 1, 300] {
+			let map: AATreeMap<String, usize> = (0 .. len)
+				.map(|i| (format!("/usr/share/{}/{}", i % 7, i), i))
+				.collect();
+			let frozen = map.clone().freeze();
+			let compressed = map.freeze_compressed();
+			assert_eq!(compressed.len(), len);
+
+			for (key, value) in &frozen {
+				assert_eq!(compressed.get(key.as_str()), Some(value), "{key}");
+				let prefix = &key[.. key.len() - 1];
+				assert_eq!(compressed.get(prefix), frozen.get(prefix));
+				let longer = format!("{key}0");
+				assert_eq!(compressed.get(&longer), frozen.get(&longer));
+			}
+			for missing in ["", "/", "/usr/share/3/", "/usr/share/9", "~"] {
+				assert!(!compressed.contains_key(missing), "{missing}");
+			}
+
+			let entries: Vec<_> = compressed.iter().collect();
+			assert!(entries.iter().map(|(k, v)| (k, *v)).eq(frozen.iter()));
+			assert_eq!(compressed.decompress(), frozen);
+		}
+	}
+
+	#[test]
+	fn test_varint_lengths() {
+		let long = alloc::vec![b'x'; 1000];
+		let map = AATreeMap::from([
+			(Vec::new(), 0),
+			(long.clone(), 1),
+			([&long[..], b"y"].concat(), 2)
+		]);
+		let compressed = map.clone().freeze_compressed();
+		assert_eq!(compressed.get(&long), Some(&1));
+		assert_eq!(compressed.get(&[]), Some(&0));
+		assert_eq!(compressed.decompress(), map.freeze());
+	}
+
+	#[test]
+	fn test_compression_ratio_without_key_bytes() {
+		let empty = AATreeMap::<String, ()>::new().freeze_compressed();
+		assert_eq!(empty.memory_stats().compression_ratio(), 1.0);
+		let empty_key = AATreeMap::from([(String::new(), ())]).freeze_compressed();
+		assert_eq!(empty_key.memory_stats().key_bytes(), 0);
+		assert_eq!(empty_key.memory_stats().compression_ratio(), 1.0);
+	}
+}
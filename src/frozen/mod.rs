@@ -0,0 +1,514 @@
+//! An immutable, read-optimized map that stores its entries in sorted arrays.
+
+use crate::{search::lower_bound_by, sorted::SortedIterator, AATreeMap};
+use alloc::vec::{self, Vec};
+use core::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt::{self, Debug},
+	hash::{Hash, Hasher},
+	iter::{FromIterator, FusedIterator},
+	ops::{Bound, RangeBounds},
+	slice
+};
+
+mod bytes;
+mod compressed;
+mod veb;
+
+pub use bytes::{
+	format_version, Codec, FromBytesError, FrozenView, ViewIter, FORMAT_VERSION
+};
+pub use compressed::{
+	ByteKey, CompressedFrozenMap, CompressedIter, MemoryStats, BLOCK_LEN
+};
+pub use veb::Layout;
+
+use veb::VebIndex;
+
+/// An immutable map that stores its keys and values in two contiguous sorted arrays.
+///
+/// Compared to [`AATreeMap`], this type does not need to store any child pointers or
+/// levels, and lookups are performed using binary search over the densely packed keys,
+/// which is much more cache friendly. Use it for read-only phases where the map is not
+/// modified anymore. It can be created using [`AATreeMap::freeze`] and be turned back
+/// into an [`AATreeMap`] in O(n) using [`thaw`](Self::thaw).
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::AATreeMap;
+/// let mut map = AATreeMap::new();
+/// map.insert(3, "c");
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+///
+/// let frozen = map.freeze();
+/// assert_eq!(frozen.get(&2), Some(&"b"));
+/// assert_eq!(frozen.range(2 ..).collect::<Vec<_>>(), [
+/// 	(&2, &"b"),
+/// 	(&3, &"c")
+/// ]);
+///
+/// let mut map = frozen.thaw();
+/// map.insert(4, "d");
+/// assert_eq!(map.len(), 4);
+/// ```
+///
+/// For large maps, lookups can be made more cache friendly by choosing a different
+/// [`Layout`] with [`AATreeMap::freeze_with_layout`].
+#[derive(Clone)]
+pub struct FrozenAATreeMap<K, V> {
+	keys: Vec<K>,
+	values: Vec<V>,
+	/// The search index if the layout is [`Layout::VebPacked`].
+	index: Option<VebIndex<K>>
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for FrozenAATreeMap<K, V> {
+	fn eq(&self, other: &Self) -> bool {
+		self.keys == other.keys && self.values == other.values
+	}
+}
+
+impl<K: Eq, V: Eq> Eq for FrozenAATreeMap<K, V> {}
+
+impl<K: PartialOrd, V: PartialOrd> PartialOrd for FrozenAATreeMap<K, V> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		match self.keys.partial_cmp(&other.keys)? {
+			Ordering::Equal => self.values.partial_cmp(&other.values),
+			ord => Some(ord)
+		}
+	}
+}
+
+impl<K: Ord, V: Ord> Ord for FrozenAATreeMap<K, V> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.keys
+			.cmp(&other.keys)
+			.then_with(|| self.values.cmp(&other.values))
+	}
+}
+
+impl<K: Hash, V: Hash> Hash for FrozenAATreeMap<K, V> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.keys.hash(state);
+		self.values.hash(state);
+	}
+}
+
+impl<K, V> Default for FrozenAATreeMap<K, V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K: Debug, V: Debug> Debug for FrozenAATreeMap<K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+impl<K, V> AATreeMap<K, V> {
+	/// Turn this map into an immutable [`FrozenAATreeMap`] in O(n).
+	pub fn freeze(self) -> FrozenAATreeMap<K, V> {
+		FrozenAATreeMap::from_sorted_unchecked(self)
+	}
+
+	/// Turn this map into an immutable [`FrozenAATreeMap`] that uses `layout` for its
+	/// lookups. Building the [`Layout::VebPacked`] index takes O(n) and clones every key
+	/// once.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{frozen::Layout, AATreeMap};
+	/// let map: AATreeMap<u32, u32> = (0 .. 1000).map(|x| (x * 2, x)).collect();
+	/// let frozen = map.freeze_with_layout(Layout::VebPacked);
+	/// assert_eq!(frozen.layout(), Layout::VebPacked);
+	/// assert_eq!(frozen.get(&500), Some(&250));
+	/// assert_eq!(frozen.get(&501), None);
+	/// assert_eq!(frozen.first_key_value_at_or_after(&501), Some((&502, &251)));
+	/// ```
+	pub fn freeze_with_layout(self, layout: Layout) -> FrozenAATreeMap<K, V>
+	where
+		K: Clone
+	{
+		self.freeze().with_layout(layout)
+	}
+}
+
+impl<K, V> FrozenAATreeMap<K, V> {
+	/// Construct a new, empty frozen map. This is mostly useful for `static` and `const`
+	/// items.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::FrozenAATreeMap;
+	/// static EMPTY: FrozenAATreeMap<u32, &str> = FrozenAATreeMap::new();
+	/// assert!(EMPTY.is_empty());
+	/// ```
+	pub const fn new() -> Self {
+		Self {
+			keys: Vec::new(),
+			values: Vec::new(),
+			index: None
+		}
+	}
+
+	/// Returns the [`Layout`] used for lookups in this map.
+	pub fn layout(&self) -> Layout {
+		match self.index {
+			Some(_) => Layout::VebPacked,
+			None => Layout::Sorted
+		}
+	}
+
+	/// Change the [`Layout`] used for lookups in this map. This does not change the
+	/// order of the entries.
+	pub fn with_layout(mut self, layout: Layout) -> Self
+	where
+		K: Clone
+	{
+		self.index = match layout {
+			Layout::Sorted => None,
+			Layout::VebPacked => Some(VebIndex::new(&self.keys))
+		};
+		self
+	}
+
+	/// Returns the number of elements in the map.
+	pub fn len(&self) -> usize {
+		self.keys.len()
+	}
+
+	/// Returns `true` if the map contains no elements.
+	pub fn is_empty(&self) -> bool {
+		self.keys.is_empty()
+	}
+
+	/// Turn this map back into an [`AATreeMap`] in O(n).
+	pub fn thaw(self) -> AATreeMap<K, V> {
+		let len = self.len();
+		AATreeMap::from_sorted_unchecked(self, len)
+	}
+
+	/// Creates an iterator over this map that visits all entries with the keys in
+	/// ascending order.
+	pub fn iter(&self) -> Iter<'_, K, V> {
+		Iter {
+			keys: self.keys.iter(),
+			values: self.values.iter()
+		}
+	}
+
+	/// Returns all keys of this map as a sorted slice.
+	pub fn keys(&self) -> &[K] {
+		&self.keys
+	}
+
+	/// Returns all values of this map as a slice, in the order of their keys.
+	pub fn values(&self) -> &[V] {
+		&self.values
+	}
+
+	/// Returns a reference to the first entry (that is, with the smallest key) in the map.
+	pub fn first_key_value(&self) -> Option<(&K, &V)> {
+		self.entry_at(0)
+	}
+
+	/// Returns a reference to the last entry (that is, with the largest key) in the map.
+	pub fn last_key_value(&self) -> Option<(&K, &V)> {
+		self.entry_at(self.len().checked_sub(1)?)
+	}
+
+	/// Build a map from entries with strictly ascending keys.
+	pub(crate) fn from_sorted_unchecked<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = (K, V)>
+	{
+		let (keys, values) = iter.into_iter().unzip();
+		Self {
+			keys,
+			values,
+			index: None
+		}
+	}
+
+	fn entry_at(&self, idx: usize) -> Option<(&K, &V)> {
+		Some((self.keys.get(idx)?, self.values.get(idx)?))
+	}
+}
+
+impl<K: Ord, V> FrozenAATreeMap<K, V> {
+	fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let idx = match &self.index {
+			Some(index) => index.lower_bound(key),
+			None => lower_bound_by(&self.keys, |k| k.borrow() < key)
+		};
+		match self.keys.get(idx) {
+			Some(k) if k.borrow() == key => Ok(idx),
+			_ => Err(idx)
+		}
+	}
+
+	/// Returns a reference to the value corresponding to the key.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(1, "a")]).freeze();
+	/// assert_eq!(map.get(&1), Some(&"a"));
+	/// assert_eq!(map.get(&2), None);
+	/// ```
+	pub fn get<Q>(&self, key: &Q) -> Option<&V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.get_key_value(key).map(|(_, v)| v)
+	}
+
+	/// Returns a reference to the key and value corresponding to the key.
+	pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.entry_at(self.search(key).ok()?)
+	}
+
+	/// Check if a key is contained within this map.
+	pub fn contains_key<Q>(&self, key: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.search(key).is_ok()
+	}
+
+	/// Returns a reference to the first entry with a key greater than or equal to `k` in
+	/// the map.
+	pub fn first_key_value_at_or_after<Q>(&self, k: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.entry_at(self.search(k).unwrap_or_else(|idx| idx))
+	}
+
+	/// Returns a reference to the last entry with a key smaller than or equal to `k` in
+	/// the map.
+	pub fn last_key_value_at_or_before<Q>(&self, k: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		match self.search(k) {
+			Ok(idx) => self.entry_at(idx),
+			Err(idx) => self.entry_at(idx.checked_sub(1)?)
+		}
+	}
+
+	/// Creates an iterator over all entries with keys within `range`, in ascending order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]).freeze();
+	/// assert_eq!(map.range(2 ..= 3).count(), 2);
+	/// assert_eq!(map.range(.. 2).collect::<Vec<_>>(), [(&1, &'a')]);
+	/// ```
+	pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		let start = match range.start_bound() {
+			Bound::Included(q) => self.keys.partition_point(|k| k.borrow() < q),
+			Bound::Excluded(q) => self.keys.partition_point(|k| k.borrow() <= q),
+			Bound::Unbounded => 0
+		};
+		let end = match range.end_bound() {
+			Bound::Included(q) => self.keys.partition_point(|k| k.borrow() <= q),
+			Bound::Excluded(q) => self.keys.partition_point(|k| k.borrow() < q),
+			Bound::Unbounded => self.len()
+		};
+		let end = end.max(start);
+		Iter {
+			keys: self.keys[start .. end].iter(),
+			values: self.values[start .. end].iter()
+		}
+	}
+}
+
+impl<K, V> From<AATreeMap<K, V>> for FrozenAATreeMap<K, V> {
+	fn from(map: AATreeMap<K, V>) -> Self {
+		map.freeze()
+	}
+}
+
+impl<K, V> From<FrozenAATreeMap<K, V>> for AATreeMap<K, V> {
+	fn from(map: FrozenAATreeMap<K, V>) -> Self {
+		map.thaw()
+	}
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for FrozenAATreeMap<K, V> {
+	fn from_iter<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = (K, V)>
+	{
+		iter.into_iter().collect::<AATreeMap<K, V>>().freeze()
+	}
+}
+
+/// An iterator over the entries of a [`FrozenAATreeMap`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a, K, V> {
+	keys: slice::Iter<'a, K>,
+	values: slice::Iter<'a, V>
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+	type Item = (&'a K, &'a V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		Some((self.keys.next()?, self.values.next()?))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.keys.size_hint()
+	}
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		Some((self.keys.next_back()?, self.values.next_back()?))
+	}
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> SortedIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a FrozenAATreeMap<K, V> {
+	type Item = (&'a K, &'a V);
+	type IntoIter = Iter<'a, K, V>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// An owning iterator over the entries of a [`FrozenAATreeMap`].
+#[derive(Debug)]
+pub struct IntoIter<K, V> {
+	keys: vec::IntoIter<K>,
+	values: vec::IntoIter<V>
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+	type Item = (K, V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		Some((self.keys.next()?, self.values.next()?))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.keys.size_hint()
+	}
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		Some((self.keys.next_back()?, self.values.next_back()?))
+	}
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+impl<K, V> SortedIterator for IntoIter<K, V> {}
+
+impl<K, V> IntoIterator for FrozenAATreeMap<K, V> {
+	type Item = (K, V);
+	type IntoIter = IntoIter<K, V>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		IntoIter {
+			keys: self.keys.into_iter(),
+			values: self.values.into_iter()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::vec::Vec;
+	use core::ops::Bound::{self, Excluded, Included, Unbounded};
+
+	#[test]
+	fn test_range_matches_iter() {
+		let map: AATreeMap<u32, u32> = (0 .. 50).map(|i| (i * 2, i)).collect();
+		let frozen = map.clone().freeze();
+		let bounds = |q| [Included(q), Excluded(q), Unbounded];
+		for lo in 0 .. 102 {
+			for hi in 0 .. 102 {
+				for (lo, hi) in bounds(lo).into_iter().zip(bounds(hi)) {
+					let range: (Bound<u32>, Bound<u32>) = (lo, hi);
+					let expected: Vec<_> = map
+						.iter()
+						.filter(|(k, _)| core::ops::RangeBounds::contains(&range, *k))
+						.collect();
+					assert_eq!(frozen.range(range).collect::<Vec<_>>(), expected);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_thaw() {
+		let map: AATreeMap<u32, u32> = (0 .. 100).map(|i| (i, i * i)).collect();
+		let thawed = map.clone().freeze().thaw();
+		thawed.assert_valid();
+		assert_eq!(thawed, map);
+	}
+
+	#[test]
+	fn test_veb_layout() {
+		use super::Layout;
+
+		for len in [0, 1, 2, 7, 100] {
+			let map: AATreeMap<u32, u32> = (0 .. len).map(|i| (i * 2, i)).collect();
+			let sorted = map.clone().freeze();
+			let packed = map.freeze_with_layout(Layout::VebPacked);
+			assert_eq!(packed.layout(), Layout::VebPacked);
+			assert_eq!(packed, sorted);
+			for q in 0 .. len * 2 + 2 {
+				assert_eq!(packed.get(&q), sorted.get(&q));
+				assert_eq!(
+					packed.first_key_value_at_or_after(&q),
+					sorted.first_key_value_at_or_after(&q)
+				);
+				assert_eq!(
+					packed.last_key_value_at_or_before(&q),
+					sorted.last_key_value_at_or_before(&q)
+				);
+			}
+			assert_eq!(packed.with_layout(Layout::Sorted).layout(), Layout::Sorted);
+		}
+	}
+}
@@ -0,0 +1,161 @@
+//! This file defines the van Emde Boas layout of the search index of a frozen map.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+/// The memory layout used for lookups in a [`FrozenAATreeMap`](super::FrozenAATreeMap).
+///
+/// The entries are always stored in sorted arrays, which are used for iteration and range
+/// queries. Depending on the layout, point lookups use a separate search index.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Layout {
+	/// Lookups use binary search over the sorted array of keys. This needs no additional
+	/// memory, but every step of the search touches a different cache line once the map
+	/// is large enough.
+	Sorted,
+
+	/// Lookups use a copy of all keys, arranged as a binary search tree in van Emde Boas
+	/// order. The tree is split at half its height into a top tree and many bottom trees,
+	/// each of which is stored contiguously and laid out recursively in the same way.
+	/// This way, a lookup touches only O(log_B n) cache lines for any cache line size B,
+	/// at the cost of storing every key a second time, together with its rank and the
+	/// positions of its children.
+	VebPacked
+}
+
+impl Default for Layout {
+	fn default() -> Self {
+		Self::Sorted
+	}
+}
+
+const NIL: usize = usize::MAX;
+
+#[derive(Clone, Debug)]
+struct VebNode<K> {
+	key: K,
+	/// The index of the key in the sorted array.
+	rank: usize,
+	/// The positions of the left and right child in the index, or [`NIL`].
+	children: [usize; 2]
+}
+
+/// A copy of the keys of a map, arranged as a binary search tree in van Emde Boas order.
+#[derive(Clone, Debug)]
+pub(super) struct VebIndex<K> {
+	nodes: Vec<VebNode<K>>
+}
+
+impl<K: Clone> VebIndex<K> {
+	/// Build the index for the strictly ascending `keys`.
+	///
+	/// The search tree has the shape of a binary heap: Node `i` (starting at 1) has the
+	/// children `2i` and `2i + 1`, and the ranks are assigned in order.
+	pub(super) fn new(keys: &[K]) -> Self {
+		let len = keys.len();
+		let mut ranks = alloc::vec![0; len + 1];
+		let mut next_rank = 0;
+		assign_ranks(1, len, &mut ranks, &mut next_rank);
+
+		let height = (usize::BITS - len.leading_zeros()) as usize;
+		let mut order = Vec::with_capacity(len);
+		veb_order(1, height, len, &mut order);
+
+		let mut positions = alloc::vec![NIL; len + 1];
+		for (pos, &node) in order.iter().enumerate() {
+			positions[node] = pos;
+		}
+		let child = |node: usize| positions.get(node).copied().unwrap_or(NIL);
+		let nodes = order
+			.iter()
+			.map(|&node| VebNode {
+				key: keys[ranks[node]].clone(),
+				rank: ranks[node],
+				children: [child(2 * node), child(2 * node + 1)]
+			})
+			.collect();
+		Self { nodes }
+	}
+}
+
+impl<K> VebIndex<K> {
+	/// Returns the rank of the first key that is not less than `key`, or the number of
+	/// keys if there is no such key.
+	pub(super) fn lower_bound<Q>(&self, key: &Q) -> usize
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let mut result = self.nodes.len();
+		let mut pos = if self.nodes.is_empty() { NIL } else { 0 };
+		while pos != NIL {
+			let node = &self.nodes[pos];
+			let is_less = node.key.borrow() < key;
+			if !is_less {
+				result = node.rank;
+			}
+			pos = node.children[is_less as usize];
+		}
+		result
+	}
+}
+
+/// Assign the ranks of all nodes in the subtree of `node` in order.
+fn assign_ranks(node: usize, len: usize, ranks: &mut [usize], next_rank: &mut usize) {
+	if node > len {
+		return;
+	}
+	assign_ranks(2 * node, len, ranks, next_rank);
+	ranks[node] = *next_rank;
+	*next_rank += 1;
+	assign_ranks(2 * node + 1, len, ranks, next_rank);
+}
+
+/// Append the nodes of the subtree of `root` with the given height in van Emde Boas
+/// order, skipping all nodes after `len`.
+fn veb_order(root: usize, height: usize, len: usize, order: &mut Vec<usize>) {
+	if root > len || height == 0 {
+		return;
+	}
+	if height == 1 {
+		order.push(root);
+		return;
+	}
+	let top = height / 2;
+	veb_order(root, top, len, order);
+	for bottom in (root << top) .. ((root + 1) << top) {
+		veb_order(bottom, height - top, len, order);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::VebIndex;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_lower_bound() {
+		for len in (0 .. 70).chain([255, 256, 1000]) {
+			let keys: Vec<u32> = (0 .. len).map(|x| x * 2).collect();
+			let index = VebIndex::new(&keys);
+			assert_eq!(index.nodes.len(), keys.len());
+			for q in 0 .. len * 2 + 2 {
+				assert_eq!(
+					index.lower_bound(&q),
+					keys.partition_point(|k| *k < q),
+					"len {len}, q {q}"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn test_veb_order() {
+		// a perfect tree of height 4 is split into a top tree of height 2 and four
+		// bottom trees of height 2
+		let mut order = Vec::new();
+		super::veb_order(1, 4, 15, &mut order);
+		assert_eq!(order, [1, 2, 3, 4, 8, 9, 5, 10, 11, 6, 12, 13, 7, 14, 15]);
+	}
+}
@@ -0,0 +1,240 @@
+//! An undirected graph that stores the neighbors of every node in an [`AATreeSet`].
+//!
+//! Keeping the neighbors sorted allows range queries on the neighbors of a node and
+//! computing common neighbors by merging two neighbor sets, which is the basic building
+//! block of many intersection-based algorithms like triangle counting.
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::graph::AAGraph;
+//!
+//! let mut graph = AAGraph::new();
+//! graph.add_edge(1, 2);
+//! graph.add_edge(2, 3);
+//! graph.add_edge(1, 3);
+//! graph.add_edge(3, 4);
+//! assert_eq!(graph.edge_count(), 4);
+//! assert_eq!(graph.common_neighbors(&1, &2).collect::<Vec<_>>(), [&3]);
+//! assert_eq!(graph.neighbors_in_range(&3, 2 ..).collect::<Vec<_>>(), [
+//! 	&2, &4
+//! ]);
+//! assert_eq!(graph.count_triangles(), 1);
+//! ```
+
+use crate::{
+	search::{after_end, before_start},
+	AATreeMap, AATreeSet
+};
+use core::{
+	fmt::{self, Debug},
+	ops::{Bound, RangeBounds}
+};
+
+/// Replacement for [`Bound::as_ref`], which requires Rust 1.65.
+fn as_ref<T>(bound: &Bound<T>) -> Bound<&T> {
+	match bound {
+		Bound::Included(x) => Bound::Included(x),
+		Bound::Excluded(x) => Bound::Excluded(x),
+		Bound::Unbounded => Bound::Unbounded
+	}
+}
+
+/// An undirected graph without self-loops or parallel edges. See the [module
+/// documentation](self) for details.
+#[derive(Clone)]
+pub struct AAGraph<N> {
+	adjacency: AATreeMap<N, AATreeSet<N>>,
+	edges: usize
+}
+
+impl<N> Default for AAGraph<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<N: Debug> Debug for AAGraph<N> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_map().entries(self.adjacency.iter()).finish()
+	}
+}
+
+impl<N> AAGraph<N> {
+	/// Construct a new, empty graph.
+	pub const fn new() -> Self {
+		Self {
+			adjacency: AATreeMap::new(),
+			edges: 0
+		}
+	}
+
+	/// Returns the number of nodes in the graph.
+	pub fn node_count(&self) -> usize {
+		self.adjacency.len()
+	}
+
+	/// Returns the number of edges in the graph.
+	pub fn edge_count(&self) -> usize {
+		self.edges
+	}
+
+	/// Creates an iterator that visits all nodes in ascending order.
+	pub fn nodes(&self) -> impl Iterator<Item = &N> {
+		self.adjacency.keys()
+	}
+}
+
+impl<N: Ord> AAGraph<N> {
+	/// Add a node without any edges to the graph. Returns `true` if the node was not
+	/// part of the graph before.
+	pub fn add_node(&mut self, node: N) -> bool {
+		if self.adjacency.contains_key(&node) {
+			return false;
+		}
+		self.adjacency.insert(node, AATreeSet::new());
+		true
+	}
+
+	/// Add an edge between `a` and `b`, adding the nodes if necessary. Returns `true` if
+	/// the edge was not part of the graph before. Self-loops are not supported, so adding
+	/// an edge from a node to itself returns `false` and leaves the graph unchanged.
+	pub fn add_edge(&mut self, a: N, b: N) -> bool
+	where
+		N: Clone
+	{
+		if a == b {
+			return false;
+		}
+		let inserted = self
+			.adjacency
+			.entry(a.clone())
+			.or_default()
+			.insert(b.clone());
+		if inserted {
+			self.adjacency.entry(b).or_default().insert(a);
+			self.edges += 1;
+		}
+		inserted
+	}
+
+	/// Remove the edge between `a` and `b`. Returns `true` if the edge was part of the
+	/// graph. The nodes stay part of the graph.
+	pub fn remove_edge(&mut self, a: &N, b: &N) -> bool {
+		let removed = self
+			.adjacency
+			.get_mut(a)
+			.map(|neighbors| neighbors.remove(b))
+			.unwrap_or(false);
+		if removed {
+			if let Some(neighbors) = self.adjacency.get_mut(b) {
+				neighbors.remove(a);
+			}
+			self.edges -= 1;
+		}
+		removed
+	}
+
+	/// Returns `true` if there is an edge between `a` and `b`.
+	pub fn contains_edge(&self, a: &N, b: &N) -> bool {
+		self.adjacency
+			.get(a)
+			.map(|neighbors| neighbors.contains(b))
+			.unwrap_or(false)
+	}
+
+	/// Returns the set of neighbors of `node`, or `None` if the node is not part of the
+	/// graph.
+	pub fn neighbors(&self, node: &N) -> Option<&AATreeSet<N>> {
+		self.adjacency.get(node)
+	}
+
+	/// Returns the number of neighbors of `node`.
+	pub fn degree(&self, node: &N) -> usize {
+		self.neighbors(node).map(AATreeSet::len).unwrap_or(0)
+	}
+
+	/// Creates an iterator that visits all neighbors of `node` within `range` in
+	/// ascending order.
+	pub fn neighbors_in_range<R>(&self, node: &N, range: R) -> impl Iterator<Item = &N>
+	where
+		N: Clone,
+		R: RangeBounds<N>
+	{
+		let start = range.start_bound().cloned();
+		let end = range.end_bound().cloned();
+		self.neighbors(node)
+			.into_iter()
+			.flat_map(AATreeSet::iter)
+			.skip_while(move |n| before_start(*n, as_ref(&start)))
+			.take_while(move |n| !after_end(*n, as_ref(&end)))
+	}
+
+	/// Creates an iterator that visits all common neighbors of `a` and `b` in ascending
+	/// order, by merging both neighbor sets in O(deg(a) + deg(b)).
+	pub fn common_neighbors(&self, a: &N, b: &N) -> impl Iterator<Item = &N> {
+		self.neighbors(a)
+			.zip(self.neighbors(b))
+			.into_iter()
+			.flat_map(|(a, b)| a.intersection(b))
+	}
+
+	/// Count the triangles in the graph. Every triangle is found exactly once by only
+	/// looking at the common neighbors of an edge `(u, v)` with `u < v` that are greater
+	/// than `v`.
+	pub fn count_triangles(&self) -> usize {
+		self.adjacency
+			.iter()
+			.flat_map(|(u, neighbors)| {
+				neighbors
+					.iter()
+					.filter(move |v| *v > u)
+					.map(move |v| (u, v))
+			})
+			.map(|(u, v)| self.common_neighbors(u, v).filter(|w| *w > v).count())
+			.sum()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AAGraph;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_graph() {
+		let mut graph = AAGraph::new();
+		assert!(graph.add_edge(1, 2));
+		assert!(!graph.add_edge(2, 1));
+		assert!(!graph.add_edge(3, 3));
+		assert!(graph.add_node(3));
+		assert!(!graph.add_node(1));
+		assert_eq!(graph.node_count(), 3);
+		assert_eq!(graph.edge_count(), 1);
+		assert!(graph.contains_edge(&2, &1));
+		assert!(graph.remove_edge(&2, &1));
+		assert!(!graph.remove_edge(&1, &2));
+		assert!(!graph.contains_edge(&1, &2));
+		assert_eq!(graph.edge_count(), 0);
+		assert_eq!(graph.degree(&1), 0);
+		assert_eq!(graph.common_neighbors(&1, &4).count(), 0);
+	}
+
+	#[test]
+	fn test_count_triangles() {
+		// complete graph with 6 nodes has 6 choose 3 = 20 triangles
+		let mut graph = AAGraph::new();
+		for a in 0 .. 6 {
+			for b in a + 1 .. 6 {
+				graph.add_edge(a, b);
+			}
+		}
+		assert_eq!(graph.edge_count(), 15);
+		assert_eq!(graph.count_triangles(), 20);
+		let neighbors: Vec<_> = graph.neighbors_in_range(&2, 1 ..= 4).copied().collect();
+		assert_eq!(neighbors, [1, 3, 4]);
+
+		graph.remove_edge(&0, &1);
+		assert_eq!(graph.count_triangles(), 16);
+	}
+}
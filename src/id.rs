@@ -0,0 +1,210 @@
+//! An allocator for integer-like ids that keeps track of free ids as ranges.
+
+use crate::{key::Step, AATreeMap};
+use core::{
+	fmt::{self, Debug},
+	ops::RangeInclusive
+};
+
+/// An allocator that hands out unique ids from a range, like ports, handles or inode
+/// numbers.
+///
+/// The free ids are stored as a map from the first to the last id of every range of
+/// consecutive free ids. Adjacent ranges are merged when ids are freed, so the memory
+/// usage depends only on the fragmentation of the free space, not on the number of ids.
+/// Allocating and freeing a single id takes O(log n), where n is the number of free
+/// ranges. Allocating a range of ids uses the first free range that is large enough,
+/// which takes O(n) in the worst case.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::AAIdAllocator;
+/// let mut ids = AAIdAllocator::new(1u16 ..= 100);
+/// assert_eq!(ids.allocate(), Some(1));
+/// assert_eq!(ids.allocate(), Some(2));
+/// assert_eq!(ids.allocate_range(10), Some(3));
+/// assert!(ids.free(2));
+/// assert!(!ids.free(2));
+/// assert_eq!(ids.allocate(), Some(2));
+/// assert_eq!(ids.allocate(), Some(13));
+/// ```
+#[derive(Clone)]
+pub struct AAIdAllocator<K> {
+	free: AATreeMap<K, K>,
+	first: K,
+	last: K
+}
+
+impl<K: Debug> Debug for AAIdAllocator<K> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("AAIdAllocator")
+			.field("range", &(&self.first ..= &self.last))
+			.field("free", &self.free)
+			.finish()
+	}
+}
+
+impl<K: Step + Clone> AAIdAllocator<K> {
+	/// Construct a new allocator that hands out ids from `range`, with all ids free.
+	pub fn new(range: RangeInclusive<K>) -> Self {
+		let (first, last) = range.into_inner();
+		let mut free = AATreeMap::new();
+		if first <= last {
+			free.insert(first.clone(), last.clone());
+		}
+		Self { free, first, last }
+	}
+
+	/// Returns the number of ranges of consecutive free ids.
+	pub fn free_ranges(&self) -> usize {
+		self.free.len()
+	}
+
+	/// Creates an iterator over all ranges of consecutive free ids in ascending order.
+	pub fn iter_free(&self) -> impl Iterator<Item = RangeInclusive<&K>> {
+		self.free.iter().map(|(first, last)| first ..= last)
+	}
+
+	/// Returns `true` if `id` is part of the range of this allocator and currently
+	/// allocated.
+	pub fn is_allocated(&self, id: &K) -> bool {
+		*id >= self.first
+			&& *id <= self.last
+			&& !self
+				.free
+				.last_key_value_at_or_before(id)
+				.map_or(false, |(_, last)| last >= id)
+	}
+
+	/// Allocate the smallest free id. Returns `None` if all ids are allocated.
+	pub fn allocate(&mut self) -> Option<K> {
+		self.allocate_range(1)
+	}
+
+	/// Allocate `n` consecutive ids from the first range of free ids that is large enough,
+	/// and return the first id. Returns `None` if there is no such range or if `n` is
+	/// zero.
+	pub fn allocate_range(&mut self, n: usize) -> Option<K> {
+		let end_offset = n.checked_sub(1)?;
+		let (first, last) = self
+			.free
+			.iter()
+			.find(|(first, last)| {
+				first.forward(end_offset).map_or(false, |end| end <= **last)
+			})
+			.map(|(first, last)| (first.clone(), last.clone()))?;
+		self.free.remove(&first);
+		let end = first.forward(end_offset)?;
+		if end < last {
+			self.free.insert(end.successor()?, last);
+		}
+		Some(first)
+	}
+
+	/// Free a previously allocated id. Returns `false` if the id was not allocated.
+	pub fn free(&mut self, id: K) -> bool {
+		self.free_range(id, 1)
+	}
+
+	/// Free `n` previously allocated consecutive ids, starting at `first`. Returns `false`
+	/// and does not free any id if not all of these ids were allocated.
+	pub fn free_range(&mut self, first: K, n: usize) -> bool {
+		let mut last = match n.checked_sub(1).and_then(|offset| first.forward(offset)) {
+			Some(last) => last,
+			None => return false
+		};
+		if first < self.first || last > self.last {
+			return false;
+		}
+		// the range must not overlap with any free range
+		let overlaps = self
+			.free
+			.last_key_value_at_or_before(&last)
+			.map_or(false, |(_, prev_last)| *prev_last >= first);
+		if overlaps {
+			return false;
+		}
+
+		// merge with the next free range
+		if let Some(next) = last.successor() {
+			if let Some(next_last) = self.free.remove(&next) {
+				last = next_last;
+			}
+		}
+		// merge with the previous free range
+		if let Some(prev) = first.predecessor() {
+			if let Some((_, prev_last)) = self.free.last_key_value_mut_at_or_before(&prev)
+			{
+				if *prev_last == prev {
+					*prev_last = last;
+					return true;
+				}
+			}
+		}
+		self.free.insert(first, last);
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AAIdAllocator;
+	use alloc::vec::Vec;
+
+	fn free_ranges(ids: &AAIdAllocator<u8>) -> Vec<(u8, u8)> {
+		ids.iter_free().map(|r| (**r.start(), **r.end())).collect()
+	}
+
+	#[test]
+	fn test_allocate_all() {
+		let mut ids = AAIdAllocator::new(0u8 ..= 255);
+		for i in 0 ..= 255 {
+			assert_eq!(ids.allocate(), Some(i));
+		}
+		assert_eq!(ids.allocate(), None);
+		assert_eq!(ids.free_ranges(), 0);
+		assert!(ids.is_allocated(&255));
+		assert!(ids.free(255));
+		assert_eq!(ids.allocate(), Some(255));
+	}
+
+	#[test]
+	fn test_coalesce() {
+		let mut ids = AAIdAllocator::new(0u8 ..= 9);
+		assert_eq!(ids.allocate_range(10), Some(0));
+		assert!(ids.free(2));
+		assert!(ids.free(4));
+		assert!(ids.free(6));
+		assert_eq!(free_ranges(&ids), [(2, 2), (4, 4), (6, 6)]);
+		assert!(!ids.free_range(1, 2));
+		assert!(ids.free(3));
+		assert!(ids.free(5));
+		assert_eq!(free_ranges(&ids), [(2, 6)]);
+		assert!(ids.free_range(7, 3));
+		assert_eq!(free_ranges(&ids), [(2, 9)]);
+		assert!(!ids.free_range(9, 2));
+		assert!(!ids.free(10));
+		assert!(!ids.is_allocated(&5));
+		assert!(ids.is_allocated(&1));
+
+		assert_eq!(ids.allocate_range(0), None);
+		assert_eq!(ids.allocate_range(9), None);
+		assert_eq!(ids.allocate_range(8), Some(2));
+		assert_eq!(ids.allocate(), None);
+	}
+
+	#[test]
+	fn test_signed_ids() {
+		let mut ids = AAIdAllocator::new(-128i8 ..= 127);
+		assert_eq!(ids.allocate_range(200), Some(-128));
+		assert_eq!(ids.allocate_range(56), Some(72));
+		assert_eq!(ids.allocate(), None);
+		assert!(ids.free_range(-100, 150));
+		assert_eq!(ids.free_ranges(), 1);
+		assert!(ids.is_allocated(&-101));
+		assert!(!ids.is_allocated(&49));
+		assert!(ids.is_allocated(&50));
+		assert_eq!(ids.allocate_range(150), Some(-100));
+	}
+}
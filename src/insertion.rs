@@ -0,0 +1,189 @@
+//! A map that remembers the order in which its keys were inserted.
+
+use crate::AATreeMap;
+use core::{
+	borrow::Borrow,
+	fmt::{self, Debug},
+	mem
+};
+
+/// An [`AATreeMap`] that additionally tracks the order in which keys were inserted.
+///
+/// Lookups and [`iter`](Self::iter) behave like those of an [`AATreeMap`], while
+/// [`iter_insertion_order`](Self::iter_insertion_order) visits the entries in the order
+/// their keys were first inserted. Like `indexmap`, overwriting the value of an existing
+/// key keeps its original position. Removing a key and inserting it again moves it to
+/// the end.
+///
+/// The insertion order is stored in a second tree that maps a sequence number to a clone
+/// of the key. Therefore, every insertion and removal takes two tree operations, and
+/// advancing the insertion order iterator takes O(log n) instead of amortized O(1).
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::InsertionOrderMap;
+/// let mut map = InsertionOrderMap::new();
+/// map.insert("b", 1);
+/// map.insert("c", 2);
+/// map.insert("a", 3);
+/// map.insert("b", 4);
+/// assert_eq!(map.iter().collect::<Vec<_>>(), [
+/// 	(&"a", &3),
+/// 	(&"b", &4),
+/// 	(&"c", &2)
+/// ]);
+/// assert_eq!(map.iter_insertion_order().collect::<Vec<_>>(), [
+/// 	(&"b", &4),
+/// 	(&"c", &2),
+/// 	(&"a", &3)
+/// ]);
+/// ```
+#[derive(Clone)]
+pub struct InsertionOrderMap<K, V> {
+	entries: AATreeMap<K, (u64, V)>,
+	order: AATreeMap<u64, K>,
+	next: u64
+}
+
+impl<K, V> Default for InsertionOrderMap<K, V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K: Debug, V: Debug> Debug for InsertionOrderMap<K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_map()
+			.entries(self.entries.iter().map(|(k, (_, v))| (k, v)))
+			.finish()
+	}
+}
+
+impl<K, V> InsertionOrderMap<K, V> {
+	/// Construct a new, empty map.
+	pub const fn new() -> Self {
+		Self {
+			entries: AATreeMap::new(),
+			order: AATreeMap::new(),
+			next: 0
+		}
+	}
+
+	/// Returns the number of entries in the map.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Returns `true` if the map contains no entries.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Removes all entries from the map.
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.order.clear();
+		self.next = 0;
+	}
+
+	/// Creates an iterator that visits all entries with the keys in ascending order.
+	pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+		self.entries.iter().map(|(k, (_, v))| (k, v))
+	}
+}
+
+impl<K: Ord, V> InsertionOrderMap<K, V> {
+	/// Returns a reference to the value corresponding to the key.
+	pub fn get<Q>(&self, key: &Q) -> Option<&V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.entries.get(key).map(|(_, v)| v)
+	}
+
+	/// Returns a mutable reference to the value corresponding to the key.
+	pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.entries.get_mut(key).map(|(_, v)| v)
+	}
+
+	/// Returns `true` if the map contains a value for the specified key.
+	pub fn contains_key<Q>(&self, key: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.entries.contains_key(key)
+	}
+
+	/// Insert a new entry into the map, or replace the value of an existing entry without
+	/// changing its position in the insertion order. Returns the old value, if any.
+	pub fn insert(&mut self, key: K, value: V) -> Option<V>
+	where
+		K: Clone
+	{
+		if let Some((_, old)) = self.entries.get_mut(&key) {
+			return Some(mem::replace(old, value));
+		}
+		let seq = self.next;
+		self.next += 1;
+		self.order.insert(seq, key.clone());
+		self.entries.insert(key, (seq, value));
+		None
+	}
+
+	/// Remove a key from the map, returning its value if it was part of the map.
+	pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let (seq, value) = self.entries.remove(key)?;
+		self.order.remove(&seq);
+		Some(value)
+	}
+
+	/// Creates an iterator that visits all entries in the order their keys were inserted.
+	pub fn iter_insertion_order(&self) -> impl Iterator<Item = (&K, &V)> {
+		self.order.values().map(move |key| {
+			let (_, value) = self
+				.entries
+				.get(key)
+				.expect("insertion order out of sync with entries");
+			(key, value)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::InsertionOrderMap;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_insertion_order() {
+		let mut map = InsertionOrderMap::new();
+		for x in [5, 3, 8, 1, 9, 2] {
+			assert_eq!(map.insert(x, x * 10), None);
+		}
+		assert_eq!(map.insert(8, 0), Some(80));
+		assert_eq!(map.remove(&3), Some(30));
+		assert_eq!(map.remove(&3), None);
+		assert_eq!(map.insert(3, 33), None);
+		assert_eq!(map.len(), 6);
+
+		let order: Vec<_> = map.iter_insertion_order().map(|(k, v)| (*k, *v)).collect();
+		assert_eq!(order, [(5, 50), (8, 0), (1, 10), (9, 90), (2, 20), (3, 33)]);
+		let sorted: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+		assert_eq!(sorted, [1, 2, 3, 5, 8, 9]);
+
+		map.clear();
+		assert!(map.is_empty());
+		assert_eq!(map.iter_insertion_order().count(), 0);
+	}
+}
@@ -2,7 +2,10 @@
 
 //! Iterator implementations for [`AATreeSet`](crate::AATreeSet) and [`AATreeMap`](crate::AATreeMap).
 
-use super::node::{AANode, Node};
+use super::{
+	node::{AANode, Node},
+	sorted::SortedIterator
+};
 use alloc::vec::Vec;
 use core::{iter::FusedIterator, marker::PhantomData};
 
@@ -32,7 +35,7 @@ impl<'a, C, T> AAIter<'a, C, T> {
 		Self {
 			stack,
 			len,
-			_ty: PhantomData::default()
+			_ty: PhantomData
 		}
 	}
 }
@@ -82,6 +85,73 @@ impl<'a, C, T> ExactSizeIterator for AAIter<'a, C, T> where &'a C: IterContent<T
 
 impl<'a, C, T> FusedIterator for AAIter<'a, C, T> where &'a C: IterContent<T> {}
 
+impl<'a, C, T> SortedIterator for AAIter<'a, C, T> where &'a C: IterContent<T> {}
+
+/// The iterator produced by a reference of an AATree-based data structure when iterating in
+/// descending order.
+pub struct AARevIter<'a, C, T> {
+	stack: Vec<(bool, &'a AANode<C>)>,
+	len: usize,
+	_ty: PhantomData<T>
+}
+
+impl<'a, C, T> AARevIter<'a, C, T> {
+	pub(super) fn new(root: &'a AANode<C>, len: usize) -> Self {
+		let mut stack = Vec::with_capacity(root.level() as usize * 2 + 1);
+		stack.push((false, root));
+		Self {
+			stack,
+			len,
+			_ty: PhantomData
+		}
+	}
+}
+
+impl<'a, C, T> Iterator for AARevIter<'a, C, T>
+where
+	&'a C: IterContent<T>
+{
+	type Item = T;
+
+	fn next(&mut self) -> Option<T> {
+		loop {
+			let (visited_right, last) = self.stack.pop()?;
+			if let Some(Node { right_child, .. }) = last.as_ref() {
+				self.stack.push((true, last));
+				if !visited_right && !right_child.is_nil() {
+					self.stack.push((false, right_child));
+				} else {
+					break;
+				}
+			}
+		}
+
+		let (_, last) = self.stack.pop()?;
+		match last.as_ref() {
+			None => unreachable!(),
+			Some(Node {
+				content,
+				left_child,
+				..
+			}) => {
+				if !left_child.is_nil() {
+					self.stack.push((false, left_child));
+				}
+				self.len -= 1;
+				Some(content.content())
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<'a, C, T> ExactSizeIterator for AARevIter<'a, C, T> where &'a C: IterContent<T> {}
+
+impl<'a, C, T> FusedIterator for AARevIter<'a, C, T> where &'a C: IterContent<T> {}
+
 /// The iterator produces from an AATree-based data structure when turned into an iterator.
 pub struct AAIntoIter<C, T> {
 	stack: Vec<AANode<C>>,
@@ -96,7 +166,7 @@ impl<C, T> AAIntoIter<C, T> {
 		Self {
 			stack,
 			len,
-			_ty: PhantomData::default()
+			_ty: PhantomData
 		}
 	}
 }
@@ -109,23 +179,10 @@ where
 
 	fn next(&mut self) -> Option<T> {
 		loop {
-			let last = self.stack.pop()?;
-			if let Some(Node {
-				level,
-				content,
-				left_child,
-				right_child
-			}) = last.unbox()
-			{
-				self.stack.push(
-					Node {
-						level,
-						content,
-						left_child: AANode::new(),
-						right_child
-					}
-					.into()
-				);
+			let mut last = self.stack.pop()?;
+			if let Some(Node { left_child, .. }) = last.as_mut() {
+				let left_child = left_child.take();
+				self.stack.push(last);
 				if !left_child.is_nil() {
 					self.stack.push(left_child);
 				} else {
@@ -159,3 +216,5 @@ where
 impl<C, T> ExactSizeIterator for AAIntoIter<C, T> where C: IterContent<T> {}
 
 impl<C, T> FusedIterator for AAIntoIter<C, T> where C: IterContent<T> {}
+
+impl<C, T> SortedIterator for AAIntoIter<C, T> where C: IterContent<T> {}
@@ -0,0 +1,226 @@
+//! Incremental persistence for [`AATreeMap`] using a write-ahead log.
+//!
+//! A [`JournaledMap`] records every modification of the map as a [`JournalOp`] in a
+//! [`Journal`]. Persist a snapshot of the map from time to time, for
+//! example using [`AATreeMap::freeze`], together with the journal entries recorded since
+//! then. The map can then be reconstructed using [`replay`], which bulk-builds the tree
+//! from the snapshot in O(n) and then applies the journal entries.
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::{
+//! 	journal::{replay, JournalOp, JournaledMap},
+//! 	AATreeMap
+//! };
+//!
+//! let mut map = JournaledMap::new(Vec::new());
+//! map.insert(1, "a");
+//! map.insert(2, "b");
+//! let snapshot = (*map).clone().freeze();
+//! map.journal_mut().clear();
+//!
+//! map.remove(&1);
+//! map.insert(3, "c");
+//! assert_eq!(map.journal(), &[
+//! 	JournalOp::Remove(1),
+//! 	JournalOp::Insert(3, "c")
+//! ]);
+//!
+//! let (map, journal) = map.into_parts();
+//! assert_eq!(replay(snapshot, journal), map);
+//! ```
+
+use crate::AATreeMap;
+use alloc::vec::Vec;
+use core::{borrow::Borrow, ops::Deref};
+
+/// A single modification of a [`JournaledMap`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum JournalOp<K, V> {
+	/// A key was inserted or its value replaced.
+	Insert(K, V),
+	/// A key was removed.
+	Remove(K),
+	/// All entries were removed.
+	Clear
+}
+
+impl<K, V> JournalOp<K, V> {
+	/// Apply this operation to `map`.
+	pub fn apply(self, map: &mut AATreeMap<K, V>)
+	where
+		K: Ord
+	{
+		match self {
+			Self::Insert(key, value) => {
+				map.insert(key, value);
+			},
+			Self::Remove(key) => {
+				map.remove(&key);
+			},
+			Self::Clear => map.clear()
+		}
+	}
+}
+
+/// An append-only log of [`JournalOp`]s.
+pub trait Journal<K, V> {
+	/// Append an operation to the journal. This is called for every modification of the
+	/// map, before the [`JournaledMap`] method returns.
+	fn record(&mut self, op: JournalOp<&K, &V>);
+}
+
+impl<K: Clone, V: Clone> Journal<K, V> for Vec<JournalOp<K, V>> {
+	fn record(&mut self, op: JournalOp<&K, &V>) {
+		self.push(match op {
+			JournalOp::Insert(key, value) => {
+				JournalOp::Insert(key.clone(), value.clone())
+			},
+			JournalOp::Remove(key) => JournalOp::Remove(key.clone()),
+			JournalOp::Clear => JournalOp::Clear
+		});
+	}
+}
+
+/// An [`AATreeMap`] that records all modifications in a [`Journal`].
+///
+/// Read access to the map is available through [`Deref`].
+#[derive(Clone, Debug)]
+pub struct JournaledMap<K, V, J> {
+	map: AATreeMap<K, V>,
+	journal: J
+}
+
+impl<K, V, J: Default> Default for JournaledMap<K, V, J> {
+	fn default() -> Self {
+		Self::new(J::default())
+	}
+}
+
+impl<K, V, J> JournaledMap<K, V, J> {
+	/// Construct a new, empty map that records all modifications in `journal`.
+	pub const fn new(journal: J) -> Self {
+		Self::from_snapshot(AATreeMap::new(), journal)
+	}
+
+	/// Construct a map with the contents of `snapshot` that records all further
+	/// modifications in `journal`.
+	pub const fn from_snapshot(snapshot: AATreeMap<K, V>, journal: J) -> Self {
+		Self {
+			map: snapshot,
+			journal
+		}
+	}
+
+	/// Returns a reference to the journal.
+	pub fn journal(&self) -> &J {
+		&self.journal
+	}
+
+	/// Returns a mutable reference to the journal, e.g. to truncate it after persisting
+	/// a snapshot.
+	pub fn journal_mut(&mut self) -> &mut J {
+		&mut self.journal
+	}
+
+	/// Split this map into the map and the journal.
+	pub fn into_parts(self) -> (AATreeMap<K, V>, J) {
+		(self.map, self.journal)
+	}
+}
+
+impl<K: Ord, V, J: Journal<K, V>> JournaledMap<K, V, J> {
+	/// Insert a new element into the map, or overwrite an existing element with the same
+	/// key. If a value was overwritten, the old value will be returned.
+	pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+		self.journal.record(JournalOp::Insert(&key, &value));
+		self.map.insert(key, value)
+	}
+
+	/// Remove a key from the map if it exists, and return the value that was previously
+	/// stored in the map for that key. Nothing is recorded if the key does not exist.
+	pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let (key, value) = self.map.remove_entry(key)?;
+		self.journal.record(JournalOp::Remove(&key));
+		Some(value)
+	}
+
+	/// Clears the map, removing all elements.
+	pub fn clear(&mut self) {
+		self.journal.record(JournalOp::Clear);
+		self.map.clear();
+	}
+}
+
+impl<K, V, J> Deref for JournaledMap<K, V, J> {
+	type Target = AATreeMap<K, V>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.map
+	}
+}
+
+/// Reconstruct a map from a snapshot and all journal entries that were recorded after
+/// the snapshot was taken.
+///
+/// Passing a [`FrozenAATreeMap`](crate::FrozenAATreeMap) as the snapshot builds the tree
+/// in O(n) before the journal entries are applied.
+pub fn replay<K, V, S, I>(snapshot: S, journal: I) -> AATreeMap<K, V>
+where
+	K: Ord,
+	S: Into<AATreeMap<K, V>>,
+	I: IntoIterator<Item = JournalOp<K, V>>
+{
+	let mut map = snapshot.into();
+	for op in journal {
+		op.apply(&mut map);
+	}
+	map
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{replay, JournaledMap};
+	use crate::AATreeMap;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_replay() {
+		let mut map = JournaledMap::new(Vec::new());
+		let mut state = 3u32;
+		let mut snapshot = None;
+		for step in 0 .. 1000u32 {
+			state = state.wrapping_mul(1103515245).wrapping_add(12345);
+			let key = (state >> 16) % 50;
+			match state % 5 {
+				0 ..= 2 => {
+					map.insert(key, step);
+				},
+				3 => {
+					map.remove(&key);
+				},
+				_ if step % 200 == 0 => map.clear(),
+				_ => {}
+			}
+			if step == 500 {
+				snapshot = Some((*map).clone().freeze());
+				map.journal_mut().clear();
+			}
+		}
+		let (map, journal) = map.into_parts();
+		assert_eq!(replay(snapshot.unwrap(), journal), map);
+	}
+
+	#[test]
+	fn test_remove_missing_is_not_recorded() {
+		let mut map: JournaledMap<u8, u8, _> =
+			JournaledMap::from_snapshot(AATreeMap::new(), Vec::new());
+		assert_eq!(map.remove(&1), None);
+		assert!(map.journal().is_empty());
+	}
+}
@@ -0,0 +1,460 @@
+//! Key adapters that change the ordering of a key type, and traits for key types.
+//!
+//! Wrapping a key in an adapter changes the order in which it is stored, but it also means
+//! that lookups can no longer simply borrow the key as its inner type: [`Borrow`] requires
+//! the borrowed form to be ordered identically to the owned form, which is not the case
+//! for e.g. `Reverse<String>` and `str`. Instead, every adapter in this module comes with
+//! a trait whose trait objects are ordered like the adapter, and the adapter can be
+//! borrowed as such a trait object. This allows lookups with a wrapped reference, without
+//! cloning the key you are looking for.
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::{
+//! 	key::{CaseInsensitive, CaseInsensitiveKey, Reverse, ReverseKey},
+//! 	AATreeMap
+//! };
+//!
+//! let mut map = AATreeMap::new();
+//! map.insert(Reverse(String::from("a")), 1);
+//! map.insert(Reverse(String::from("b")), 2);
+//! assert_eq!(
+//! 	map.first_key_value(),
+//! 	Some((&Reverse(String::from("b")), &2))
+//! );
+//!
+//! let key: &dyn ReverseKey<str> = &Reverse("a");
+//! assert_eq!(map.get(key), Some(&1));
+//!
+//! let mut map = AATreeMap::new();
+//! map.insert(CaseInsensitive(String::from("Hello")), 1);
+//! let key: &dyn CaseInsensitiveKey = &CaseInsensitive("HELLO");
+//! assert_eq!(map.get(key), Some(&1));
+//! ```
+
+use core::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt::{self, Display},
+	hash::{Hash, Hasher}
+};
+
+pub use core::cmp::Reverse;
+
+/// A key that is ordered in reverse. Implemented by [`Reverse<K>`] for every `K` that can
+/// be borrowed as `Q`.
+///
+/// `Reverse<K>` can be borrowed as `dyn ReverseKey<Q>`, so a map with keys of type
+/// `Reverse<String>` can be queried with a `&dyn ReverseKey<str>`, which in turn can be
+/// created from a `&Reverse<&str>`.
+pub trait ReverseKey<Q: ?Sized> {
+	/// Returns the wrapped key.
+	fn key(&self) -> &Q;
+}
+
+impl<K, Q> ReverseKey<Q> for Reverse<K>
+where
+	K: Borrow<Q>,
+	Q: ?Sized
+{
+	fn key(&self) -> &Q {
+		self.0.borrow()
+	}
+}
+
+impl<'a, K, Q> Borrow<dyn ReverseKey<Q> + 'a> for Reverse<K>
+where
+	K: Borrow<Q> + 'a,
+	Q: ?Sized + 'a
+{
+	fn borrow(&self) -> &(dyn ReverseKey<Q> + 'a) {
+		self
+	}
+}
+
+impl<Q: PartialEq + ?Sized> PartialEq for dyn ReverseKey<Q> + '_ {
+	fn eq(&self, other: &Self) -> bool {
+		self.key() == other.key()
+	}
+}
+
+impl<Q: Eq + ?Sized> Eq for dyn ReverseKey<Q> + '_ {}
+
+impl<Q: PartialOrd + ?Sized> PartialOrd for dyn ReverseKey<Q> + '_ {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		other.key().partial_cmp(self.key())
+	}
+}
+
+impl<Q: Ord + ?Sized> Ord for dyn ReverseKey<Q> + '_ {
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.key().cmp(self.key())
+	}
+}
+
+/// A key type where every value has a well-defined successor, like the integer types.
+/// Used by [`AATreeSet::next_free`](crate::AATreeSet::next_free) and
+/// [`AATreeMap::next_free_key`](crate::AATreeMap::next_free_key).
+pub trait Successor: Ord + Sized {
+	/// Returns the smallest value that is greater than `self`, or `None` if `self` is the
+	/// largest value.
+	fn successor(&self) -> Option<Self>;
+}
+
+/// A key type that can be stepped forward and backward by an arbitrary number of values,
+/// like the integer types. Used by [`AAIdAllocator`](crate::AAIdAllocator).
+pub trait Step: Successor {
+	/// Returns the largest value that is smaller than `self`, or `None` if `self` is the
+	/// smallest value.
+	fn predecessor(&self) -> Option<Self>;
+
+	/// Returns the value `n` steps after `self`, or `None` if that would overflow.
+	fn forward(&self, n: usize) -> Option<Self>;
+}
+
+/// Implements [`Successor`] and [`Step`] for integer types. Every type is paired with its
+/// unsigned counterpart, through which [`Step::forward`] computes the distance to the
+/// largest value, so that signed types can be stepped across zero.
+macro_rules! impl_step {
+	($($ty:ty => $unsigned:ty),*) => {
+		$(
+			impl Successor for $ty {
+				fn successor(&self) -> Option<Self> {
+					self.checked_add(1)
+				}
+			}
+
+			impl Step for $ty {
+				fn predecessor(&self) -> Option<Self> {
+					self.checked_sub(1)
+				}
+
+				fn forward(&self, n: usize) -> Option<Self> {
+					let n = <$unsigned>::try_from(n).ok()?;
+					let remaining = (<$ty>::MAX as $unsigned).wrapping_sub(*self as $unsigned);
+					if n > remaining {
+						return None;
+					}
+					Some((*self as $unsigned).wrapping_add(n) as $ty)
+				}
+			}
+		)*
+	};
+}
+
+impl_step!(
+	u8 => u8,
+	u16 => u16,
+	u32 => u32,
+	u64 => u64,
+	u128 => u128,
+	usize => usize,
+	i8 => u8,
+	i16 => u16,
+	i32 => u32,
+	i64 => u64,
+	i128 => u128,
+	isize => usize
+);
+
+/// Compare two strings character by character after converting them to lowercase.
+fn cmp_case_insensitive(a: &str, b: &str) -> Ordering {
+	let a = a.chars().flat_map(char::to_lowercase);
+	let b = b.chars().flat_map(char::to_lowercase);
+	a.cmp(b)
+}
+
+/// A string that is compared case-insensitively.
+///
+/// Two strings are compared by converting every character to lowercase using
+/// [`char::to_lowercase`]. This handles all of Unicode's simple and special lowercase
+/// mappings, but is not a full case folding as required by e.g. German `ß` and `SS`.
+///
+/// `CaseInsensitive<S>` can be borrowed as `dyn CaseInsensitiveKey`, so a map with keys
+/// of type `CaseInsensitive<String>` can be queried with a `&dyn CaseInsensitiveKey`,
+/// which in turn can be created from a `&CaseInsensitive<&str>`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CaseInsensitive<S>(pub S);
+
+impl<S: AsRef<str>> PartialEq for CaseInsensitive<S> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl<S: AsRef<str>> Eq for CaseInsensitive<S> {}
+
+impl<S: AsRef<str>> PartialOrd for CaseInsensitive<S> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<S: AsRef<str>> Ord for CaseInsensitive<S> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		cmp_case_insensitive(self.0.as_ref(), other.0.as_ref())
+	}
+}
+
+impl<S: AsRef<str>> Hash for CaseInsensitive<S> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		for c in self.0.as_ref().chars().flat_map(char::to_lowercase) {
+			c.hash(state);
+		}
+	}
+}
+
+/// A string that is compared case-insensitively. Implemented by [`CaseInsensitive<S>`].
+pub trait CaseInsensitiveKey {
+	/// Returns the wrapped string.
+	fn as_str(&self) -> &str;
+}
+
+impl<S: AsRef<str>> CaseInsensitiveKey for CaseInsensitive<S> {
+	fn as_str(&self) -> &str {
+		self.0.as_ref()
+	}
+}
+
+impl<'a, S: AsRef<str> + 'a> Borrow<dyn CaseInsensitiveKey + 'a> for CaseInsensitive<S> {
+	fn borrow(&self) -> &(dyn CaseInsensitiveKey + 'a) {
+		self
+	}
+}
+
+impl PartialEq for dyn CaseInsensitiveKey + '_ {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for dyn CaseInsensitiveKey + '_ {}
+
+impl PartialOrd for dyn CaseInsensitiveKey + '_ {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for dyn CaseInsensitiveKey + '_ {
+	fn cmp(&self, other: &Self) -> Ordering {
+		cmp_case_insensitive(self.as_str(), other.as_str())
+	}
+}
+
+macro_rules! total_float {
+	($(#[$attr:meta])* $name:ident($float:ty, $bits:ty, $signed:ty)) => {
+		$(#[$attr])*
+		#[derive(Clone, Copy, Debug, Default)]
+		pub struct $name(pub $float);
+
+		impl $name {
+			/// Map the float to an integer with the same total order.
+			fn key(self) -> $signed {
+				let bits = self.0.to_bits() as $signed;
+				// flip all bits except the sign bit of negative numbers
+				bits ^ (((bits >> (<$bits>::BITS - 1)) as $bits) >> 1) as $signed
+			}
+		}
+
+		impl From<$float> for $name {
+			fn from(value: $float) -> Self {
+				Self(value)
+			}
+		}
+
+		impl From<$name> for $float {
+			fn from(value: $name) -> Self {
+				value.0
+			}
+		}
+
+		impl PartialEq for $name {
+			fn eq(&self, other: &Self) -> bool {
+				self.key() == other.key()
+			}
+		}
+
+		impl Eq for $name {}
+
+		impl PartialOrd for $name {
+			fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+				Some(self.cmp(other))
+			}
+		}
+
+		impl Ord for $name {
+			fn cmp(&self, other: &Self) -> Ordering {
+				self.key().cmp(&other.key())
+			}
+		}
+
+		impl Hash for $name {
+			fn hash<H: Hasher>(&self, state: &mut H) {
+				self.key().hash(state);
+			}
+		}
+
+		impl Display for $name {
+			fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+				Display::fmt(&self.0, f)
+			}
+		}
+
+		#[cfg(feature = "serde")]
+		impl serde::Serialize for $name {
+			fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+				self.0.serialize(serializer)
+			}
+		}
+
+		#[cfg(feature = "serde")]
+		impl<'de> serde::Deserialize<'de> for $name {
+			fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+				<$float>::deserialize(deserializer).map(Self)
+			}
+		}
+	};
+}
+
+total_float! {
+	/// An [`f64`] that is ordered by the IEEE 754 `totalOrder` predicate, so it can be used
+	/// as the key of a map or set.
+	///
+	/// The order is the same as that of [`f64::total_cmp`]: Negative NaNs come first,
+	/// followed by negative infinity, the negative numbers, `-0.0`, `0.0`, the positive
+	/// numbers, positive infinity, and finally positive NaNs. Therefore, `-0.0` and `0.0`
+	/// are distinct keys, and NaNs are equal to each other only if they have the same bit
+	/// pattern. Normalize the values before inserting them if that is not desired.
+	///
+	/// Since [`Borrow`] requires the borrowed type to implement [`Ord`] for lookups, maps
+	/// with `TotalF64` keys cannot be queried with a plain `&f64`. However, as `TotalF64`
+	/// is [`Copy`], wrapping the value for a lookup is free.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{key::TotalF64, AATreeMap};
+	/// let mut prices = AATreeMap::new();
+	/// prices.insert(TotalF64(101.5), "ask");
+	/// prices.insert(TotalF64(99.25), "bid");
+	/// prices.insert(TotalF64(f64::NAN), "invalid");
+	/// assert_eq!(prices.get(&TotalF64(99.25)), Some(&"bid"));
+	/// assert_eq!(prices.first_key_value(), Some((&TotalF64(99.25), &"bid")));
+	/// assert_eq!(prices.last_key_value().map(|(_, v)| *v), Some("invalid"));
+	/// assert_eq!(prices.get(&100.0.into()), None);
+	/// ```
+	TotalF64(f64, u64, i64)
+}
+
+total_float! {
+	/// An [`f32`] that is ordered by the IEEE 754 `totalOrder` predicate, so it can be used
+	/// as the key of a map or set. See [`TotalF64`] for details.
+	TotalF32(f32, u32, i32)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		CaseInsensitive, CaseInsensitiveKey, Reverse, ReverseKey, Step, TotalF32,
+		TotalF64
+	};
+	use crate::{AATreeMap, AATreeSet};
+	use alloc::{string::String, vec::Vec};
+
+	#[test]
+	fn test_step_forward() {
+		assert_eq!((-100i8).forward(150), Some(50));
+		assert_eq!((-128i8).forward(255), Some(127));
+		assert_eq!((-128i8).forward(256), None);
+		assert_eq!(100i8.forward(28), None);
+		assert_eq!(i64::MIN.forward(usize::MAX), Some(i64::MAX));
+		assert_eq!(200u8.forward(55), Some(255));
+		assert_eq!(200u8.forward(56), None);
+		assert_eq!(0u8.forward(1000), None);
+		assert_eq!(0i128.forward(usize::MAX), Some(usize::MAX as i128));
+	}
+
+	#[test]
+	fn test_reverse() {
+		let set: AATreeSet<Reverse<String>> = ["b", "a", "c"]
+			.iter()
+			.map(|s| Reverse(String::from(*s)))
+			.collect();
+		let keys: Vec<&str> = set.iter().map(|s| s.0.as_str()).collect();
+		assert_eq!(keys, ["c", "b", "a"]);
+
+		for s in ["a", "b", "c"] {
+			let key: &dyn ReverseKey<str> = &Reverse(s);
+			assert_eq!(set.get(key).map(|k| k.0.as_str()), Some(s));
+		}
+		let key: &dyn ReverseKey<str> = &Reverse("bb");
+		assert!(!set.contains(key));
+		assert_eq!(set.first_at_or_after(key).map(|k| k.0.as_str()), Some("b"));
+		assert_eq!(set.last_at_or_before(key).map(|k| k.0.as_str()), Some("c"));
+	}
+
+	#[test]
+	fn test_case_insensitive() {
+		let mut map = AATreeMap::new();
+		map.insert(CaseInsensitive(String::from("b")), 1);
+		map.insert(CaseInsensitive(String::from("A")), 2);
+		map.insert(CaseInsensitive(String::from("B")), 3);
+		assert_eq!(map.len(), 2);
+		let values: Vec<i32> = map.values().copied().collect();
+		assert_eq!(values, [2, 3]);
+
+		let key: &dyn CaseInsensitiveKey = &CaseInsensitive("a");
+		assert_eq!(map.get(key), Some(&2));
+		let key: &dyn CaseInsensitiveKey = &CaseInsensitive("ÄB");
+		assert_eq!(map.get(key), None);
+		assert_ne!(CaseInsensitive("Straße"), CaseInsensitive("STRASSE"));
+	}
+
+	#[test]
+	fn test_total_float() {
+		let values = [
+			-f64::NAN,
+			f64::NEG_INFINITY,
+			f64::MIN,
+			-1.0,
+			-f64::MIN_POSITIVE,
+			-0.0,
+			0.0,
+			f64::MIN_POSITIVE,
+			1.0,
+			f64::MAX,
+			f64::INFINITY,
+			f64::NAN
+		];
+		for (i, a) in values.iter().enumerate() {
+			for (j, b) in values.iter().enumerate() {
+				assert_eq!(TotalF64(*a).cmp(&TotalF64(*b)), i.cmp(&j), "{a} <=> {b}");
+			}
+		}
+		let values = [
+			-f32::NAN,
+			f32::NEG_INFINITY,
+			-1.0,
+			-0.0,
+			0.0,
+			f32::MIN_POSITIVE,
+			f32::INFINITY,
+			f32::NAN
+		];
+		for (i, a) in values.iter().enumerate() {
+			for (j, b) in values.iter().enumerate() {
+				assert_eq!(TotalF32(*a).cmp(&TotalF32(*b)), i.cmp(&j), "{a} <=> {b}");
+			}
+		}
+
+		let set: AATreeSet<TotalF64> = [2.0, f64::NAN, 1.0, -0.0, 0.0, -3.5]
+			.into_iter()
+			.map(TotalF64)
+			.collect();
+		assert_eq!(set.len(), 6);
+		assert!(set.contains(&TotalF64(f64::NAN)));
+		assert_eq!(set.first_at_or_after(&TotalF64(0.5)), Some(&TotalF64(1.0)));
+	}
+}
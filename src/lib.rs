@@ -3,27 +3,131 @@
 #![warn(missing_debug_implementations, rust_2018_idioms)]
 #![deny(elided_lifetimes_in_paths, unreachable_pub, unsafe_code)]
 // clippy doesn't like our code style
-#![cfg_attr(feature = "cargo-clippy", allow(clippy::tabs_in_doc_comments))]
+#![allow(clippy::tabs_in_doc_comments)]
 
 //! AA-Tree implementation in Rust.
 //!
 //! An AA-Tree is a self-balancing binary search tree based on a RedBlack-Tree
 //! with a simplified self-balancing logic that should benefit performance.
+//!
+//! # Real-time use
+//!
+//! Once a set or map has been constructed, the following read operations never allocate
+//! and never panic, unless the [`Ord`] implementation of the key type does:
+//!
+//!  - point queries like [`AATreeMap::get`], [`AATreeMap::contains_key`] and
+//!    [`AATreeSet::contains`],
+//!  - bound queries like [`AATreeMap::first_key_value`],
+//!    [`AATreeMap::first_key_value_at_or_after`] and [`AATreeSet::last_at_or_before`],
+//!  - advancing an iterator returned by [`AATreeMap::iter`] or [`AATreeSet::iter`].
+//!
+//! Creating an iterator allocates its stack once, with a capacity proportional to the
+//! height of the tree. Create iterators outside the hot path if that is a concern.
+//! All of these operations take O(log n) time in the worst case, except for advancing an
+//! iterator, which takes amortized O(1) time.
 #![cfg_attr(feature = "document-features", doc = concat!(
 	"\n\n## Features\n",
 	document_features::document_features!()
 ))]
 
 extern crate alloc;
+#[cfg(all(feature = "std", not(any(doc, test))))]
+extern crate std;
+
+/// Construct an [`AANode`](node::AANode) with explicit levels, e.g. to create test
+/// fixtures for algorithms that operate on the tree structure.
+///
+/// `Nil` (or no input) creates an empty tree, and a single expression creates a leaf at
+/// level 1. All other nodes are written as `content => [level, left, right]`, where the
+/// children are again written in this syntax, wrapped in parentheses unless they are
+/// `Nil` or a leaf. This is the same syntax as used by
+/// [`AANode::to_canonical_string`](node::AANode::to_canonical_string).
+///
+/// It is the caller's responsibility to ensure that the resulting tree is a valid AA
+/// tree.
+///
+/// # Example
+///
+/// ```rust
+/// use aatree::{aanode, node::AANode};
+///
+/// let mut root = AANode::new();
+/// for content in ['A', 'B', 'C'] {
+/// 	root.insert(content);
+/// }
+/// assert_eq!(root, aanode!('B' => [2, 'A', 'C']));
+///
+/// let root = aanode!('D' => [3, ('B' => [2, 'A', 'C']), ('F' => [2, Nil, 'G'])]);
+/// assert_eq!(root.to_canonical_string(), "D => [3, (B => [2, A, C]), (F => [2, Nil, G])]");
+/// ```
+#[macro_export]
+macro_rules! aanode {
+	() => {
+		$crate::node::AANode::new()
+	};
+	(Nil) => {
+		$crate::node::AANode::new()
+	};
+	($content:expr) => {
+		$crate::node::AANode::from($content)
+	};
+	($content:expr => [$level:expr, $left:tt, $right:tt]) => {
+		$crate::node::AANode::with_children(
+			$content,
+			$level,
+			$crate::aanode!(@internal $left),
+			$crate::aanode!(@internal $right)
+		)
+	};
+	(@internal ($content:expr => [$level:expr, $left:tt, $right:tt])) => {
+		$crate::aanode!($content => [$level, $left, $right])
+	};
+	(@internal $inner:tt) => {
+		$crate::aanode!($inner)
+	};
+}
 
+#[cfg(feature = "chunked")]
+pub mod chunked;
+pub mod counted;
+pub mod fmt;
+pub mod frozen;
+#[cfg(feature = "graph")]
+pub mod graph;
+pub mod id;
+pub mod insertion;
 pub mod iter;
+pub mod journal;
+pub mod key;
 pub mod map;
+pub mod minmax;
 pub mod node;
 #[cfg(feature = "openapi")]
 mod openapi;
+#[cfg(feature = "orderbook")]
+pub mod orderbook;
+#[cfg(feature = "parse")]
+pub mod parse;
+pub mod prelude;
+pub mod search;
 #[cfg(feature = "serde")]
-mod serde;
+pub mod serde;
 pub mod set;
+pub mod sorted;
+pub mod staged;
+#[cfg(feature = "testalloc")]
+pub mod testalloc;
+#[cfg(feature = "testutil")]
+pub mod testutil;
+pub mod topk;
+pub mod traits;
 
+pub use counted::CountedSet;
+pub use frozen::FrozenAATreeMap;
+pub use id::AAIdAllocator;
+pub use insertion::InsertionOrderMap;
 pub use map::AATreeMap;
+pub use minmax::MinMaxMap;
 pub use set::AATreeSet;
+pub use staged::StagedMap;
+pub use topk::AATopK;
@@ -0,0 +1,203 @@
+//! This file defines batch modifications for [`AATreeMap`].
+
+use super::AATreeMap;
+use crate::node::should_rebuild;
+use alloc::vec::Vec;
+use core::{borrow::Borrow, mem};
+
+/// A modification of a single key, applied by [`AATreeMap::apply_sorted`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BulkOp<V> {
+	/// Insert the value, or replace the existing value of the key.
+	Insert(V),
+	/// Remove the key if it exists.
+	Remove
+}
+
+impl<K: Ord, V> AATreeMap<K, V> {
+	/// Remove all `keys` from the map and return the removed entries in ascending order.
+	/// Keys that are not part of the map are ignored.
+	///
+	/// If the number of keys is large compared to the size of the map, the tree is
+	/// rebuilt from the remaining entries in a single pass instead of removing the keys
+	/// one by one. The keys are sorted first unless they are already in ascending order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map: AATreeMap<u32, char> = (0 .. 5).zip('a' ..).collect();
+	/// assert_eq!(map.remove_many(&[3, 1, 7]), [(1, 'b'), (3, 'd')]);
+	/// assert_eq!(map.keys().collect::<Vec<_>>(), [&0, &2, &4]);
+	/// ```
+	pub fn remove_many<'a, Q, I>(&mut self, keys: I) -> Vec<(K, V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized + 'a,
+		I: IntoIterator<Item = &'a Q>
+	{
+		let mut keys: Vec<&Q> = keys.into_iter().collect();
+		if keys.windows(2).any(|w| w[0] > w[1]) {
+			keys.sort_unstable();
+		}
+		keys.dedup();
+
+		if !should_rebuild(self.len, keys.len()) {
+			return keys
+				.into_iter()
+				.filter_map(|key| self.remove_entry(key))
+				.collect();
+		}
+
+		let mut removed = Vec::new();
+		let mut kept = Vec::with_capacity(self.len.saturating_sub(keys.len()));
+		let mut keys = keys.into_iter().peekable();
+		for (key, value) in mem::take(self) {
+			while keys.next_if(|k| *k < key.borrow()).is_some() {}
+			if keys.next_if(|k| *k == key.borrow()).is_some() {
+				removed.push((key, value));
+			} else {
+				kept.push((key, value));
+			}
+		}
+		let len = kept.len();
+		*self = Self::from_sorted_unchecked(kept, len);
+		removed
+	}
+
+	/// Apply a stream of modifications to the map. The keys should be in ascending
+	/// order; modifications of the same key are applied in the order they appear.
+	///
+	/// If the number of modifications is large compared to the size of the map, they
+	/// are merged with the existing entries in a single pass and the tree is rebuilt in
+	/// O(n). Otherwise, or if the keys are not in ascending order, the modifications are
+	/// applied one by one.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, map::BulkOp};
+	/// let mut map = AATreeMap::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+	/// map.apply_sorted([
+	/// 	(0, BulkOp::Insert('z')),
+	/// 	(2, BulkOp::Remove),
+	/// 	(3, BulkOp::Insert('C'))
+	/// ]);
+	/// assert_eq!(map, AATreeMap::from([(0, 'z'), (1, 'a'), (3, 'C')]));
+	/// ```
+	pub fn apply_sorted<I>(&mut self, ops: I)
+	where
+		I: IntoIterator<Item = (K, BulkOp<V>)>
+	{
+		let ops: Vec<(K, BulkOp<V>)> = ops.into_iter().collect();
+		let sorted = ops.windows(2).all(|w| w[0].0 <= w[1].0);
+		if !sorted || !should_rebuild(self.len, ops.len()) {
+			for (key, op) in ops {
+				match op {
+					BulkOp::Insert(value) => {
+						self.insert(key, value);
+					},
+					BulkOp::Remove => {
+						self.remove(&key);
+					}
+				}
+			}
+			return;
+		}
+
+		let mut entries = mem::take(self).into_iter().peekable();
+		let mut merged: Vec<(K, V)> = Vec::with_capacity(entries.len() + ops.len());
+		for (key, op) in ops {
+			while let Some(entry) = entries.next_if(|(k, _)| *k < key) {
+				merged.push(entry);
+			}
+			// drop the current entry of this key, which is either the result of a
+			// previous modification of the same key or an existing entry
+			if merged.last().map(|(k, _)| *k == key).unwrap_or(false) {
+				merged.pop();
+			} else {
+				entries.next_if(|(k, _)| *k == key);
+			}
+			if let BulkOp::Insert(value) = op {
+				merged.push((key, value));
+			}
+		}
+		merged.extend(entries);
+		let len = merged.len();
+		*self = Self::from_sorted_unchecked(merged, len);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::BulkOp;
+	use crate::AATreeMap;
+	use alloc::{collections::BTreeMap, vec::Vec};
+
+	#[test]
+	fn test_remove_many() {
+		for (len, keys) in [(1000, 10), (1000, 300), (1000, 2000), (0, 5), (5, 0)] {
+			let mut map: AATreeMap<u32, u32> = (0 .. len).map(|i| (i * 2, i)).collect();
+			let mut reference: BTreeMap<u32, u32> =
+				map.iter().map(|(k, v)| (*k, *v)).collect();
+			let mut state = len ^ keys;
+			let keys: Vec<u32> = (0 .. keys)
+				.map(|_| {
+					state = state.wrapping_mul(1103515245).wrapping_add(12345);
+					(state >> 8) % (len * 2 + 1)
+				})
+				.collect();
+
+			let removed = map.remove_many(&keys);
+			let mut expected: Vec<(u32, u32)> = keys
+				.iter()
+				.filter_map(|k| reference.remove_entry(k))
+				.collect();
+			expected.sort_unstable();
+			assert_eq!(removed, expected);
+			map.assert_valid();
+			assert!(map.iter().eq(reference.iter()));
+		}
+	}
+
+	#[test]
+	fn test_apply_sorted() {
+		for (len, ops, sorted) in [
+			(1000, 10, true),
+			(1000, 500, true),
+			(100, 1000, true),
+			(100, 500, false),
+			(0, 50, true)
+		] {
+			let mut map: AATreeMap<u32, u32> = (0 .. len).map(|i| (i * 2, i)).collect();
+			let mut reference: BTreeMap<u32, u32> =
+				map.iter().map(|(k, v)| (*k, *v)).collect();
+			let mut state = len ^ ops;
+			let mut ops: Vec<(u32, BulkOp<u32>)> = (0 .. ops)
+				.map(|i| {
+					state = state.wrapping_mul(1103515245).wrapping_add(12345);
+					let key = (state >> 8) % (len * 2 + 1);
+					let op = if state % 3 == 0 {
+						BulkOp::Remove
+					} else {
+						BulkOp::Insert(i)
+					};
+					(key, op)
+				})
+				.collect();
+			if sorted {
+				ops.sort_by_key(|(k, _)| *k);
+			}
+
+			for (key, op) in &ops {
+				match op {
+					BulkOp::Insert(value) => reference.insert(*key, *value),
+					BulkOp::Remove => reference.remove(key)
+				};
+			}
+			map.apply_sorted(ops);
+			map.assert_valid();
+			assert!(map.iter().eq(reference.iter()));
+		}
+	}
+}
@@ -0,0 +1,144 @@
+//! This file defines nearest-neighbour queries for [`AATreeMap`].
+
+use super::AATreeMap;
+use core::{borrow::Borrow, cmp::Ordering, ops::Sub};
+
+/// Decides which key to return from [`AATreeMap::closest_key`] if the predecessor and
+/// successor of the queried key have the same distance.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Tie {
+	/// Prefer the smaller key.
+	Lower,
+	/// Prefer the larger key.
+	Upper
+}
+
+/// Returns whichever of `lower` and `upper` is closer to `key`.
+pub(crate) fn closest<'a, K, Q, D>(
+	key: &Q,
+	lower: Option<&'a K>,
+	upper: Option<&'a K>,
+	tie: Tie
+) -> Option<&'a K>
+where
+	K: Borrow<Q>,
+	Q: ?Sized,
+	for<'q> &'q Q: Sub<&'q Q, Output = D>,
+	D: Ord
+{
+	match (lower, upper) {
+		(Some(lower), Some(upper)) => {
+			let lower_dist = key - lower.borrow();
+			let upper_dist = upper.borrow() - key;
+			Some(match (lower_dist.cmp(&upper_dist), tie) {
+				(Ordering::Less, _) | (Ordering::Equal, Tie::Lower) => lower,
+				(Ordering::Greater, _) | (Ordering::Equal, Tie::Upper) => upper
+			})
+		},
+		(lower, upper) => lower.or(upper)
+	}
+}
+
+impl<K: Ord, V> AATreeMap<K, V> {
+	/// Returns the key that is closest to `key`, which is either the key itself if it is
+	/// present, or whichever of its predecessor and successor is nearer. If both are
+	/// equally far away, `tie` decides which one is returned.
+	///
+	/// The distance is computed by subtracting the smaller from the larger key, so for
+	/// signed integers the distance must not overflow.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{map::Tie, AATreeMap};
+	/// let map = AATreeMap::from([(10, "a"), (20, "b"), (40, "c")]);
+	/// assert_eq!(map.closest_key(&13, Tie::Lower), Some(&10));
+	/// assert_eq!(map.closest_key(&30, Tie::Lower), Some(&20));
+	/// assert_eq!(map.closest_key(&30, Tie::Upper), Some(&40));
+	/// assert_eq!(map.closest_key(&100, Tie::Lower), Some(&40));
+	/// ```
+	pub fn closest_key<Q, D>(&self, key: &Q, tie: Tie) -> Option<&K>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized,
+		for<'q> &'q Q: Sub<&'q Q, Output = D>,
+		D: Ord
+	{
+		let lower = self.last_key_value_at_or_before(key).map(|(k, _)| k);
+		if let Some(lower) = lower {
+			if lower.borrow() == key {
+				return Some(lower);
+			}
+		}
+		let upper = self.first_key_value_at_or_after(key).map(|(k, _)| k);
+		closest(key, lower, upper, tie)
+	}
+
+	/// Returns the key itself if it is present, otherwise snaps `key` to the nearest key
+	/// within the key range of this map. Keys below or above all keys of this map saturate
+	/// to the smallest or largest key, respectively. If the predecessor and successor of
+	/// `key` are equally far away, the smaller key is returned. Only returns `None` if the
+	/// map is empty.
+	///
+	/// This is equivalent to [`closest_key`](Self::closest_key) with [`Tie::Lower`].
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(10, "a"), (20, "b"), (40, "c")]);
+	/// assert_eq!(map.clamp_key(&20), Some(&20));
+	/// assert_eq!(map.clamp_key(&30), Some(&20));
+	/// assert_eq!(map.clamp_key(&35), Some(&40));
+	/// assert_eq!(map.clamp_key(&5), Some(&10));
+	/// assert_eq!(map.clamp_key(&50), Some(&40));
+	/// ```
+	pub fn clamp_key<Q, D>(&self, key: &Q) -> Option<&K>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized,
+		for<'q> &'q Q: Sub<&'q Q, Output = D>,
+		D: Ord
+	{
+		self.closest_key(key, Tie::Lower)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Tie;
+	use crate::AATreeMap;
+
+	#[test]
+	fn test_closest_key_empty() {
+		let map = AATreeMap::<u32, ()>::new();
+		assert_eq!(map.closest_key(&1, Tie::Lower), None);
+		assert_eq!(map.clamp_key(&1), None);
+	}
+
+	#[test]
+	fn test_closest_key_exhaustive() {
+		let map: AATreeMap<i32, ()> =
+			[-7, -2, 0, 3, 9, 10, 15].iter().map(|&k| (k, ())).collect();
+		for q in -20 .. 30 {
+			for tie in [Tie::Lower, Tie::Upper] {
+				let expected = map.keys().min_by_key(|k| {
+					((*k - q).abs(), if tie == Tie::Lower { **k } else { -**k })
+				});
+				assert_eq!(map.closest_key(&q, tie), expected, "closest to {q}");
+			}
+		}
+	}
+
+	#[test]
+	fn test_clamp_key_nearest() {
+		let map: AATreeMap<i32, ()> = [10, 20, 40].iter().map(|&k| (k, ())).collect();
+		assert_eq!(map.clamp_key(&35), Some(&40));
+		assert_eq!(map.clamp_key(&25), Some(&20));
+		assert_eq!(map.clamp_key(&30), Some(&20));
+		for q in 0 .. 50 {
+			let expected = map.keys().min_by_key(|k| ((*k - q).abs(), **k));
+			assert_eq!(map.clamp_key(&q), expected, "clamp {q}");
+		}
+	}
+}
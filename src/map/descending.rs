@@ -0,0 +1,198 @@
+//! This file defines a view of a map in descending key order.
+
+use super::{AATreeMap, KeyValue};
+use crate::iter::AARevIter;
+use core::{
+	borrow::Borrow,
+	fmt::{self, Debug},
+	ops::Bound
+};
+
+/// A read-only view of an [`AATreeMap`] that sees its entries in descending key order.
+/// Returned by [`AATreeMap::descending`].
+///
+/// All methods of this view are mirrored, so that the first entry is the one with the
+/// largest key, and "after" means "smaller". This is useful e.g. for max-first priority
+/// queues without wrapping every key in [`Reverse`](crate::key::Reverse).
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::AATreeMap;
+/// let map = AATreeMap::from([(1, "a"), (2, "b"), (3, "c")]);
+/// let desc = map.descending();
+/// assert_eq!(desc.first_key_value(), Some((&3, &"c")));
+/// assert_eq!(desc.first_key_value_at_or_after(&2), Some((&2, &"b")));
+/// assert_eq!(desc.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [3, 2, 1]);
+/// ```
+pub struct Descending<'a, K, V> {
+	map: &'a AATreeMap<K, V>
+}
+
+impl<K, V> Clone for Descending<'_, K, V> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<K, V> Copy for Descending<'_, K, V> {}
+
+impl<K: Debug, V: Debug> Debug for Descending<'_, K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+impl<K, V> AATreeMap<K, V> {
+	/// Creates an iterator over this map that visits all entries with the keys in
+	/// descending order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(1, "a"), (3, "c"), (2, "b")]);
+	/// let mut iter = map.iter_descending();
+	/// assert_eq!(iter.next(), Some((&3, &"c")));
+	/// assert_eq!(iter.next(), Some((&2, &"b")));
+	/// assert_eq!(iter.next(), Some((&1, &"a")));
+	/// assert_eq!(iter.next(), None);
+	/// ```
+	pub fn iter_descending(&self) -> AARevIter<'_, KeyValue<K, V>, (&K, &V)> {
+		AARevIter::new(&self.root, self.len)
+	}
+
+	/// Returns a view of this map in descending key order.
+	pub fn descending(&self) -> Descending<'_, K, V> {
+		Descending { map: self }
+	}
+}
+
+impl<'a, K, V> Descending<'a, K, V> {
+	/// Returns the number of entries in the map.
+	pub fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	/// Returns `true` if the map contains no entries.
+	pub fn is_empty(&self) -> bool {
+		self.map.is_empty()
+	}
+
+	/// Creates an iterator that visits all entries with the keys in descending order.
+	pub fn iter(&self) -> AARevIter<'a, KeyValue<K, V>, (&'a K, &'a V)> {
+		self.map.iter_descending()
+	}
+
+	/// Returns a reference to the value corresponding to the key.
+	pub fn get<Q>(&self, key: &Q) -> Option<&'a V>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.map.get(key)
+	}
+
+	/// Returns the entry with the largest key.
+	pub fn first_key_value(&self) -> Option<(&'a K, &'a V)>
+	where
+		K: Ord
+	{
+		self.map.last_key_value()
+	}
+
+	/// Returns the entry with the smallest key.
+	pub fn last_key_value(&self) -> Option<(&'a K, &'a V)>
+	where
+		K: Ord
+	{
+		self.map.first_key_value()
+	}
+
+	/// Returns the entry with the largest key that is smaller than or equal to `k`.
+	pub fn first_key_value_at_or_after<Q>(&self, k: &Q) -> Option<(&'a K, &'a V)>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.map.last_key_value_at_or_before(k)
+	}
+
+	/// Returns the entry with the smallest key that is greater than or equal to `k`.
+	pub fn last_key_value_at_or_before<Q>(&self, k: &Q) -> Option<(&'a K, &'a V)>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.map.first_key_value_at_or_after(k)
+	}
+
+	/// Returns the first entry in descending order whose key is within a range starting
+	/// at `bound`, that is, the entry with the largest key that is smaller than (or equal
+	/// to, if the bound is inclusive) the bound.
+	pub fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<(&'a K, &'a V)>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.map.upper_bound(bound)
+	}
+
+	/// Returns the last entry in descending order whose key is within a range ending at
+	/// `bound`, that is, the entry with the smallest key that is greater than (or equal
+	/// to, if the bound is inclusive) the bound.
+	pub fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<(&'a K, &'a V)>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.map.lower_bound(bound)
+	}
+}
+
+impl<'a, K, V> IntoIterator for Descending<'a, K, V> {
+	type Item = (&'a K, &'a V);
+	type IntoIter = AARevIter<'a, KeyValue<K, V>, (&'a K, &'a V)>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::vec::Vec;
+	use core::ops::Bound;
+
+	#[test]
+	fn test_iter_descending() {
+		for len in 0 .. 50 {
+			let map: AATreeMap<u32, u32> = (0 .. len).map(|x| (x, x * x)).collect();
+			let mut expected: Vec<_> = map.iter().collect();
+			expected.reverse();
+			let iter = map.iter_descending();
+			assert_eq!(iter.len(), len as usize);
+			assert_eq!(iter.collect::<Vec<_>>(), expected);
+		}
+	}
+
+	#[test]
+	fn test_descending() {
+		let map: AATreeMap<u32, char> = (0 .. 5).map(|x| x * 10).zip('a' ..).collect();
+		let desc = map.descending();
+		assert_eq!(desc.len(), 5);
+		assert_eq!(desc.first_key_value(), Some((&40, &'e')));
+		assert_eq!(desc.last_key_value(), Some((&0, &'a')));
+		assert_eq!(desc.first_key_value_at_or_after(&25), Some((&20, &'c')));
+		assert_eq!(desc.last_key_value_at_or_before(&25), Some((&30, &'d')));
+		assert_eq!(desc.lower_bound(Bound::Excluded(&20)), Some((&10, &'b')));
+		assert_eq!(desc.upper_bound(Bound::Excluded(&20)), Some((&30, &'d')));
+		assert_eq!(desc.lower_bound::<u32>(Bound::Unbounded), Some((&40, &'e')));
+		assert_eq!(desc.get(&30), Some(&'d'));
+		assert_eq!(
+			alloc::format!("{desc:?}"),
+			"{40: 'e', 30: 'd', 20: 'c', 10: 'b', 0: 'a'}"
+		);
+	}
+}
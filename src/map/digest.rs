@@ -0,0 +1,160 @@
+//! This file defines range digests for [`AATreeMap`].
+
+use super::{AATreeMap, KeyValue};
+use crate::node::AANode;
+use core::{
+	borrow::Borrow,
+	hash::{Hash, Hasher},
+	ops::{Bound, RangeBounds}
+};
+
+/// The 64-bit FNV-1a hash function. It is not resistant against collision attacks, but it
+/// is deterministic, unlike the default hasher of the standard library.
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+	fn default() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl Hasher for FnvHasher {
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= *byte as u64;
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+}
+
+/// Hash all entries of the subtree `node` that are within `range`, in ascending order.
+fn digest_node<K, V, Q, R>(
+	node: &AANode<KeyValue<K, V>>,
+	range: &R,
+	hasher: &mut FnvHasher
+) where
+	K: Borrow<Q> + Hash,
+	V: Hash,
+	Q: Ord + ?Sized,
+	R: RangeBounds<Q>
+{
+	let node = match node.as_ref() {
+		Some(node) => node,
+		None => return
+	};
+	let key = node.content.key.borrow();
+	let after_start = match range.start_bound() {
+		Bound::Included(start) => key >= start,
+		Bound::Excluded(start) => key > start,
+		Bound::Unbounded => true
+	};
+	let before_end = match range.end_bound() {
+		Bound::Included(end) => key <= end,
+		Bound::Excluded(end) => key < end,
+		Bound::Unbounded => true
+	};
+
+	if after_start {
+		digest_node(&node.left_child, range, hasher);
+	}
+	if after_start && before_end {
+		node.content.key.hash(hasher);
+		node.content.value.hash(hasher);
+	}
+	if before_end {
+		digest_node(&node.right_child, range, hasher);
+	}
+}
+
+impl<K: Ord, V> AATreeMap<K, V> {
+	/// Compute a digest of all entries within `range`. Two ranges with the same entries
+	/// have the same digest, independent of the entries outside of the range and of the
+	/// shape of the tree. This allows caches to detect whether a range has changed since
+	/// it was last read.
+	///
+	/// The digest is computed in O(log n + k) time, where k is the number of entries in
+	/// the range, using the non-cryptographic FNV-1a hash function. It is deterministic,
+	/// but might differ between platforms and versions of this crate.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::from([(1, "a"), (5, "b"), (9, "c")]);
+	/// let digest = map.range_digest(0 .. 6);
+	///
+	/// map.insert(7, "d");
+	/// assert_eq!(map.range_digest(0 .. 6), digest);
+	///
+	/// map.insert(5, "e");
+	/// assert_ne!(map.range_digest(0 .. 6), digest);
+	/// ```
+	pub fn range_digest<Q, R>(&self, range: R) -> u64
+	where
+		K: Borrow<Q> + Hash,
+		V: Hash,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		let mut hasher = FnvHasher::default();
+		digest_node(&self.root, &range, &mut hasher);
+		hasher.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::FnvHasher;
+	use crate::{AATreeMap, AATreeSet};
+	use core::{
+		hash::{Hash, Hasher},
+		ops::Bound
+	};
+
+	#[test]
+	fn test_range_digest() {
+		let map: AATreeMap<u32, u32> = (0 .. 100).map(|i| (i, i * i)).collect();
+		let mut hasher = FnvHasher::default();
+		for i in 10 .. 20u32 {
+			i.hash(&mut hasher);
+			(i * i).hash(&mut hasher);
+		}
+		assert_eq!(map.range_digest(10 .. 20), hasher.finish());
+		assert_eq!(map.range_digest(10 ..= 19), hasher.finish());
+		assert_eq!(
+			map.range_digest((Bound::Excluded(9), Bound::Excluded(20))),
+			hasher.finish()
+		);
+
+		let other: AATreeMap<u32, u32> = (5 .. 25).map(|i| (i, i * i)).collect();
+		assert_eq!(other.range_digest(10 .. 20), hasher.finish());
+		assert_eq!(map.range_digest(..), map.range_digest(0 .. 100));
+		assert_eq!(map.range_digest(200 ..), FnvHasher::default().finish());
+	}
+
+	#[test]
+	fn test_hash() {
+		fn hash<T: Hash>(value: &T) -> u64 {
+			let mut hasher = FnvHasher::default();
+			value.hash(&mut hasher);
+			hasher.finish()
+		}
+
+		let ascending: AATreeMap<u32, u32> = (0 .. 100).map(|x| (x, x * 2)).collect();
+		let descending: AATreeMap<u32, u32> =
+			(0 .. 100).rev().map(|x| (x, x * 2)).collect();
+		assert_eq!(hash(&ascending), hash(&descending));
+		assert_eq!(
+			hash(&ascending.keys().copied().collect::<AATreeSet<_>>()),
+			hash(&descending.keys().copied().collect::<AATreeSet<_>>())
+		);
+
+		let mut other = ascending.clone();
+		other.insert(5, 0);
+		assert_ne!(hash(&ascending), hash(&other));
+	}
+}
@@ -1,6 +1,8 @@
 use super::{AATreeMap, KeyValue};
+use alloc::borrow::ToOwned;
 use core::{
-	fmt::{self, Debug, Formatter},
+	borrow::Borrow,
+	fmt::{self, Debug, Display, Formatter},
 	mem
 };
 
@@ -38,29 +40,26 @@ impl<'a, K, V> Entry<'a, K, V> {
 
 	pub fn or_insert(self, default: V) -> &'a mut V
 	where
-		K: Ord + Clone
+		K: Ord
 	{
-		// TODO remove K: Clone once this doesn't require it anymore
 		self.or_insert_with(|| default)
 	}
 
 	pub fn or_insert_with<F>(self, default: F) -> &'a mut V
 	where
 		F: FnOnce() -> V,
-		K: Ord + Clone
+		K: Ord
 	{
-		// TODO remove K: Clone once this doesn't require it anymore
 		self.or_insert_with_key(|_| default())
 	}
 
 	pub fn or_insert_with_key<F>(self, default: F) -> &'a mut V
 	where
 		F: FnOnce(&K) -> V,
-		K: Ord + Clone
+		K: Ord
 	{
 		match self {
 			Self::Occupied(entry) => entry.into_mut(),
-			// TODO remove K: Clone once this doesn't require it anymore
 			Self::Vacant(entry) => {
 				let value = default(entry.key());
 				entry.insert(value)
@@ -70,12 +69,38 @@ impl<'a, K, V> Entry<'a, K, V> {
 
 	pub fn or_default(self) -> &'a mut V
 	where
-		K: Ord + Clone,
+		K: Ord,
 		V: Default
 	{
-		// TODO remove K: Clone once this doesn't require it anymore
 		self.or_insert_with(V::default)
 	}
+
+	/// Set the value of the entry, and return an [`OccupiedEntry`] to keep working with
+	/// it.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::new();
+	/// let mut entry = map.entry("a").insert_entry(1);
+	/// *entry.get_mut() += 1;
+	/// assert_eq!(entry.get(), &2);
+	/// assert_eq!(map.entry("a").insert_entry(5).key(), &"a");
+	/// assert_eq!(map.get("a"), Some(&5));
+	/// ```
+	pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V>
+	where
+		K: Ord
+	{
+		match self {
+			Self::Occupied(mut entry) => {
+				entry.insert(value);
+				entry
+			},
+			Self::Vacant(entry) => entry.insert_entry(value)
+		}
+	}
 }
 
 pub struct OccupiedEntry<'a, K, V> {
@@ -113,6 +138,81 @@ impl<K: Debug, V: Debug> Debug for OccupiedEntry<'_, K, V> {
 	}
 }
 
+/// The first or last entry of a map, returned by
+/// [`pop_first_entry`](AATreeMap::pop_first_entry) and
+/// [`pop_last_entry`](AATreeMap::pop_last_entry). It can be inspected and modified in
+/// place, and removed from the map using [`remove`](Self::remove).
+///
+/// The entry is detached from the tree when the handle is created, which descends the
+/// left or right spine of the tree once without comparing any keys. Inspecting,
+/// modifying and removing the entry afterwards takes O(1). If the handle is dropped or
+/// turned into a reference using [`into_mut`](Self::into_mut) instead, the entry is
+/// inserted back into the map in O(log n).
+pub struct PopEntry<'a, K: Ord, V> {
+	/// This is only `None` once the entry has been removed or inserted back.
+	inner: Option<Detached<'a, K, V>>
+}
+
+/// A map together with its detached first or last entry.
+type Detached<'a, K, V> = (&'a mut AATreeMap<K, V>, KeyValue<K, V>);
+
+impl<'a, K: Ord, V> PopEntry<'a, K, V> {
+	fn entry(&self) -> &KeyValue<K, V> {
+		&self.inner.as_ref().expect("entry was already taken").1
+	}
+
+	fn entry_mut(&mut self) -> &mut KeyValue<K, V> {
+		&mut self.inner.as_mut().expect("entry was already taken").1
+	}
+
+	pub fn key(&self) -> &K {
+		&self.entry().key
+	}
+
+	pub fn get(&self) -> &V {
+		&self.entry().value
+	}
+
+	pub fn get_mut(&mut self) -> &mut V {
+		&mut self.entry_mut().value
+	}
+
+	/// Insert the entry back into the map and return a mutable reference to its value.
+	pub fn into_mut(mut self) -> &'a mut V {
+		let (map, KeyValue { key, value }) =
+			self.inner.take().expect("entry was already taken");
+		&mut map.insert_vacant(key, value).value
+	}
+
+	pub fn insert(&mut self, value: V) -> V {
+		mem::replace(self.get_mut(), value)
+	}
+
+	/// Remove the entry from the map and return its key and value, so that they can be
+	/// reused.
+	pub fn remove(mut self) -> (K, V) {
+		let (_, entry) = self.inner.take().expect("entry was already taken");
+		entry.into_tuple()
+	}
+}
+
+impl<K: Ord, V> Drop for PopEntry<'_, K, V> {
+	fn drop(&mut self) {
+		if let Some((map, KeyValue { key, value })) = self.inner.take() {
+			map.insert_vacant(key, value);
+		}
+	}
+}
+
+impl<K: Ord + Debug, V: Debug> Debug for PopEntry<'_, K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("PopEntry")
+			.field("key", self.key())
+			.field("value", self.get())
+			.finish()
+	}
+}
+
 pub struct VacantEntry<'a, K, V> {
 	pub(crate) key: K,
 	pub(crate) map: &'a mut AATreeMap<K, V>
@@ -129,11 +229,20 @@ impl<'a, K, V> VacantEntry<'a, K, V> {
 
 	pub fn insert(self, value: V) -> &'a mut V
 	where
-		K: Ord + Clone
+		K: Ord
+	{
+		&mut self.map.insert_vacant(self.key, value).value
+	}
+
+	/// Insert the entry into the map, and return an [`OccupiedEntry`] to keep working
+	/// with it.
+	pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V>
+	where
+		K: Ord
 	{
-		// TODO properly return a reference and stop cloning the key
-		self.map.insert(self.key.clone(), value);
-		self.map.get_mut(&self.key).unwrap()
+		OccupiedEntry {
+			entry: self.map.insert_vacant(self.key, value)
+		}
 	}
 }
 
@@ -144,3 +253,343 @@ impl<K: Debug, V: Debug> Debug for VacantEntry<'_, K, V> {
 			.finish()
 	}
 }
+
+/// An entry of a map that was looked up by a borrowed key, returned by
+/// [`AATreeMap::entry_ref`]. The owned key is only created if a vacant entry is
+/// inserted.
+pub enum EntryRef<'a, 'b, K, Q: ?Sized, V> {
+	Vacant(VacantEntryRef<'a, 'b, K, Q, V>),
+	Occupied(OccupiedEntry<'a, K, V>)
+}
+
+impl<K: Debug, Q: Debug + ?Sized, V: Debug> Debug for EntryRef<'_, '_, K, Q, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Vacant(v) => f.debug_tuple("EntryRef").field(v).finish(),
+			Self::Occupied(o) => f.debug_tuple("EntryRef").field(o).finish()
+		}
+	}
+}
+
+impl<'a, 'b, K, Q: ?Sized, V> EntryRef<'a, 'b, K, Q, V> {
+	pub fn key(&self) -> &Q
+	where
+		K: Borrow<Q>
+	{
+		match self {
+			Self::Vacant(entry) => entry.key(),
+			Self::Occupied(entry) => entry.key().borrow()
+		}
+	}
+
+	pub fn and_modify<F>(mut self, f: F) -> Self
+	where
+		F: FnOnce(&mut V)
+	{
+		if let Self::Occupied(entry) = &mut self {
+			f(entry.get_mut());
+		}
+		self
+	}
+
+	pub fn or_insert(self, default: V) -> &'a mut V
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ToOwned<Owned = K>
+	{
+		self.or_insert_with(|| default)
+	}
+
+	pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+	where
+		F: FnOnce() -> V,
+		K: Borrow<Q> + Ord,
+		Q: Ord + ToOwned<Owned = K>
+	{
+		self.or_insert_with_key(|_| default())
+	}
+
+	pub fn or_insert_with_key<F>(self, default: F) -> &'a mut V
+	where
+		F: FnOnce(&Q) -> V,
+		K: Borrow<Q> + Ord,
+		Q: Ord + ToOwned<Owned = K>
+	{
+		match self {
+			Self::Occupied(entry) => entry.into_mut(),
+			Self::Vacant(entry) => {
+				let value = default(entry.key());
+				entry.insert(value)
+			}
+		}
+	}
+
+	pub fn or_default(self) -> &'a mut V
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ToOwned<Owned = K>,
+		V: Default
+	{
+		self.or_insert_with(V::default)
+	}
+}
+
+pub struct VacantEntryRef<'a, 'b, K, Q: ?Sized, V> {
+	pub(crate) key: &'b Q,
+	pub(crate) map: &'a mut AATreeMap<K, V>
+}
+
+impl<'a, 'b, K, Q: ?Sized, V> VacantEntryRef<'a, 'b, K, Q, V> {
+	pub fn key(&self) -> &'b Q {
+		self.key
+	}
+
+	/// Insert the entry into the map, converting the borrowed key into an owned key.
+	pub fn insert(self, value: V) -> &'a mut V
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ToOwned<Owned = K>
+	{
+		&mut self.map.insert_vacant(self.key.to_owned(), value).value
+	}
+}
+
+impl<K, Q: Debug + ?Sized, V> Debug for VacantEntryRef<'_, '_, K, Q, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("VacantEntryRef")
+			.field("key", &self.key)
+			.finish()
+	}
+}
+
+/// The error returned by [`AATreeMap::try_insert`] if the key is already present. It
+/// contains the occupied entry and the value that was not inserted.
+pub struct OccupiedError<'a, K, V> {
+	entry: OccupiedEntry<'a, K, V>,
+	value: V
+}
+
+impl<'a, K, V> OccupiedError<'a, K, V> {
+	/// Returns a reference to the occupied entry.
+	pub fn entry(&self) -> &OccupiedEntry<'a, K, V> {
+		&self.entry
+	}
+
+	/// Returns a reference to the value that was not inserted.
+	pub fn value(&self) -> &V {
+		&self.value
+	}
+
+	/// Returns the occupied entry and the value that was not inserted.
+	pub fn into_parts(self) -> (OccupiedEntry<'a, K, V>, V) {
+		(self.entry, self.value)
+	}
+}
+
+impl<K: Debug, V: Debug> Debug for OccupiedError<'_, K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("OccupiedError")
+			.field("key", self.entry.key())
+			.field("old_value", self.entry.get())
+			.field("new_value", &self.value)
+			.finish()
+	}
+}
+
+impl<K: Debug, V: Debug> Display for OccupiedError<'_, K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"failed to insert {:?}, key {:?} already exists with value {:?}",
+			self.value,
+			self.entry.key(),
+			self.entry.get()
+		)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K: Debug, V: Debug> std::error::Error for OccupiedError<'_, K, V> {}
+
+impl<K, V> AATreeMap<K, V> {
+	/// Gets the first entry (that is, with the smallest key) in the map, which can be
+	/// inspected and then either kept, modified or removed. Unlike
+	/// [`first_entry`](Self::first_entry), the returned entry can be removed, which
+	/// returns its key and value like [`pop_first`](Self::pop_first).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::from([(1, "a"), (2, "b"), (3, "c")]);
+	/// while let Some(entry) = map.pop_first_entry() {
+	/// 	if *entry.key() > 1 {
+	/// 		break;
+	/// 	}
+	/// 	assert_eq!(entry.remove(), (1, "a"));
+	/// }
+	/// assert_eq!(map.len(), 2);
+	/// ```
+	pub fn pop_first_entry(&mut self) -> Option<PopEntry<'_, K, V>>
+	where
+		K: Ord
+	{
+		let (key, value) = self.pop_first()?;
+		Some(PopEntry {
+			inner: Some((self, KeyValue { key, value }))
+		})
+	}
+
+	/// Gets the last entry (that is, with the largest key) in the map, which can be
+	/// inspected and then either kept, modified or removed, like
+	/// [`pop_first_entry`](Self::pop_first_entry).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::from([(1, 10), (2, 20), (3, 5)]);
+	/// let mut entry = map.pop_last_entry().unwrap();
+	/// if *entry.get() < 10 {
+	/// 	*entry.get_mut() += 10;
+	/// }
+	/// assert_eq!(entry.remove(), (3, 15));
+	/// assert_eq!(map.last_key_value(), Some((&2, &20)));
+	/// ```
+	pub fn pop_last_entry(&mut self) -> Option<PopEntry<'_, K, V>>
+	where
+		K: Ord
+	{
+		let (key, value) = self.pop_last()?;
+		Some(PopEntry {
+			inner: Some((self, KeyValue { key, value }))
+		})
+	}
+
+	/// Insert an entry whose key is known to be absent from the map, and return a mutable
+	/// reference to it without searching for the key again.
+	fn insert_vacant(&mut self, key: K, value: V) -> &mut KeyValue<K, V>
+	where
+		K: Ord
+	{
+		let path = self.root.insert_new(KeyValue { key, value });
+		self.len += 1;
+		#[cfg(feature = "tracing")]
+		self.root.check_height(self.len);
+		self.root.content_at(path)
+	}
+
+	/// Insert a new entry into the map if the key is not present yet, and return a
+	/// mutable reference to the inserted value. If the key is already present, nothing
+	/// is changed and an [`OccupiedError`] containing the occupied entry and the value is
+	/// returned instead.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::new();
+	/// assert_eq!(map.try_insert(1, "a").unwrap(), &"a");
+	///
+	/// let err = map.try_insert(1, "b").unwrap_err();
+	/// assert_eq!(err.entry().get(), &"a");
+	/// assert_eq!(err.value(), &"b");
+	/// assert_eq!(map.get(&1), Some(&"a"));
+	/// ```
+	pub fn try_insert(
+		&mut self,
+		key: K,
+		value: V
+	) -> Result<&mut V, OccupiedError<'_, K, V>>
+	where
+		K: Ord
+	{
+		match self.entry(key) {
+			Entry::Occupied(entry) => Err(OccupiedError { entry, value }),
+			Entry::Vacant(entry) => Ok(entry.insert(value))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Entry;
+	use crate::AATreeMap;
+	use alloc::{string::String, vec::Vec};
+
+	#[test]
+	fn test_pop_entry() {
+		let mut map: AATreeMap<u32, String> =
+			(0 .. 100).map(|i| (i, alloc::format!("{i}"))).collect();
+		let mut buffers = Vec::new();
+		while let Some(entry) = map.pop_first_entry() {
+			if *entry.key() >= 10 {
+				break;
+			}
+			buffers.push(entry.remove().1);
+		}
+		assert_eq!(buffers.len(), 10);
+		map.assert_valid();
+
+		let mut entry = map.pop_last_entry().unwrap();
+		entry.get_mut().push('!');
+		assert_eq!(entry.insert(String::from("last")), "99!");
+		assert_eq!(entry.into_mut(), "last");
+		for i in (10 .. 100).rev() {
+			assert_eq!(map.pop_last_entry().unwrap().remove().0, i);
+			map.assert_valid();
+		}
+		assert!(map.pop_last_entry().is_none());
+		assert!(map.is_empty());
+	}
+
+	#[test]
+	fn test_pop_entry_dropped() {
+		let mut map: AATreeMap<u32, u32> = (0 .. 10).map(|i| (i, i)).collect();
+		{
+			let mut entry = map.pop_first_entry().unwrap();
+			assert_eq!((entry.key(), entry.get()), (&0, &0));
+			*entry.get_mut() = 100;
+		}
+		assert_eq!(map.len(), 10);
+		assert_eq!(map.first_key_value(), Some((&0, &100)));
+		drop(map.pop_last_entry());
+		assert_eq!(map.len(), 10);
+		assert_eq!(map.last_key_value(), Some((&9, &9)));
+		map.assert_valid();
+	}
+
+	#[test]
+	fn test_entry_without_clone() {
+		#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+		struct Handle(u32);
+
+		let mut map = AATreeMap::new();
+		let mut state = 7u32;
+		for i in 0 .. 1000 {
+			state = state.wrapping_mul(1103515245).wrapping_add(12345);
+			let key = state % 500;
+			let value = map.entry(Handle(key)).or_insert_with(Vec::new);
+			value.push(i);
+			assert_eq!(value.last(), Some(&i));
+			map.assert_valid();
+		}
+		assert_eq!(map.values().map(Vec::len).sum::<usize>(), 1000);
+		assert_eq!(*map.try_insert(Handle(1000), Vec::from([1])).unwrap(), [1]);
+		assert!(map.try_insert(Handle(1000), Vec::new()).is_err());
+		match map.entry(Handle(1001)) {
+			Entry::Vacant(entry) => {
+				let mut entry = entry.insert_entry(Vec::new());
+				entry.get_mut().push(1);
+				assert_eq!(entry.key(), &Handle(1001));
+			},
+			Entry::Occupied(_) => unreachable!()
+		}
+		assert_eq!(map[&Handle(1001)], [1]);
+		map.assert_valid();
+
+		let mut counts: AATreeMap<Handle, u32> = AATreeMap::new();
+		counts.increment(Handle(1));
+		assert_eq!(*counts.increment(Handle(1)), 2);
+	}
+}
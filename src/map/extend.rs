@@ -0,0 +1,175 @@
+//! This file defines fallible construction and extension of a map.
+
+use super::AATreeMap;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// The error returned by [`AATreeMap::from_iter_unique`] and
+/// [`AATreeSet::from_iter_unique`](crate::AATreeSet::from_iter_unique) if the iterator
+/// contains the same key more than once.
+#[derive(Debug)]
+pub struct DuplicateKeyError<K>(K);
+
+impl<K> DuplicateKeyError<K> {
+	pub(crate) fn new(key: K) -> Self {
+		Self(key)
+	}
+
+	/// Returns a reference to the duplicate key.
+	pub fn key(&self) -> &K {
+		&self.0
+	}
+
+	/// Returns the duplicate key.
+	pub fn into_key(self) -> K {
+		self.0
+	}
+}
+
+impl<K: Debug> Display for DuplicateKeyError<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "Duplicate key {:?}", self.0)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K: Debug> std::error::Error for DuplicateKeyError<K> {}
+
+/// The error returned by [`AATreeMap::try_extend`].
+#[derive(Debug)]
+pub struct TryExtendError<E> {
+	inserted: usize,
+	error: E
+}
+
+impl<E> TryExtendError<E> {
+	/// Returns the number of entries that were inserted before the error occurred.
+	pub fn inserted(&self) -> usize {
+		self.inserted
+	}
+
+	/// Returns a reference to the error returned by the iterator.
+	pub fn error(&self) -> &E {
+		&self.error
+	}
+
+	/// Returns the error returned by the iterator.
+	pub fn into_error(self) -> E {
+		self.error
+	}
+}
+
+impl<E: Display> Display for TryExtendError<E> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{} (after inserting {} entries)",
+			self.error, self.inserted
+		)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for TryExtendError<E> {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.error)
+	}
+}
+
+impl<K: Ord, V> AATreeMap<K, V> {
+	/// Creates a map from an iterator, like [`FromIterator::from_iter`], but returns an
+	/// error if the iterator contains the same key more than once instead of silently
+	/// keeping the last value.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from_iter_unique([(1, "a"), (2, "b")]).unwrap();
+	/// assert_eq!(map.len(), 2);
+	///
+	/// let err = AATreeMap::from_iter_unique([(1, "a"), (2, "b"), (1, "c")]).unwrap_err();
+	/// assert_eq!(*err.key(), 1);
+	/// assert_eq!(err.to_string(), "Duplicate key 1");
+	/// ```
+	pub fn from_iter_unique<I>(iter: I) -> Result<Self, DuplicateKeyError<K>>
+	where
+		I: IntoIterator<Item = (K, V)>
+	{
+		let mut map = Self::new();
+		for (key, value) in iter {
+			if map.contains_key(&key) {
+				return Err(DuplicateKeyError::new(key));
+			}
+			map.insert(key, value);
+		}
+		Ok(map)
+	}
+
+	/// Extends the map with the entries of a fallible iterator, like [`Extend::extend`].
+	/// Stops at the first error, which is returned together with the number of entries
+	/// that were inserted before. Entries that replaced an existing entry with the same
+	/// key are counted, too. Entries inserted before the error remain in the map.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::new();
+	/// let entries = ["1=a", "2=b", "3", "4=d"].into_iter().map(|entry| {
+	/// 	let (key, value) = entry.split_once('=').ok_or("missing separator")?;
+	/// 	Ok::<_, &str>((key.parse::<u32>().unwrap(), value))
+	/// });
+	/// let err = map.try_extend(entries).unwrap_err();
+	/// assert_eq!(err.inserted(), 2);
+	/// assert_eq!(*err.error(), "missing separator");
+	/// assert_eq!(map.len(), 2);
+	/// ```
+	pub fn try_extend<I, E>(&mut self, iter: I) -> Result<(), TryExtendError<E>>
+	where
+		I: IntoIterator<Item = Result<(K, V), E>>
+	{
+		let mut inserted = 0;
+		for entry in iter {
+			match entry {
+				Ok((key, value)) => {
+					self.insert(key, value);
+					inserted += 1;
+				},
+				Err(error) => return Err(TryExtendError { inserted, error })
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::string::ToString;
+
+	#[test]
+	fn test_try_extend() {
+		let mut map = AATreeMap::from([(1, 'a')]);
+		let ok: [Result<_, &str>; 2] = [Ok((1, 'b')), Ok((2, 'c'))];
+		assert!(map.try_extend(ok).is_ok());
+		assert_eq!(map, AATreeMap::from([(1, 'b'), (2, 'c')]));
+
+		let err = map
+			.try_extend([Ok((3, 'd')), Err("oops"), Ok((4, 'e'))])
+			.unwrap_err();
+		assert_eq!(err.inserted(), 1);
+		assert_eq!(err.to_string(), "oops (after inserting 1 entries)");
+		assert_eq!(err.into_error(), "oops");
+		assert_eq!(map.len(), 3);
+		assert!(!map.contains_key(&4));
+	}
+
+	#[test]
+	fn test_from_iter_unique() {
+		let map = AATreeMap::from_iter_unique((0 .. 100).map(|x| (x, x))).unwrap();
+		assert_eq!(map.len(), 100);
+		let err =
+			AATreeMap::from_iter_unique((0 .. 100).map(|x| (x % 42, x))).unwrap_err();
+		assert_eq!(err.into_key(), 0);
+	}
+}
@@ -0,0 +1,223 @@
+//! This file defines the lazy removal of entries from a map.
+
+use super::{AATreeMap, KeyValue};
+use crate::{
+	iter::AAIntoIter,
+	node::{AANode, IntoNodes, Node},
+	sorted::SortedIterator
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+	fmt::{self, Debug},
+	iter::FusedIterator,
+	marker::PhantomData,
+	mem
+};
+
+impl<K, V> AATreeMap<K, V> {
+	/// Creates an iterator that removes and yields all entries in ascending order of
+	/// their keys. The map is empty as soon as this method returns, and can be reused
+	/// after the iterator is dropped. Entries that have not been visited when the
+	/// iterator is dropped are dropped as well.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::from([(2, 'b'), (1, 'a')]);
+	/// assert_eq!(map.drain().collect::<Vec<_>>(), [(1, 'a'), (2, 'b')]);
+	/// assert!(map.is_empty());
+	///
+	/// map.insert(3, 'c');
+	/// assert_eq!(map.len(), 1);
+	/// ```
+	pub fn drain(&mut self) -> Drain<'_, K, V> {
+		Drain {
+			iter: mem::take(self).into_iter(),
+			_map: PhantomData
+		}
+	}
+
+	/// Creates an iterator that visits all entries in ascending order of their keys, and
+	/// removes and yields those for which `pred` returns `true`. `pred` may modify the
+	/// values of the entries that are kept.
+	///
+	/// The iterator takes the tree apart into its nodes while iterating, and links the
+	/// remaining nodes into a balanced tree again when it is dropped, without allocating
+	/// new nodes. Entries that have not been visited when the iterator is dropped are
+	/// kept. Creating and dropping the iterator therefore takes O(n) time, independently
+	/// of the number of removed entries. If the iterator is leaked, e.g. using
+	/// [`mem::forget`], the map is left empty.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map: AATreeMap<u32, u32> = (0 .. 8).map(|x| (x, x * 10)).collect();
+	/// let odd: Vec<_> = map.extract_if(|key, _| key % 2 == 1).collect();
+	/// assert_eq!(odd, [(1, 10), (3, 30), (5, 50), (7, 70)]);
+	/// assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 2, 4, 6]);
+	/// ```
+	pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F>
+	where
+		F: FnMut(&K, &mut V) -> bool
+	{
+		let remaining = mem::take(&mut self.len);
+		let nodes = IntoNodes::new(self.root.take());
+		ExtractIf {
+			map: self,
+			nodes,
+			remaining,
+			kept: Vec::new(),
+			pred
+		}
+	}
+}
+
+/// An iterator that removes and yields the entries of a map that match a predicate.
+/// Returned by [`AATreeMap::extract_if`].
+pub struct ExtractIf<'a, K, V, F> {
+	map: &'a mut AATreeMap<K, V>,
+	/// The nodes that have not been visited yet.
+	nodes: IntoNodes<KeyValue<K, V>>,
+	/// The number of nodes that have not been visited yet.
+	remaining: usize,
+	/// The nodes that have been visited and are kept, in ascending order.
+	kept: Vec<Box<Node<KeyValue<K, V>>>>,
+	pred: F
+}
+
+impl<K, V, F> Debug for ExtractIf<'_, K, V, F> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ExtractIf").finish_non_exhaustive()
+	}
+}
+
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+	F: FnMut(&K, &mut V) -> bool
+{
+	type Item = (K, V);
+
+	fn next(&mut self) -> Option<(K, V)> {
+		for mut node in &mut self.nodes {
+			self.remaining -= 1;
+			let (key, value) = node.content.as_tuple_mut();
+			if (self.pred)(key, value) {
+				return Some(node.content.into_tuple());
+			}
+			self.kept.push(node);
+		}
+		None
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(0, Some(self.remaining))
+	}
+}
+
+impl<K, V, F> FusedIterator for ExtractIf<'_, K, V, F> where F: FnMut(&K, &mut V) -> bool {}
+
+impl<K, V, F> SortedIterator for ExtractIf<'_, K, V, F> where F: FnMut(&K, &mut V) -> bool {}
+
+impl<K, V, F> Drop for ExtractIf<'_, K, V, F> {
+	fn drop(&mut self) {
+		self.kept.extend(&mut self.nodes);
+		let len = self.kept.len();
+		self.map.root = AANode::from_sorted_nodes(&mut self.kept.drain(..), len);
+		self.map.len = len;
+	}
+}
+
+/// An iterator that removes and yields all entries of a map in ascending order of their
+/// keys. Returned by [`AATreeMap::drain`].
+pub struct Drain<'a, K, V> {
+	iter: AAIntoIter<KeyValue<K, V>, (K, V)>,
+	_map: PhantomData<&'a mut AATreeMap<K, V>>
+}
+
+impl<K, V> Debug for Drain<'_, K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Drain")
+			.field("len", &self.iter.len())
+			.finish_non_exhaustive()
+	}
+}
+
+impl<K, V> Iterator for Drain<'_, K, V> {
+	type Item = (K, V);
+
+	fn next(&mut self) -> Option<(K, V)> {
+		self.iter.next()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.iter.size_hint()
+	}
+}
+
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {}
+
+impl<K, V> FusedIterator for Drain<'_, K, V> {}
+
+impl<K, V> SortedIterator for Drain<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_extract_if() {
+		for len in [0, 1, 2, 3, 10, 100, 1000] {
+			let mut map: AATreeMap<u32, u32> = (0 .. len).map(|x| (x, x)).collect();
+			let extracted: Vec<_> = map
+				.extract_if(|key, value| {
+					*value += 1;
+					key % 3 == 0
+				})
+				.collect();
+			map.assert_valid();
+			assert_eq!(
+				extracted,
+				(0 .. len)
+					.step_by(3)
+					.map(|x| (x, x + 1))
+					.collect::<Vec<_>>()
+			);
+			assert_eq!(map.len(), (0 .. len).filter(|x| x % 3 != 0).count());
+			assert!(map
+				.iter()
+				.all(|(key, value)| key % 3 != 0 && *value == key + 1));
+		}
+	}
+
+	#[test]
+	fn test_extract_if_partial() {
+		let mut map: AATreeMap<u32, u32> = (0 .. 100).map(|x| (x, x)).collect();
+		let mut iter = map.extract_if(|key, _| key % 2 == 0);
+		assert_eq!(iter.next(), Some((0, 0)));
+		assert_eq!(iter.next(), Some((2, 2)));
+		drop(iter);
+		map.assert_valid();
+		assert_eq!(map.len(), 98);
+		assert_eq!(map.first_key_value(), Some((&1, &1)));
+		assert!(map.contains_key(&4));
+	}
+
+	#[test]
+	fn test_drain() {
+		for len in [0, 1, 2, 3, 10, 100] {
+			let mut map: AATreeMap<u32, u32> = (0 .. len).map(|x| (x, x)).collect();
+			let mut drain = map.drain();
+			assert_eq!(drain.len(), len as usize);
+			let first: Vec<_> = drain.by_ref().take(2).map(|(k, _)| k).collect();
+			assert_eq!(first, (0 .. len.min(2)).collect::<Vec<_>>());
+			assert_eq!(drain.len(), len.saturating_sub(2) as usize);
+			drop(drain);
+			assert!(map.is_empty());
+			map.insert(1, 1);
+			map.assert_valid();
+		}
+	}
+}
@@ -1,8 +1,42 @@
 //! This method defines several access methods for [`AATreeMap`].
 
-use super::{AATreeMap, Entry, KeyValue, OccupiedEntry, VacantEntry};
-use crate::node::TraverseStep;
-use core::{borrow::Borrow, cmp::Ordering, fmt::Debug};
+use super::{
+	AATreeMap, Entry, EntryRef, KeyValue, OccupiedEntry, VacantEntry, VacantEntryRef
+};
+use crate::{
+	key::Successor,
+	node::TraverseStep,
+	search::{self, after_end, before_start, RankEstimate}
+};
+use core::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt::{self, Display, Formatter},
+	ops::{AddAssign, Bound}
+};
+
+/// The error returned by [`AATreeMap::get_within_depth`] if the lookup would have to
+/// visit more nodes than allowed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DepthExceeded {
+	max_depth: usize
+}
+
+impl DepthExceeded {
+	/// Returns the maximum number of nodes that the lookup was allowed to visit.
+	pub fn max_depth(&self) -> usize {
+		self.max_depth
+	}
+}
+
+impl Display for DepthExceeded {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "Lookup exceeded the maximum depth of {}", self.max_depth)
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DepthExceeded {}
 
 impl<K, V> AATreeMap<K, V> {
 	fn kv<Q>(&self, key: &Q) -> Option<&KeyValue<K, V>>
@@ -58,6 +92,49 @@ impl<K, V> AATreeMap<K, V> {
 		self.kv(key).map(|kv| kv.as_tuple())
 	}
 
+	/// Returns a reference to the value corresponding to the key, but visits at most
+	/// `max_depth` nodes. If the key could not be found or ruled out within that many
+	/// steps, [`DepthExceeded`] is returned instead.
+	///
+	/// This bounds the worst-case latency of a single lookup independently of the
+	/// size of the map, e.g. for loops that need to meet a deadline and would rather
+	/// defer a lookup. A lookup never visits more than [`height`](Self::height) nodes,
+	/// so a `max_depth` of at least the height always succeeds.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, u32> = (0 .. 100).map(|k| (k, k * 2)).collect();
+	/// assert_eq!(map.get_within_depth(&21, map.height()), Ok(Some(&42)));
+	/// assert_eq!(map.get_within_depth(&100, map.height()), Ok(None));
+	/// assert!(map.get_within_depth(&21, 1).is_err());
+	/// ```
+	pub fn get_within_depth<Q>(
+		&self,
+		key: &Q,
+		max_depth: usize
+	) -> Result<Option<&V>, DepthExceeded>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let mut node = self.root.as_ref();
+		let mut depth = 0;
+		while let Some(n) = node {
+			if depth == max_depth {
+				return Err(DepthExceeded { max_depth });
+			}
+			depth += 1;
+			node = match key.cmp(n.content.key.borrow()) {
+				Ordering::Equal => return Ok(Some(&n.content.value)),
+				Ordering::Less => n.left_child.as_ref(),
+				Ordering::Greater => n.right_child.as_ref()
+			};
+		}
+		Ok(None)
+	}
+
 	fn kv_mut<Q>(&mut self, key: &Q) -> Option<&mut KeyValue<K, V>>
 	where
 		K: Ord + Borrow<Q>,
@@ -105,6 +182,78 @@ impl<K, V> AATreeMap<K, V> {
 		}
 	}
 
+	/// Gets the entry for a borrowed key, like [`entry`](Self::entry). The key is only
+	/// converted into an owned key if the entry is vacant and a value is inserted, which
+	/// avoids allocating e.g. a `String` when the key is usually present already.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut counts: AATreeMap<String, usize> = AATreeMap::new();
+	/// for word in ["a", "b", "a"] {
+	/// 	*counts.entry_ref(word).or_insert(0) += 1;
+	/// }
+	/// assert_eq!(counts.get("a"), Some(&2));
+	/// assert_eq!(counts.get("b"), Some(&1));
+	/// ```
+	#[allow(unsafe_code)]
+	pub fn entry_ref<'a, 'b, Q>(&'a mut self, key: &'b Q) -> EntryRef<'a, 'b, K, Q, V>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		// see entry for why this is necessary
+		match unsafe { &mut *(self as *mut Self) }.kv_mut(key) {
+			Some(kv) => EntryRef::Occupied(OccupiedEntry { entry: kv }),
+			None => EntryRef::Vacant(VacantEntryRef { key, map: self })
+		}
+	}
+
+	/// Update the value corresponding to the key using `f`, or insert `default` if the
+	/// key is not present yet. Returns a mutable reference to the value.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::new();
+	/// for word in ["a", "b", "a"] {
+	/// 	map.update_or_insert(word, 1, |count| *count += 1);
+	/// }
+	/// assert_eq!(map.get("a"), Some(&2));
+	/// assert_eq!(map.get("b"), Some(&1));
+	/// ```
+	pub fn update_or_insert<F>(&mut self, key: K, default: V, f: F) -> &mut V
+	where
+		K: Ord,
+		F: FnOnce(&mut V)
+	{
+		self.entry(key).and_modify(f).or_insert(default)
+	}
+
+	/// Increment the counter corresponding to the key by one, starting at zero if the key
+	/// is not present yet. Returns a mutable reference to the counter.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::<char, u32>::new();
+	/// for c in "hello".chars() {
+	/// 	map.increment(c);
+	/// }
+	/// assert_eq!(map.get(&'l'), Some(&2));
+	/// assert_eq!(*map.increment('h'), 2);
+	/// ```
+	pub fn increment(&mut self, key: K) -> &mut V
+	where
+		K: Ord,
+		V: AddAssign + From<u8>
+	{
+		self.update_or_insert(key, V::from(1), |count| *count += V::from(1))
+	}
+
 	/// Returns a mutable reference to the value corresponding to the key.
 	///
 	/// # Example
@@ -125,6 +274,29 @@ impl<K, V> AATreeMap<K, V> {
 		self.kv_mut(key).map(|kv| &mut kv.value)
 	}
 
+	/// Returns a reference to the key and a mutable reference to the value corresponding
+	/// to the key. The key cannot be modified, since that could change its order with
+	/// respect to the other keys.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::new();
+	/// map.insert(String::from("Apple"), 1);
+	/// let (key, value) = map.get_key_value_mut("Apple").unwrap();
+	/// *value += 1;
+	/// assert_eq!(key, "Apple");
+	/// assert_eq!(map.get("Apple"), Some(&2));
+	/// ```
+	pub fn get_key_value_mut<Q>(&mut self, key: &Q) -> Option<(&K, &mut V)>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.kv_mut(key).map(KeyValue::as_tuple_mut)
+	}
+
 	/// Gets the first entry (that is, with the smallest key) in the map, allowing for
 	/// in-place manipulation of the entry.
 	///
@@ -140,15 +312,16 @@ impl<K, V> AATreeMap<K, V> {
 	/// map.insert(3, 'c');
 	/// println!("{map:?}");
 	///
-	/// let Some(mut entry) = map.first_entry() else { unreachable!() };
+	/// let Some(mut entry) = map.first_entry() else {
+	/// 	unreachable!()
+	/// };
 	/// *entry.get_mut() = 'b';
 	/// assert_eq!(map.get(&1), Some(&'b'));
 	/// assert_eq!(map.get(&3), Some(&'c'));
 	/// ```
 	pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>>
 	where
-		K: Ord + Debug,
-		V: Debug
+		K: Ord
 	{
 		let mut traverse = self.root.traverse_mut()?;
 		while traverse.has_left_child() {
@@ -202,7 +375,9 @@ impl<K, V> AATreeMap<K, V> {
 	where
 		K: Ord
 	{
-		self.root.remove_successor().map(KeyValue::into_tuple)
+		let entry = self.root.remove_successor()?;
+		self.len -= 1;
+		Some(entry.into_tuple())
 	}
 
 	/// Gets the last entry (that is, with the largest key) in the map, allowing for
@@ -219,7 +394,9 @@ impl<K, V> AATreeMap<K, V> {
 	/// map.insert(1, 'a');
 	/// map.insert(3, 'c');
 	///
-	/// let Some(mut entry) = map.last_entry() else { unreachable!() };
+	/// let Some(mut entry) = map.last_entry() else {
+	/// 	unreachable!()
+	/// };
 	/// *entry.get_mut() = 'b';
 	/// assert_eq!(map.get(&1), Some(&'a'));
 	/// assert_eq!(map.get(&3), Some(&'b'));
@@ -280,7 +457,9 @@ impl<K, V> AATreeMap<K, V> {
 	where
 		K: Ord
 	{
-		self.root.remove_predecessor().map(KeyValue::into_tuple)
+		let entry = self.root.remove_predecessor()?;
+		self.len -= 1;
+		Some(entry.into_tuple())
 	}
 
 	pub fn pop_largest(&mut self) -> Option<(K, V)>
@@ -309,17 +488,7 @@ impl<K, V> AATreeMap<K, V> {
 		K: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		self.root.traverse(
-			|content| match content.key.borrow().cmp(k) {
-				Ordering::Greater => TraverseStep::Left,
-				Ordering::Less => TraverseStep::Right,
-				Ordering::Equal => TraverseStep::Value(Some(content.as_tuple()))
-			},
-			|content, sub| match sub {
-				None if content.key.borrow() > k => Some(content.as_tuple()),
-				sub => sub
-			}
-		)
+		self.lower_bound(Bound::Included(k))
 	}
 
 	/// Returns a mutable reference to the first entry with a key greater than or equal
@@ -344,23 +513,7 @@ impl<K, V> AATreeMap<K, V> {
 		K: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		let mut traverse = self.root.traverse_mut()?;
-		loop {
-			match traverse.peek().key.borrow().cmp(k) {
-				Ordering::Greater
-					if traverse
-						.peek_left_child()
-						.map(|left| left.key.borrow() >= k)
-						.unwrap_or(false) =>
-				{
-					traverse = traverse.turn_left().unwrap()
-				},
-
-				Ordering::Less => traverse = traverse.turn_right().ok()?,
-
-				_ => return Some(traverse.into_content().as_tuple_mut())
-			}
-		}
+		self.lower_bound_mut(Bound::Included(k))
 	}
 
 	/// Returns a reference to the last entry with a key smaller than or equal to `k` in
@@ -382,17 +535,7 @@ impl<K, V> AATreeMap<K, V> {
 		K: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		self.root.traverse(
-			|content| match content.key.borrow().cmp(k) {
-				Ordering::Greater => TraverseStep::Left,
-				Ordering::Less => TraverseStep::Right,
-				Ordering::Equal => TraverseStep::Value(Some(content.as_tuple()))
-			},
-			|content, sub| match sub {
-				None if content.key.borrow() < k => Some(content.as_tuple()),
-				sub => sub
-			}
-		)
+		self.upper_bound(Bound::Included(k))
 	}
 
 	/// Returns a mutable reference to the last entry with a key smaller than or equal to
@@ -414,27 +557,149 @@ impl<K, V> AATreeMap<K, V> {
 	/// ```
 	pub fn last_key_value_mut_at_or_before<Q>(&mut self, k: &Q) -> Option<(&K, &mut V)>
 	where
-		K: Borrow<Q> + Ord + Debug,
-		V: Debug,
+		K: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		let mut traverse = self.root.traverse_mut()?;
-		loop {
-			match traverse.peek().key.borrow().cmp(k) {
-				Ordering::Greater => traverse = traverse.turn_left().ok()?,
+		self.upper_bound_mut(Bound::Included(k))
+	}
 
-				Ordering::Less
-					if traverse
-						.peek_right_child()
-						.map(|right| right.key.borrow() <= k)
-						.unwrap_or(false) =>
-				{
-					traverse = traverse.turn_right().unwrap()
-				},
+	/// Returns a reference to the first entry whose key is within a range starting at
+	/// `bound`. This is the first entry with a key greater than (or equal to, if the bound
+	/// is inclusive) the bound, or the first entry of the map if it is unbounded.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// use std::ops::Bound;
+	///
+	/// let map = AATreeMap::from([(10, "a"), (20, "b"), (30, "c")]);
+	/// assert_eq!(map.lower_bound(Bound::Included(&20)), Some((&20, &"b")));
+	/// assert_eq!(map.lower_bound(Bound::Excluded(&20)), Some((&30, &"c")));
+	/// assert_eq!(map.lower_bound::<i32>(Bound::Unbounded), Some((&10, &"a")));
+	/// ```
+	pub fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		search::lower_bound(&self.root, |kv| before_start(&kv.key, bound))
+			.map(KeyValue::as_tuple)
+	}
 
-				_ => return Some(traverse.into_content().as_tuple_mut())
-			}
-		}
+	/// Returns a mutable reference to the first entry whose key is within a range
+	/// starting at `bound`. See [`lower_bound`](Self::lower_bound) for details.
+	pub fn lower_bound_mut<Q>(&mut self, bound: Bound<&Q>) -> Option<(&K, &mut V)>
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		search::lower_bound_mut(&mut self.root, |kv| before_start(&kv.key, bound))
+			.map(KeyValue::as_tuple_mut)
+	}
+
+	/// Returns a reference to the last entry whose key is within a range ending at
+	/// `bound`. This is the last entry with a key less than (or equal to, if the bound is
+	/// inclusive) the bound, or the last entry of the map if it is unbounded.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// use std::ops::Bound;
+	///
+	/// let map = AATreeMap::from([(10, "a"), (20, "b"), (30, "c")]);
+	/// assert_eq!(map.upper_bound(Bound::Included(&20)), Some((&20, &"b")));
+	/// assert_eq!(map.upper_bound(Bound::Excluded(&20)), Some((&10, &"a")));
+	/// assert_eq!(map.upper_bound::<i32>(Bound::Unbounded), Some((&30, &"c")));
+	/// ```
+	pub fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		search::upper_bound(&self.root, |kv| after_end(&kv.key, bound))
+			.map(KeyValue::as_tuple)
+	}
+
+	/// Returns a mutable reference to the last entry whose key is within a range ending
+	/// at `bound`. See [`upper_bound`](Self::upper_bound) for details.
+	pub fn upper_bound_mut<Q>(&mut self, bound: Bound<&Q>) -> Option<(&K, &mut V)>
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		search::upper_bound_mut(&mut self.root, |kv| after_end(&kv.key, bound))
+			.map(KeyValue::as_tuple_mut)
+	}
+
+	/// Returns the smallest key at or after `start` that is not part of the map, or `None`
+	/// if there is no such key because all keys from `start` up to the maximum value of
+	/// the key type are taken. This is useful to allocate ids.
+	///
+	/// The tree is walked in order starting at `start` until a gap is found, which takes
+	/// O(log n + r) time, where r is the number of consecutive keys starting at `start`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(1u8, "a"), (2, "b"), (3, "c"), (5, "e"), (255, "z")]);
+	/// assert_eq!(map.next_free_key(&0), Some(0));
+	/// assert_eq!(map.next_free_key(&1), Some(4));
+	/// assert_eq!(map.next_free_key(&5), Some(6));
+	/// assert_eq!(map.next_free_key(&255), None);
+	/// ```
+	pub fn next_free_key(&self, start: &K) -> Option<K>
+	where
+		K: Successor + Clone
+	{
+		search::next_free(&self.root, start, |kv| &kv.key)
+	}
+
+	/// Estimates the number of keys in the map that are less than `k` in O(log n) time.
+	///
+	/// The map does not keep track of the sizes of its subtrees, so the rank is
+	/// approximated from the levels of the nodes along the search path. The returned
+	/// [`RankEstimate`] contains bounds that are guaranteed to contain the exact rank,
+	/// and an estimate in between. See [`search::estimate_rank`] for details on the
+	/// error.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, ()> = (0 .. 1000).map(|k| (k, ())).collect();
+	/// let rank = map.estimate_rank(&250);
+	/// assert!(rank.min() <= 250 && 250 <= rank.max());
+	/// ```
+	pub fn estimate_rank<Q>(&self, k: &Q) -> RankEstimate
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		search::estimate_rank(&self.root, self.len, |kv| kv.key.borrow() < k)
+	}
+
+	/// Returns `true` if `k` is estimated to be in the first half of the map, i.e. if
+	/// fewer than half of the keys are estimated to be less than `k`. This is based on
+	/// [`estimate_rank`](Self::estimate_rank) and therefore only approximate for keys
+	/// close to the median.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, ()> = (0 .. 1000).map(|k| (k, ())).collect();
+	/// assert!(map.is_in_first_half(&10));
+	/// assert!(!map.is_in_first_half(&990));
+	/// ```
+	pub fn is_in_first_half<Q>(&self, k: &Q) -> bool
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		self.estimate_rank(k).estimate() < self.len - self.len / 2
 	}
 }
 
@@ -467,6 +732,12 @@ mod tests {
 		let (key, value) = map.first_key_value_mut_at_or_after(&15).unwrap();
 		assert_eq!(*key, 20);
 		assert_eq!(*value, "b");
+
+		// For 25, we need to go left and then right
+		assert_eq!(map.first_key_value_at_or_after(&25), Some((&30, &"c")));
+		let (key, value) = map.first_key_value_mut_at_or_after(&25).unwrap();
+		assert_eq!(*key, 30);
+		assert_eq!(*value, "c");
 	}
 
 	#[test]
@@ -490,4 +761,51 @@ mod tests {
 		assert_eq!(*key, 30);
 		assert_eq!(*value, "c");
 	}
+
+	#[test]
+	fn test_next_free_key() {
+		let keys = [0u32, 1, 2, 4, 5, 6, 7, 9, 10, 20];
+		let map: AATreeMap<u32, ()> = keys.iter().map(|k| (*k, ())).collect();
+		for start in 0 .. 25 {
+			let expected = (start ..).find(|k| !keys.contains(k));
+			assert_eq!(map.next_free_key(&start), expected, "start={start}");
+		}
+	}
+
+	#[test]
+	fn test_bounds_mut() {
+		let mut map: AATreeMap<u32, u32> = (0 .. 20).map(|x| (x * 2, x)).collect();
+		for key in 0 .. 42 {
+			let lower = (key + 1) / 2;
+			let upper = key / 2;
+			assert_eq!(
+				map.first_key_value_mut_at_or_after(&key).map(|(_, v)| *v),
+				(lower < 20).then(|| lower)
+			);
+			assert_eq!(
+				map.last_key_value_mut_at_or_before(&key).map(|(_, v)| *v),
+				Some(upper.min(19))
+			);
+		}
+	}
+
+	#[test]
+	fn test_get_within_depth() {
+		let map: AATreeMap<u32, u32> = (0 .. 1000).map(|k| (k, k)).collect();
+		for key in 0 .. 1001 {
+			let expected = map.get(&key);
+			assert_eq!(map.get_within_depth(&key, map.height()), Ok(expected));
+			let mut depth = 0;
+			while map.get_within_depth(&key, depth).is_err() {
+				depth += 1;
+			}
+			assert!(depth <= map.height());
+			assert_eq!(map.get_within_depth(&key, depth), Ok(expected));
+		}
+
+		let empty: AATreeMap<u32, u32> = AATreeMap::new();
+		assert_eq!(empty.get_within_depth(&1, 0), Ok(None));
+		let err = map.get_within_depth(&1, 0).unwrap_err();
+		assert_eq!(err.max_depth(), 0);
+	}
 }
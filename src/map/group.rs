@@ -0,0 +1,115 @@
+//! This file defines aggregation methods for [`AATreeMap`] that group entries into
+//! buckets derived from their keys.
+
+use super::AATreeMap;
+
+/// Fold all `items` into buckets. Since the items are visited in order, consecutive items
+/// usually fall into the same bucket, so the accumulator only needs to be looked up in
+/// the result when the bucket changes.
+pub(crate) fn group_fold<T, I, B, A, F, G>(
+	items: I,
+	mut bucket: F,
+	init: A,
+	mut fold: G
+) -> AATreeMap<B, A>
+where
+	I: Iterator<Item = T>,
+	B: Ord,
+	A: Clone,
+	F: FnMut(&T) -> B,
+	G: FnMut(A, T) -> A
+{
+	let mut groups = AATreeMap::new();
+	let mut current: Option<(B, A)> = None;
+	for item in items {
+		let b = bucket(&item);
+		let acc = match current.take() {
+			Some((current_b, acc)) if current_b == b => acc,
+			prev => {
+				if let Some((prev_b, prev_acc)) = prev {
+					groups.insert(prev_b, prev_acc);
+				}
+				groups.remove(&b).unwrap_or_else(|| init.clone())
+			}
+		};
+		current = Some((b, fold(acc, item)));
+	}
+	if let Some((b, acc)) = current {
+		groups.insert(b, acc);
+	}
+	groups
+}
+
+impl<K, V> AATreeMap<K, V> {
+	/// Count the entries of this map per bucket, where the bucket of each entry is derived
+	/// from its key. The resulting map yields the buckets in ascending order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// // events keyed by their timestamp in seconds
+	/// let events =
+	/// 	AATreeMap::from([(5, "a"), (42, "b"), (61, "c"), (130, "d"), (170, "e")]);
+	/// let per_minute = events.group_count_by(|ts| ts / 60);
+	/// assert_eq!(per_minute, AATreeMap::from([(0, 2), (1, 1), (2, 2)]));
+	/// ```
+	pub fn group_count_by<B, F>(&self, bucket: F) -> AATreeMap<B, usize>
+	where
+		B: Ord,
+		F: FnMut(&K) -> B
+	{
+		self.group_fold_by(bucket, 0, |count, _, _| count + 1)
+	}
+
+	/// Fold the entries of this map per bucket, where the bucket of each entry is derived
+	/// from its key. Every bucket starts with a clone of `init`, and `fold` is called for
+	/// each entry in ascending key order. The resulting map yields the buckets in
+	/// ascending order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let sales = AATreeMap::from([(3, 10), (7, 5), (12, 1), (18, 4)]);
+	/// let per_decade = sales.group_fold_by(|k| k / 10, 0, |sum, _, v| sum + v);
+	/// assert_eq!(per_decade, AATreeMap::from([(0, 15), (1, 5)]));
+	/// ```
+	pub fn group_fold_by<B, A, F, G>(
+		&self,
+		mut bucket: F,
+		init: A,
+		mut fold: G
+	) -> AATreeMap<B, A>
+	where
+		B: Ord,
+		A: Clone,
+		F: FnMut(&K) -> B,
+		G: FnMut(A, &K, &V) -> A
+	{
+		group_fold(
+			self.iter(),
+			|(k, _)| bucket(k),
+			init,
+			|acc, (k, v)| fold(acc, k, v)
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+
+	#[test]
+	fn test_group_non_monotonic() {
+		let map: AATreeMap<u32, u32> = (0 .. 20).map(|i| (i, i)).collect();
+		let groups = map.group_fold_by(|k| k % 3, 0, |sum, _, v| sum + v);
+		assert_eq!(groups, AATreeMap::from([(0, 63), (1, 70), (2, 57)]));
+	}
+
+	#[test]
+	fn test_group_empty() {
+		let map = AATreeMap::<u32, u32>::new();
+		assert!(map.group_count_by(|k| *k).is_empty());
+	}
+}
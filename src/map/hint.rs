@@ -0,0 +1,176 @@
+//! This file defines lookups that are optimized for an expected outcome.
+
+use super::AATreeMap;
+use core::{borrow::Borrow, cmp::Ordering};
+
+/// The expected outcome of a lookup, passed to [`AATreeMap::get_with_hint`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LookupHint {
+	/// The key is expected to be part of the map most of the time.
+	Hit,
+	/// The key is expected to be missing from the map most of the time.
+	Miss
+}
+
+/// Statistics about the cost of a single lookup, returned by
+/// [`AATreeMap::get_with_stats`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct QueryStats {
+	comparisons: usize,
+	nodes_visited: usize
+}
+
+impl QueryStats {
+	/// Returns the number of times two keys were compared.
+	pub fn comparisons(&self) -> usize {
+		self.comparisons
+	}
+
+	/// Returns the number of nodes that were visited.
+	pub fn nodes_visited(&self) -> usize {
+		self.nodes_visited
+	}
+
+	/// Record visiting a node and comparing its key.
+	fn visit(&mut self) {
+		self.comparisons += 1;
+		self.nodes_visited += 1;
+	}
+}
+
+impl<K, V> AATreeMap<K, V> {
+	/// Returns a reference to the value corresponding to the key, like
+	/// [`get`](Self::get), but optimized for the expected outcome of the lookup.
+	///
+	/// With [`LookupHint::Hit`], the search performs a three-way comparison at every
+	/// node and stops as soon as the key is found. With [`LookupHint::Miss`], the search
+	/// only checks whether the key is less than the key of each node, always descends
+	/// to a leaf, and defers the equality check to the very end. This halves the number
+	/// of comparisons and unpredictable branches per node, which pays off when most
+	/// lookups fail and would descend to a leaf anyways.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, map::LookupHint};
+	/// let map = AATreeMap::from([(1, "a"), (3, "c")]);
+	/// assert_eq!(map.get_with_hint(&1, LookupHint::Miss), Some(&"a"));
+	/// assert_eq!(map.get_with_hint(&2, LookupHint::Miss), None);
+	/// assert_eq!(map.get_with_hint(&3, LookupHint::Hit), Some(&"c"));
+	/// ```
+	pub fn get_with_hint<Q>(&self, key: &Q, hint: LookupHint) -> Option<&V>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.lookup(key, hint, &mut QueryStats::default())
+	}
+
+	/// Returns a reference to the value corresponding to the key like
+	/// [`get_with_hint`](Self::get_with_hint), together with statistics about the cost
+	/// of this single lookup.
+	///
+	/// This allows query planners to account for the cost of every query, e.g. to
+	/// decide between this map and another index at runtime.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, map::LookupHint};
+	/// let map: AATreeMap<u32, u32> = (0 .. 1000).map(|k| (k * 2, k)).collect();
+	/// let (value, stats) = map.get_with_stats(&501, LookupHint::Miss);
+	/// assert_eq!(value, None);
+	/// assert!(stats.nodes_visited() <= map.height());
+	/// assert_eq!(stats.comparisons(), stats.nodes_visited() + 1);
+	/// ```
+	pub fn get_with_stats<Q>(&self, key: &Q, hint: LookupHint) -> (Option<&V>, QueryStats)
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let mut stats = QueryStats::default();
+		let value = self.lookup(key, hint, &mut stats);
+		(value, stats)
+	}
+
+	#[inline]
+	fn lookup<Q>(&self, key: &Q, hint: LookupHint, stats: &mut QueryStats) -> Option<&V>
+	where
+		K: Ord + Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let mut node = self.root.as_ref();
+		match hint {
+			LookupHint::Hit => {
+				while let Some(n) = node {
+					stats.visit();
+					node = match key.cmp(n.content.key.borrow()) {
+						Ordering::Equal => return Some(&n.content.value),
+						Ordering::Less => n.left_child.as_ref(),
+						Ordering::Greater => n.right_child.as_ref()
+					};
+				}
+				None
+			},
+			LookupHint::Miss => {
+				// the node with the largest key less than or equal to the searched key
+				let mut candidate = None;
+				while let Some(n) = node {
+					stats.visit();
+					node = if key < n.content.key.borrow() {
+						n.left_child.as_ref()
+					} else {
+						candidate = Some(&n.content);
+						n.right_child.as_ref()
+					};
+				}
+				candidate
+					.filter(|kv| {
+						stats.comparisons += 1;
+						kv.key.borrow() == key
+					})
+					.map(|kv| &kv.value)
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{LookupHint, QueryStats};
+	use crate::AATreeMap;
+
+	#[test]
+	fn test_get_with_hint() {
+		let map: AATreeMap<u32, u32> = (0 .. 500).map(|i| (i * 2, i)).collect();
+		for key in 0 .. 1002 {
+			let expected = map.get(&key);
+			assert_eq!(map.get_with_hint(&key, LookupHint::Hit), expected);
+			assert_eq!(map.get_with_hint(&key, LookupHint::Miss), expected);
+		}
+		let empty = AATreeMap::<u32, u32>::new();
+		assert_eq!(empty.get_with_hint(&0, LookupHint::Miss), None);
+	}
+
+	#[test]
+	fn test_get_with_stats() {
+		let map: AATreeMap<u32, u32> = (0 .. 500).map(|i| (i * 2, i)).collect();
+		for key in 0 .. 1002 {
+			let expected = map.get(&key);
+			let (value, hit) = map.get_with_stats(&key, LookupHint::Hit);
+			assert_eq!(value, expected);
+			assert_eq!(hit.comparisons(), hit.nodes_visited());
+			assert!(hit.nodes_visited() <= map.height());
+
+			let (value, miss) = map.get_with_stats(&key, LookupHint::Miss);
+			assert_eq!(value, expected);
+			assert!(miss.nodes_visited() <= map.height());
+			assert!(miss.nodes_visited() >= hit.nodes_visited());
+			assert!(miss.comparisons() <= miss.nodes_visited() + 1);
+		}
+
+		let empty = AATreeMap::<u32, u32>::new();
+		let (_, stats) = empty.get_with_stats(&0, LookupHint::Miss);
+		assert_eq!(stats, QueryStats::default());
+	}
+}
@@ -0,0 +1,100 @@
+//! This file defines conversions that swap the keys and values of a map.
+
+use super::AATreeMap;
+use crate::AATreeSet;
+use alloc::vec::Vec;
+
+impl<K, V: Ord> AATreeMap<K, V> {
+	/// Collect all entries as value-key pairs, sorted by value. Entries with equal values
+	/// keep the ascending order of their keys since the sort is stable.
+	fn into_sorted_by_value(self) -> Vec<(V, K)> {
+		let mut pairs: Vec<_> =
+			self.into_iter().map(|(key, value)| (value, key)).collect();
+		pairs.sort_by(|a, b| a.0.cmp(&b.0));
+		pairs
+	}
+
+	/// Swap the keys and values of this map. If multiple keys map to the same value, the
+	/// greatest of these keys is kept, just like inserting all value-key pairs in
+	/// ascending order of the keys would. Use [`invert_grouped`](Self::invert_grouped)
+	/// to keep all keys.
+	///
+	/// The pairs are sorted once and the tree is built in O(n) afterwards, which is
+	/// considerably faster than inserting them one by one.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(1, 'b'), (2, 'a'), (3, 'b')]);
+	/// assert_eq!(map.invert(), AATreeMap::from([('a', 2), ('b', 3)]));
+	/// ```
+	pub fn invert(self) -> AATreeMap<V, K> {
+		let mut inverted: Vec<(V, K)> = Vec::new();
+		for (value, key) in self.into_sorted_by_value() {
+			match inverted.last_mut() {
+				Some(last) if last.0 == value => last.1 = key,
+				_ => inverted.push((value, key))
+			}
+		}
+		let len = inverted.len();
+		AATreeMap::from_sorted_unchecked(inverted, len)
+	}
+
+	/// Swap the keys and values of this map, collecting all keys that map to the same
+	/// value into a set. This builds an inverted index of the map.
+	///
+	/// The pairs are sorted once and all trees are built in O(n) afterwards, which is
+	/// considerably faster than inserting them one by one.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, AATreeSet};
+	/// let map = AATreeMap::from([(1, 'b'), (2, 'a'), (3, 'b')]);
+	/// assert_eq!(
+	/// 	map.invert_grouped(),
+	/// 	AATreeMap::from([('a', AATreeSet::from([2])), ('b', AATreeSet::from([1, 3]))])
+	/// );
+	/// ```
+	pub fn invert_grouped(self) -> AATreeMap<V, AATreeSet<K>> {
+		let mut groups: Vec<(V, Vec<K>)> = Vec::new();
+		for (value, key) in self.into_sorted_by_value() {
+			match groups.last_mut() {
+				Some(last) if last.0 == value => last.1.push(key),
+				_ => groups.push((value, Vec::from([key])))
+			}
+		}
+		let len = groups.len();
+		let groups = groups.into_iter().map(|(value, keys)| {
+			let len = keys.len();
+			(value, AATreeSet::from_sorted_unchecked(keys, len))
+		});
+		AATreeMap::from_sorted_unchecked(groups, len)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{AATreeMap, AATreeSet};
+
+	#[test]
+	fn test_invert_matches_naive() {
+		let map: AATreeMap<u32, u32> = (0 .. 1000).map(|k| (k, k * 37 % 101)).collect();
+
+		let mut expected = AATreeMap::new();
+		let mut expected_grouped: AATreeMap<u32, AATreeSet<u32>> = AATreeMap::new();
+		for (&key, &value) in &map {
+			expected.insert(value, key);
+			expected_grouped.entry(value).or_default().insert(key);
+		}
+
+		let inverted = map.clone().invert();
+		inverted.assert_valid();
+		assert_eq!(inverted, expected);
+		let grouped = map.invert_grouped();
+		grouped.assert_valid();
+		assert_eq!(grouped, expected_grouped);
+		assert_eq!(grouped.flat_len(), 1000);
+	}
+}
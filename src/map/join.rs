@@ -0,0 +1,271 @@
+//! This file defines ordered joins of two maps.
+
+use super::{AATreeMap, KeyValue};
+use crate::{iter::AAIter, sorted::SortedIterator};
+use core::{
+	cmp::Ordering,
+	fmt::{self, Debug},
+	iter::{FusedIterator, Peekable}
+};
+
+type Iter<'a, K, V> = Peekable<AAIter<'a, KeyValue<K, V>, (&'a K, &'a V)>>;
+
+impl<K, V> AATreeMap<K, V> {
+	/// Creates an iterator over all keys that are part of both `self` and `other`, in
+	/// ascending order, together with the values from both maps.
+	///
+	/// Both maps are walked simultaneously in a single pass in O(n + m), which is faster
+	/// than looking up every key of one map in the other unless one map is much smaller.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let names = AATreeMap::from([(1, "alice"), (2, "bob"), (3, "carol")]);
+	/// let ages = AATreeMap::from([(2, 42), (3, 37), (4, 19)]);
+	/// assert_eq!(names.join_inner(&ages).collect::<Vec<_>>(), [
+	/// 	(&2, &"bob", &42),
+	/// 	(&3, &"carol", &37)
+	/// ]);
+	/// ```
+	pub fn join_inner<'a, V2>(
+		&'a self,
+		other: &'a AATreeMap<K, V2>
+	) -> JoinInner<'a, K, V, V2> {
+		JoinInner {
+			a: self.iter().peekable(),
+			b: other.iter().peekable()
+		}
+	}
+
+	/// Creates an iterator over all entries of `self` in ascending order, together with
+	/// the value of the same key in `other` if there is one. Both maps are walked in a
+	/// single pass in O(n + m).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let names = AATreeMap::from([(1, "alice"), (2, "bob")]);
+	/// let ages = AATreeMap::from([(2, 42), (3, 37)]);
+	/// assert_eq!(names.join_left(&ages).collect::<Vec<_>>(), [
+	/// 	(&1, &"alice", None),
+	/// 	(&2, &"bob", Some(&42))
+	/// ]);
+	/// ```
+	pub fn join_left<'a, V2>(
+		&'a self,
+		other: &'a AATreeMap<K, V2>
+	) -> JoinLeft<'a, K, V, V2> {
+		JoinLeft {
+			a: self.iter(),
+			b: other.iter().peekable()
+		}
+	}
+
+	/// Creates an iterator over all keys that are part of `self` or `other`, in ascending
+	/// order, together with the values from both maps where present. Both maps are
+	/// walked in a single pass in O(n + m).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let names = AATreeMap::from([(1, "alice"), (2, "bob")]);
+	/// let ages = AATreeMap::from([(2, 42), (3, 37)]);
+	/// assert_eq!(names.join_outer(&ages).collect::<Vec<_>>(), [
+	/// 	(&1, Some(&"alice"), None),
+	/// 	(&2, Some(&"bob"), Some(&42)),
+	/// 	(&3, None, Some(&37))
+	/// ]);
+	/// ```
+	pub fn join_outer<'a, V2>(
+		&'a self,
+		other: &'a AATreeMap<K, V2>
+	) -> JoinOuter<'a, K, V, V2> {
+		JoinOuter {
+			a: self.iter().peekable(),
+			b: other.iter().peekable()
+		}
+	}
+}
+
+/// An iterator over the keys that are part of two maps. Returned by
+/// [`AATreeMap::join_inner`].
+pub struct JoinInner<'a, K, V1, V2> {
+	a: Iter<'a, K, V1>,
+	b: Iter<'a, K, V2>
+}
+
+impl<K, V1, V2> Debug for JoinInner<'_, K, V1, V2> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("JoinInner").finish_non_exhaustive()
+	}
+}
+
+impl<'a, K: Ord, V1, V2> Iterator for JoinInner<'a, K, V1, V2> {
+	type Item = (&'a K, &'a V1, &'a V2);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let (a, b) = (self.a.peek()?.0, self.b.peek()?.0);
+			match a.cmp(b) {
+				Ordering::Less => {
+					self.a.next();
+				},
+				Ordering::Greater => {
+					self.b.next();
+				},
+				Ordering::Equal => {
+					let (key, a) = self.a.next()?;
+					let (_, b) = self.b.next()?;
+					return Some((key, a, b));
+				}
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let max = self.a.len().min(self.b.len());
+		(0, Some(max))
+	}
+}
+
+impl<K: Ord, V1, V2> FusedIterator for JoinInner<'_, K, V1, V2> {}
+
+impl<K: Ord, V1, V2> SortedIterator for JoinInner<'_, K, V1, V2> {}
+
+/// An iterator over the entries of a map, together with the values of the same keys in
+/// another map. Returned by [`AATreeMap::join_left`].
+pub struct JoinLeft<'a, K, V1, V2> {
+	a: AAIter<'a, KeyValue<K, V1>, (&'a K, &'a V1)>,
+	b: Iter<'a, K, V2>
+}
+
+impl<K, V1, V2> Debug for JoinLeft<'_, K, V1, V2> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("JoinLeft").finish_non_exhaustive()
+	}
+}
+
+impl<'a, K: Ord, V1, V2> Iterator for JoinLeft<'a, K, V1, V2> {
+	type Item = (&'a K, &'a V1, Option<&'a V2>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (key, a) = self.a.next()?;
+		let b = loop {
+			match self.b.peek() {
+				Some((b, _)) if *b < key => {
+					self.b.next();
+				},
+				Some((b, _)) if *b == key => break self.b.next().map(|(_, b)| b),
+				_ => break None
+			}
+		};
+		Some((key, a, b))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.a.size_hint()
+	}
+}
+
+impl<K: Ord, V1, V2> ExactSizeIterator for JoinLeft<'_, K, V1, V2> {}
+
+impl<K: Ord, V1, V2> FusedIterator for JoinLeft<'_, K, V1, V2> {}
+
+impl<K: Ord, V1, V2> SortedIterator for JoinLeft<'_, K, V1, V2> {}
+
+/// An iterator over the keys that are part of either of two maps. Returned by
+/// [`AATreeMap::join_outer`].
+pub struct JoinOuter<'a, K, V1, V2> {
+	a: Iter<'a, K, V1>,
+	b: Iter<'a, K, V2>
+}
+
+impl<K, V1, V2> Debug for JoinOuter<'_, K, V1, V2> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("JoinOuter").finish_non_exhaustive()
+	}
+}
+
+impl<'a, K: Ord, V1, V2> Iterator for JoinOuter<'a, K, V1, V2> {
+	type Item = (&'a K, Option<&'a V1>, Option<&'a V2>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let cmp = match (self.a.peek(), self.b.peek()) {
+			(None, None) => return None,
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(Some((a, _)), Some((b, _))) => a.cmp(b)
+		};
+		Some(match cmp {
+			Ordering::Less => {
+				let (key, a) = self.a.next()?;
+				(key, Some(a), None)
+			},
+			Ordering::Greater => {
+				let (key, b) = self.b.next()?;
+				(key, None, Some(b))
+			},
+			Ordering::Equal => {
+				let (key, a) = self.a.next()?;
+				let (_, b) = self.b.next()?;
+				(key, Some(a), Some(b))
+			}
+		})
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let (a, b) = (self.a.len(), self.b.len());
+		(a.max(b), a.checked_add(b))
+	}
+}
+
+impl<K: Ord, V1, V2> FusedIterator for JoinOuter<'_, K, V1, V2> {}
+
+impl<K: Ord, V1, V2> SortedIterator for JoinOuter<'_, K, V1, V2> {}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::{collections::BTreeMap, vec::Vec};
+
+	#[test]
+	fn test_joins() {
+		let a: AATreeMap<u32, u32> = (0 .. 100).step_by(2).map(|k| (k, k)).collect();
+		let b: AATreeMap<u32, i64> =
+			(0 .. 150).step_by(3).map(|k| (k, -(k as i64))).collect();
+		let empty: AATreeMap<u32, i64> = AATreeMap::new();
+
+		for (a, b) in [(&a, &b), (&a, &empty)] {
+			let a_std: BTreeMap<_, _> = a.iter().collect();
+			let b_std: BTreeMap<_, _> = b.iter().collect();
+
+			let inner: Vec<_> = a.join_inner(b).collect();
+			let expected: Vec<_> = a_std
+				.iter()
+				.filter_map(|(k, v)| b_std.get(k).map(|w| (*k, *v, *w)))
+				.collect();
+			assert_eq!(inner, expected);
+
+			let left: Vec<_> = a.join_left(b).collect();
+			let expected: Vec<_> = a_std
+				.iter()
+				.map(|(k, v)| (*k, *v, b_std.get(k).copied()))
+				.collect();
+			assert_eq!(left, expected);
+			assert_eq!(a.join_left(b).len(), a.len());
+
+			let mut keys: Vec<_> = a_std.keys().chain(b_std.keys()).copied().collect();
+			keys.sort();
+			keys.dedup();
+			let outer: Vec<_> = a.join_outer(b).collect();
+			let expected: Vec<_> = keys
+				.into_iter()
+				.map(|k| (k, a_std.get(k).copied(), b_std.get(k).copied()))
+				.collect();
+			assert_eq!(outer, expected);
+		}
+	}
+}
@@ -0,0 +1,208 @@
+//! This file defines the iterators over only the keys or only the values of a map.
+
+use super::{AATreeMap, KeyValue, RangeMut};
+use crate::{
+	iter::{AAIntoIter, AAIter},
+	sorted::SortedIterator
+};
+use core::{
+	fmt::{self, Debug},
+	iter::FusedIterator
+};
+
+impl<K, V> AATreeMap<K, V> {
+	/// Creates an iterator visiting all the keys, in sorted order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{map::Keys, AATreeMap};
+	/// struct Cursor<'a> {
+	/// 	keys: Keys<'a, u32, char>
+	/// }
+	///
+	/// let map = AATreeMap::from([(2, 'b'), (1, 'a')]);
+	/// let mut cursor = Cursor { keys: map.keys() };
+	/// assert_eq!(cursor.keys.next(), Some(&1));
+	/// assert_eq!(cursor.keys.len(), 1);
+	/// ```
+	pub fn keys(&self) -> Keys<'_, K, V> {
+		Keys {
+			iter: AAIter::new(&self.root, self.len)
+		}
+	}
+
+	/// Creates an iterator visiting all the values, in sorted order.
+	pub fn values(&self) -> Values<'_, K, V> {
+		Values {
+			iter: AAIter::new(&self.root, self.len)
+		}
+	}
+
+	/// Creates an iterator visiting all the values mutably, in order by key.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map = AATreeMap::from([(1, 10), (2, 20), (3, 30)]);
+	/// for value in map.values_mut() {
+	/// 	*value /= 10;
+	/// }
+	/// assert_eq!(map.values().copied().collect::<Vec<_>>(), [1, 2, 3]);
+	/// ```
+	pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+		ValuesMut {
+			iter: RangeMut::full(&mut self.root),
+			len: self.len
+		}
+	}
+
+	/// Creates a consuming iterator visiting all the keys, in sorted order. The map
+	/// cannot be used after calling this.
+	pub fn into_keys(self) -> IntoKeys<K, V> {
+		IntoKeys {
+			iter: AAIntoIter::new(self.root, self.len)
+		}
+	}
+
+	/// Creates a consuming iterator visiting all the values, in order by key. The map
+	/// cannot be used after calling this.
+	pub fn into_values(self) -> IntoValues<K, V> {
+		IntoValues {
+			iter: AAIntoIter::new(self.root, self.len)
+		}
+	}
+}
+
+macro_rules! key_value_iter {
+	($(#[$attr:meta])* $name:ident $(<$lt:lifetime>)?, $iter:ty, $item:ty, |$kv:ident| $content:expr) => {
+		$(#[$attr])*
+		pub struct $name<$($lt,)? K, V> {
+			iter: $iter
+		}
+
+		impl<$($lt,)? K, V> Debug for $name<$($lt,)? K, V> {
+			fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+				f.debug_struct(stringify!($name))
+					.field("remaining", &self.iter.len())
+					.finish_non_exhaustive()
+			}
+		}
+
+		impl<$($lt,)? K, V> Iterator for $name<$($lt,)? K, V> {
+			type Item = $item;
+
+			fn next(&mut self) -> Option<$item> {
+				self.iter.next().map(|$kv| $content)
+			}
+
+			fn size_hint(&self) -> (usize, Option<usize>) {
+				self.iter.size_hint()
+			}
+		}
+
+		impl<$($lt,)? K, V> ExactSizeIterator for $name<$($lt,)? K, V> {}
+
+		impl<$($lt,)? K, V> FusedIterator for $name<$($lt,)? K, V> {}
+	};
+}
+
+key_value_iter!(
+	/// An iterator over the keys of an [`AATreeMap`], created by [`AATreeMap::keys`].
+	Keys<'a>, AAIter<'a, KeyValue<K, V>, &'a KeyValue<K, V>>, &'a K, |kv| &kv.key
+);
+key_value_iter!(
+	/// An iterator over the values of an [`AATreeMap`], created by [`AATreeMap::values`].
+	Values<'a>, AAIter<'a, KeyValue<K, V>, &'a KeyValue<K, V>>, &'a V, |kv| &kv.value
+);
+key_value_iter!(
+	/// An owning iterator over the keys of an [`AATreeMap`], created by
+	/// [`AATreeMap::into_keys`].
+	IntoKeys, AAIntoIter<KeyValue<K, V>, KeyValue<K, V>>, K, |kv| kv.key
+);
+key_value_iter!(
+	/// An owning iterator over the values of an [`AATreeMap`], created by
+	/// [`AATreeMap::into_values`].
+	IntoValues, AAIntoIter<KeyValue<K, V>, KeyValue<K, V>>, V, |kv| kv.value
+);
+
+/// A mutable iterator over the values of an [`AATreeMap`], created by
+/// [`AATreeMap::values_mut`].
+pub struct ValuesMut<'a, K, V> {
+	iter: RangeMut<'a, K, V>,
+	len: usize
+}
+
+impl<K, V> Debug for ValuesMut<'_, K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ValuesMut")
+			.field("remaining", &self.len)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+	type Item = &'a mut V;
+
+	fn next(&mut self) -> Option<&'a mut V> {
+		let (_, value) = self.iter.next()?;
+		self.len -= 1;
+		Some(value)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+impl<K, V> FusedIterator for ValuesMut<'_, K, V> {}
+
+impl<K, V> SortedIterator for Keys<'_, K, V> {}
+
+impl<K, V> SortedIterator for IntoKeys<K, V> {}
+
+#[cfg(test)]
+mod tests {
+	use crate::{sorted::SortedIterator, AATreeMap, AATreeSet};
+	use alloc::{string::String, vec::Vec};
+
+	#[test]
+	fn test_keys_and_values() {
+		let map: AATreeMap<u32, String> = (0 .. 10)
+			.rev()
+			.map(|i| (i, alloc::format!("{i}")))
+			.collect();
+		let mut keys = map.keys();
+		assert_eq!(keys.size_hint(), (10, Some(10)));
+		keys.next();
+		let set: AATreeSet<u32> = keys.copied().collect_sorted();
+		assert_eq!(set.len(), 9);
+		assert_eq!(map.values().map(String::len).sum::<usize>(), 10);
+
+		let keys: Vec<u32> = map.clone().into_keys().collect();
+		assert_eq!(keys, (0 .. 10).collect::<Vec<_>>());
+		let values: Vec<String> = map.into_values().skip(8).collect();
+		assert_eq!(values, ["8", "9"]);
+	}
+
+	#[test]
+	fn test_values_mut() {
+		let mut map: AATreeMap<u32, u32> = AATreeMap::new();
+		assert_eq!(map.values_mut().next(), None);
+
+		map = (0 .. 10).map(|i| (i, i)).collect();
+		let mut values = map.values_mut();
+		assert_eq!(values.len(), 10);
+		*values.next().unwrap() = 100;
+		assert_eq!(values.len(), 9);
+		for value in values {
+			*value *= 2;
+		}
+		assert_eq!(map.get(&0), Some(&100));
+		assert_eq!(map.get(&9), Some(&18));
+		assert_eq!(map.values_mut().len(), 10);
+	}
+}
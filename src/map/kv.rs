@@ -1,7 +1,12 @@
 //! This file defines the [`KeyValue`] type that is used by [`AATreeMap`](crate::AATreeMap).
 
 use crate::iter::IterContent;
-use core::{borrow::Borrow, cmp::Ordering};
+use core::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt::{self, Display, Formatter},
+	str::FromStr
+};
 
 /// An entry in an [`AATreeMap`](crate::AATreeMap). This type is used with iterators
 /// returned by [`AATreeMap`](crate::AATreeMap).
@@ -38,6 +43,63 @@ impl<'a, K, V> IterContent<(&'a K, &'a V)> for &'a KeyValue<K, V> {
 	}
 }
 
+/// Displays the entry as `key: value`, which is used by the canonical tree encoding.
+impl<K: Display, V: Display> Display for KeyValue<K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: {}", self.key, self.value)
+	}
+}
+
+/// The error returned when parsing an entry of a map from `key: value` fails, e.g. by
+/// [`AATreeMap::from_canonical_str`](crate::AATreeMap::from_canonical_str).
+#[derive(Debug)]
+pub enum ParseKeyValueError<K, V> {
+	/// The string does not contain a colon separating the key from the value.
+	MissingColon,
+	/// The key could not be parsed.
+	Key(K),
+	/// The value could not be parsed.
+	Value(V)
+}
+
+impl<K: Display, V: Display> Display for ParseKeyValueError<K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::MissingColon => f.write_str("Missing colon between key and value"),
+			Self::Key(err) => write!(f, "Invalid key: {err}"),
+			Self::Value(err) => write!(f, "Invalid value: {err}")
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K, V> std::error::Error for ParseKeyValueError<K, V>
+where
+	K: std::error::Error + 'static,
+	V: std::error::Error + 'static
+{
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::MissingColon => None,
+			Self::Key(err) => Some(err),
+			Self::Value(err) => Some(err)
+		}
+	}
+}
+
+/// Parses an entry from `key: value`. The key must not contain a colon.
+impl<K: FromStr, V: FromStr> FromStr for KeyValue<K, V> {
+	type Err = ParseKeyValueError<K::Err, V::Err>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (key, value) = s.split_once(':').ok_or(ParseKeyValueError::MissingColon)?;
+		Ok(Self {
+			key: key.trim().parse().map_err(ParseKeyValueError::Key)?,
+			value: value.trim().parse().map_err(ParseKeyValueError::Value)?
+		})
+	}
+}
+
 impl<K, V> Borrow<K> for KeyValue<K, V> {
 	fn borrow(&self) -> &K {
 		&self.key
@@ -75,3 +137,26 @@ impl<K: Ord, V> Ord for KeyValue<K, V> {
 		self.key.cmp(&other.key)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{KeyValue, ParseKeyValueError};
+	use alloc::string::ToString;
+
+	#[test]
+	fn test_parse_key_value() {
+		let kv: KeyValue<u32, char> = " 1 : a ".parse().unwrap();
+		assert_eq!((kv.key, kv.value), (1, 'a'));
+
+		let err = "1 a".parse::<KeyValue<u32, char>>().unwrap_err();
+		assert!(matches!(err, ParseKeyValueError::MissingColon));
+		let err = "x: a".parse::<KeyValue<u32, char>>().unwrap_err();
+		assert!(matches!(err, ParseKeyValueError::Key(_)));
+		assert_eq!(
+			err.to_string(),
+			"Invalid key: invalid digit found in string"
+		);
+		let err = "1: ab".parse::<KeyValue<u32, char>>().unwrap_err();
+		assert!(matches!(err, ParseKeyValueError::Value(_)));
+	}
+}
@@ -1,22 +1,61 @@
 use crate::{
+	fmt::{DebugTruncated, DisplayMap},
 	iter::{AAIntoIter, AAIter},
-	node::{AANode, TraverseStep}
+	node::{AANode, Keep, ParseCanonicalError, TraverseStep},
+	sorted::SortedIterator
 };
-use alloc::vec::Vec;
+use alloc::{boxed::Box, string::String, vec::Vec};
 use core::{
 	borrow::Borrow,
 	cmp::Ordering,
-	fmt::{self, Debug},
+	fmt::{self, Debug, Display},
+	hash::{Hash, Hasher},
 	iter::FromIterator,
-	mem
+	mem,
+	ops::Index,
+	str::FromStr
 };
 
+mod bulk;
+mod closest;
+mod descending;
+mod digest;
 mod entry;
+mod extend;
+mod extract;
 mod get;
+mod group;
+mod hint;
+mod invert;
+mod join;
+mod keys;
 mod kv;
-
-pub use entry::{Entry, OccupiedEntry, VacantEntry};
-use kv::KeyValue;
+mod multi;
+mod range;
+mod shard;
+mod split;
+mod stream;
+
+pub use bulk::BulkOp;
+pub(crate) use closest::closest;
+pub use closest::Tie;
+pub use descending::Descending;
+pub use entry::{
+	Entry, EntryRef, OccupiedEntry, OccupiedError, PopEntry, VacantEntry, VacantEntryRef
+};
+pub use extend::{DuplicateKeyError, TryExtendError};
+pub use extract::{Drain, ExtractIf};
+pub use get::DepthExceeded;
+pub(crate) use group::group_fold;
+pub use hint::{LookupHint, QueryStats};
+pub use join::{JoinInner, JoinLeft, JoinOuter};
+pub use keys::{IntoKeys, IntoValues, Keys, Values, ValuesMut};
+pub(crate) use kv::KeyValue;
+pub use kv::ParseKeyValueError;
+pub(crate) use range::RawRange;
+pub use range::{Range, RangeMut};
+pub use shard::OverlapError;
+pub use stream::{NotSortedError, SortedStream};
 
 #[derive(Clone)]
 pub struct AATreeMap<K, V> {
@@ -24,6 +63,42 @@ pub struct AATreeMap<K, V> {
 	len: usize
 }
 
+/// An [`AATreeMap`] that stores its values out-of-line.
+///
+/// Every node of the tree stores its key and value next to each other. When the values
+/// are large, this means that every node touched while searching for a key is large, even
+/// though only the keys are needed to navigate the tree. Boxing the values keeps the
+/// nodes small, at the cost of one additional allocation per entry.
+///
+/// As a rule of thumb, consider this alias when values are larger than a few cache lines.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::map::BoxedAATreeMap;
+/// let mut map: BoxedAATreeMap<u32, [u8; 256]> = BoxedAATreeMap::new();
+/// map.insert(1, Box::new([0; 256]));
+/// assert_eq!(map[&1][0], 0);
+/// ```
+pub type BoxedAATreeMap<K, V> = AATreeMap<K, Box<V>>;
+
+/// An [`AATreeMap`] keyed by timestamps, e.g. milliseconds since the UNIX epoch, like it is
+/// used for time series and event logs.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::map::AATimestampMap;
+/// let mut events: AATimestampMap<&str> = AATimestampMap::new();
+/// events.insert(1_700_000_000_000, "start");
+/// events.insert(1_700_000_060_000, "stop");
+/// assert_eq!(
+/// 	events.last_key_value_at_or_before(&1_700_000_030_000),
+/// 	Some((&1_700_000_000_000, &"start"))
+/// );
+/// ```
+pub type AATimestampMap<V> = AATreeMap<u64, V>;
+
 impl<K, V> Default for AATreeMap<K, V> {
 	fn default() -> Self {
 		Self::new()
@@ -53,6 +128,17 @@ impl<K: PartialEq, V: PartialEq> PartialEq for AATreeMap<K, V> {
 
 impl<K: Eq, V: Eq> Eq for AATreeMap<K, V> {}
 
+/// The hash only depends on the entries of the map in ascending order, so maps with equal
+/// contents hash identically regardless of the order in which they were inserted.
+impl<K: Hash, V: Hash> Hash for AATreeMap<K, V> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		state.write_usize(self.len);
+		for entry in self {
+			entry.hash(state);
+		}
+	}
+}
+
 impl<K: PartialOrd, V: PartialOrd> PartialOrd for AATreeMap<K, V> {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
 		self.iter().partial_cmp(other.iter())
@@ -82,6 +168,55 @@ impl<K, V> AATreeMap<K, V> {
 		}
 	}
 
+	/// Build a map from exactly `len` entries with strictly ascending keys in O(n).
+	pub(crate) fn from_sorted_unchecked<I>(iter: I, len: usize) -> Self
+	where
+		I: IntoIterator<Item = (K, V)>
+	{
+		let mut iter = iter.into_iter().map(|(key, value)| KeyValue { key, value });
+		Self {
+			root: AANode::from_sorted_iter(&mut iter, len),
+			len
+		}
+	}
+
+	/// Build a map from an iterator that is known to yield its entries in strictly
+	/// ascending order of their keys, in O(n) without comparing any keys. Debug builds
+	/// panic if the keys are not strictly ascending.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, char> = (0 .. 10).zip('a' ..).collect();
+	/// let copy = AATreeMap::from_sorted_iter(map.range(3 ..= 5));
+	/// assert_eq!(copy.into_iter().collect::<Vec<_>>(), [
+	/// 	(&3, &'d'),
+	/// 	(&4, &'e'),
+	/// 	(&5, &'f')
+	/// ]);
+	/// ```
+	pub fn from_sorted_iter<I>(iter: I) -> Self
+	where
+		K: Ord,
+		I: IntoIterator<Item = (K, V)>,
+		I::IntoIter: SortedIterator
+	{
+		let iter = iter.into_iter().map(|(key, value)| KeyValue { key, value });
+		let (root, len) = AANode::from_sorted_items(iter);
+		Self { root, len }
+	}
+
+	#[cfg(test)]
+	#[track_caller]
+	pub(crate) fn assert_valid(&self)
+	where
+		K: Ord
+	{
+		self.root.assert_valid();
+		assert_eq!(self.root.count(), self.len);
+	}
+
 	/// Returns the number of elements in the map.
 	///
 	/// # Example
@@ -112,6 +247,34 @@ impl<K, V> AATreeMap<K, V> {
 		self.len == 0
 	}
 
+	/// Returns the level of the root of the tree, or `0` if the map is empty. This takes
+	/// O(1) time.
+	///
+	/// The level of the root never exceeds `log2(n + 1)` for a map with `n` elements.
+	/// Every path from the root to a leaf visits at most two nodes per level, so the
+	/// [`height`](Self::height) of the tree, and thereby the number of nodes visited by
+	/// any operation, never exceeds `2 * level`, which is at most `2 * log2(n + 1)`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, u32> = (1 ..= 100).map(|i| (i, i)).collect();
+	/// assert!(map.level() <= 6);
+	/// assert!(map.height() <= 2 * map.level() as usize);
+	/// ```
+	pub fn level(&self) -> u8 {
+		self.root.level()
+	}
+
+	/// Returns the height of the tree, that is, the number of nodes on the longest path
+	/// from the root to a leaf, or `0` if the map is empty. This walks the whole tree and
+	/// takes O(n) time. Use [`level`](Self::level) for an upper bound that can be
+	/// computed in O(1) time.
+	pub fn height(&self) -> usize {
+		self.root.height()
+	}
+
 	/// Clears the map, removing all elements.
 	///
 	/// # Example
@@ -133,30 +296,37 @@ impl<K, V> AATreeMap<K, V> {
 		self.into_iter()
 	}
 
-	/// Creates an iterator visiting all the keys, in sorted order.
-	pub fn keys(&self) -> impl Iterator<Item = &K> {
-		// TODO is there a better way to implement this?
-		self.iter().map(|(k, _)| k)
-	}
-
-	/// Creates an iterator visiting all the values, in sorted order.
-	pub fn values(&self) -> impl Iterator<Item = &V> {
-		// TODO is there a better way to implement this?
-		self.iter().map(|(_, v)| v)
-	}
-
-	/// Creates a consuming iterator visiting all the keys, in sorted order. The map
-	/// cannot be used after calling this.
-	pub fn into_keys(self) -> impl Iterator<Item = K> {
-		// TODO is there a better way to implement this?
-		self.into_iter().map(|(k, _)| k)
+	/// Returns an adapter that formats this map like its [`Debug`] implementation, but
+	/// prints only the first and last `n` entries and the number of omitted entries. This
+	/// is useful to log maps that might be huge.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, u32> = (0 .. 1000).map(|i| (i, i * i)).collect();
+	/// assert_eq!(
+	/// 	format!("{:?}", map.debug_truncated(2)),
+	/// 	"{0: 0, 1: 1, ... 996 more ..., 998: 996004, 999: 998001}"
+	/// );
+	/// ```
+	pub fn debug_truncated(&self, n: usize) -> DebugTruncated<'_, Self>
+	where
+		K: Debug,
+		V: Debug
+	{
+		DebugTruncated::new(self, n)
 	}
 
-	/// Creates a consuming iterator visiting all the values, in order by key. The map
-	/// cannot be used after calling this.
-	pub fn into_values(self) -> impl Iterator<Item = V> {
-		// TODO is there a better way to implement this?
-		self.into_iter().map(|(_, v)| v)
+	/// Render the structure of this map in the DOT format understood by Graphviz.
+	#[cfg(feature = "debug-tools")]
+	pub fn to_dot(&self) -> alloc::string::String
+	where
+		K: Debug,
+		V: Debug
+	{
+		self.root
+			.to_dot_with(|kv| alloc::format!("{:?}: {:?}", kv.key, kv.value))
 	}
 
 	/// Insert a new element into the map, or overwrite an existing element
@@ -181,13 +351,20 @@ impl<K, V> AATreeMap<K, V> {
 		match inserted {
 			None => {
 				self.len += 1;
+				#[cfg(feature = "tracing")]
+				self.root.check_height(self.len);
 				None
 			},
 			Some(entry) => Some(entry.value)
 		}
 	}
 
-	/// Moves all elements from `other` into `self`, leaving `other` empty.
+	/// Moves all elements from `other` into `self`, leaving `other` empty. If a key from
+	/// `other` is already present in `self`, the entry from `other` replaces it.
+	///
+	/// If `other` is small compared to `self`, its entries are inserted one by one in
+	/// O(m log(n + m)). Otherwise, both maps are merged in O(n + m) and rebuilt into a
+	/// balanced tree, without reallocating any node.
 	///
 	/// # Examples
 	///
@@ -219,7 +396,8 @@ impl<K, V> AATreeMap<K, V> {
 	where
 		K: Ord
 	{
-		self.extend(mem::take(other));
+		let other = mem::take(other);
+		self.len = self.root.append(self.len, other.root, other.len, Keep::New);
 	}
 
 	/// Check if a key is contained within this map.
@@ -270,7 +448,7 @@ impl<K, V> AATreeMap<K, V> {
 		K: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		self.root.remove::<Q, K>(k).map(|entry| entry.value)
+		self.remove_entry(k).map(|(_, value)| value)
 	}
 
 	/// Remove a key from the map if it exists, and return the key and the value that was
@@ -293,7 +471,108 @@ impl<K, V> AATreeMap<K, V> {
 		K: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		self.root.remove::<Q, K>(k).map(KeyValue::into_tuple)
+		let entry = self.root.remove::<Q, K>(k)?;
+		self.len -= 1;
+		Some(entry.into_tuple())
+	}
+
+	/// Retains only the entries for which `f` returns `true`, visiting the entries in
+	/// ascending order of their keys.
+	///
+	/// The tree is rebuilt in place in O(n) without allocating new nodes, which is
+	/// considerably faster than removing every entry individually or collecting the
+	/// remaining entries into a new map. If `f` panics, the map is left empty.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map: AATreeMap<u32, u32> = (0 .. 8).map(|x| (x, x * 10)).collect();
+	/// map.retain(|&key, _| key % 2 == 0);
+	/// assert_eq!(map.into_iter().collect::<Vec<_>>(), [
+	/// 	(0, 0),
+	/// 	(2, 20),
+	/// 	(4, 40),
+	/// 	(6, 60)
+	/// ]);
+	/// ```
+	pub fn retain<F>(&mut self, mut f: F)
+	where
+		F: FnMut(&K, &mut V) -> bool
+	{
+		let len = mem::take(&mut self.len);
+		let removed = self.root.retain(|kv| {
+			let (key, value) = kv.as_tuple_mut();
+			f(key, value)
+		});
+		self.len = len - removed;
+	}
+}
+
+impl<K: Display, V: Display> AATreeMap<K, V> {
+	/// Returns an adapter that displays all entries of this map in order. Every key is
+	/// separated from its value by `kv_sep`, and the entries are separated by `entry_sep`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([("b", 2), ("a", 1)]);
+	/// assert_eq!(map.display_with("=", ", ").to_string(), "a=1, b=2");
+	/// ```
+	pub fn display_with<'a>(
+		&'a self,
+		kv_sep: &'a str,
+		entry_sep: &'a str
+	) -> DisplayMap<'a, K, V> {
+		DisplayMap::new(self, kv_sep, entry_sep)
+	}
+
+	/// Encode the structure of this map as a deterministic single-line string, with
+	/// entries written as `key: value`. See [`AANode::to_canonical_string`] for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+	/// assert_eq!(map.to_canonical_string(), "2: b => [2, 1: a, 3: c]");
+	/// ```
+	pub fn to_canonical_string(&self) -> String {
+		self.root.to_canonical_string()
+	}
+}
+
+/// The error returned by [`AATreeMap::from_canonical_str`].
+type ParseMapError<K, V> =
+	ParseCanonicalError<ParseKeyValueError<<K as FromStr>::Err, <V as FromStr>::Err>>;
+
+impl<K: FromStr, V: FromStr> AATreeMap<K, V> {
+	/// Parse a map from the canonical encoding of its structure. See
+	/// [`AANode::from_canonical_str`] for details. Keys must not contain a colon.
+	///
+	/// The encoded tree is not checked for being a valid AA tree with ordered keys.
+	pub fn from_canonical_str(s: &str) -> Result<Self, ParseMapError<K, V>> {
+		let root = AANode::from_canonical_str(s)?;
+		let len = root.count();
+		Ok(Self { root, len })
+	}
+}
+
+impl<K, V, Q> Index<&Q> for AATreeMap<K, V>
+where
+	K: Borrow<Q> + Ord,
+	Q: Ord + ?Sized
+{
+	type Output = V;
+
+	/// Returns a reference to the value corresponding to the supplied key.
+	///
+	/// # Panics
+	///
+	/// Panics if the key is not present in the map.
+	fn index(&self, key: &Q) -> &V {
+		self.get(key).expect("no entry found for key")
 	}
 }
 
@@ -324,9 +603,8 @@ impl<K: Ord, V> From<Vec<(K, V)>> for AATreeMap<K, V> {
 
 impl<K: Ord, V> Extend<(K, V)> for AATreeMap<K, V> {
 	fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
-		for (key, value) in iter {
-			self.insert(key, value);
-		}
+		let iter = iter.into_iter().map(|(key, value)| KeyValue { key, value });
+		self.len = self.root.extend(self.len, iter, Keep::New);
 	}
 }
 
@@ -355,3 +633,101 @@ impl<'a, K, V> IntoIterator for &'a AATreeMap<K, V> {
 		AAIter::new(&self.root, self.len)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::{boxed::Box, rc::Rc, string::String, sync::Arc, vec::Vec};
+
+	#[test]
+	fn test_len_after_removal() {
+		let mut map: AATreeMap<u32, u32> = (0 .. 10).map(|i| (i, i)).collect();
+		assert_eq!(map.remove(&3), Some(3));
+		assert_eq!(map.remove(&3), None);
+		assert_eq!(map.remove_entry(&4), Some((4, 4)));
+		assert_eq!(map.pop_first(), Some((0, 0)));
+		assert_eq!(map.pop_last(), Some((9, 9)));
+		assert_eq!(map.len(), 6);
+		assert_eq!(map.iter().count(), 6);
+	}
+
+	macro_rules! test_borrowed_lookups {
+		($name:ident : $key:ty, $query:ty, $conv:expr) => {
+			#[test]
+			fn $name() {
+				let conv = $conv;
+				let mut map: AATreeMap<$key, u32> = AATreeMap::new();
+				for (i, key) in ["b", "d", "f", "h"].into_iter().enumerate() {
+					map.insert(conv(key), i as u32);
+				}
+				let q = |key: &'static str| -> &$query { key.as_ref() };
+
+				assert!(map.contains_key(q("d")));
+				assert!(!map.contains_key(q("e")));
+				assert_eq!(map.get(q("f")), Some(&2));
+				assert_eq!(map[q("h")], 3);
+				assert_eq!(map.get_key_value(q("b")).map(|(_, v)| *v), Some(0));
+				*map.get_mut(q("b")).unwrap() += 10;
+				assert_eq!(map.get(q("b")), Some(&10));
+
+				let at_or_after = map.first_key_value_at_or_after(q("c"));
+				assert_eq!(at_or_after.map(|(_, v)| *v), Some(1));
+				let at_or_before = map.last_key_value_at_or_before(q("g"));
+				assert_eq!(at_or_before.map(|(_, v)| *v), Some(2));
+				let (_, v) = map.first_key_value_mut_at_or_after(q("e")).unwrap();
+				*v += 10;
+				let (_, v) = map.last_key_value_mut_at_or_before(q("e")).unwrap();
+				*v += 10;
+				assert_eq!(map.values().copied().collect::<Vec<_>>(), [10, 11, 12, 3]);
+
+				assert_eq!(map.remove(q("d")), Some(11));
+				assert_eq!(map.remove(q("d")), None);
+				let (key, value) = map.remove_entry(q("f")).unwrap();
+				assert_eq!(key, conv("f"));
+				assert_eq!(value, 12);
+				assert_eq!(map.len(), 2);
+			}
+		};
+	}
+
+	test_borrowed_lookups!(test_string_by_str: String, str, String::from);
+	test_borrowed_lookups!(test_box_str_by_str: Box<str>, str, Box::<str>::from);
+	test_borrowed_lookups!(test_arc_str_by_str: Arc<str>, str, Arc::<str>::from);
+	test_borrowed_lookups!(test_rc_bytes_by_slice: Rc<[u8]>, [u8], |key: &str| Rc::<[u8]>::from(key.as_bytes()));
+	test_borrowed_lookups!(test_box_bytes_by_slice: Box<[u8]>, [u8], |key: &str| Box::<[u8]>::from(key.as_bytes()));
+
+	/// Check that `2 ^ level <= n + 1` and `height <= 2 * level`, which together
+	/// imply `height <= 2 * log2(n + 1)`.
+	fn assert_height_bound(map: &AATreeMap<u32, ()>) {
+		let (level, height, len) = (map.level() as u32, map.height() as u32, map.len());
+		assert!(
+			1 << level <= len + 1,
+			"level {level} too high for {len} entries"
+		);
+		assert!(height <= 2 * level);
+		assert!(1u128 << height <= (len as u128 + 1).pow(2));
+	}
+
+	#[test]
+	fn test_height_bound() {
+		let mut map = AATreeMap::new();
+		assert_eq!((map.level(), map.height()), (0, 0));
+
+		// ascending inserts followed by pseudo-random inserts and removals
+		for key in 0 .. 1000 {
+			map.insert(key, ());
+			assert_height_bound(&map);
+		}
+		let mut state = 7u32;
+		for _ in 0 .. 5000 {
+			state = state.wrapping_mul(1103515245).wrapping_add(12345);
+			let key = state % 2000;
+			if state & 0x10000 == 0 {
+				map.insert(key, ());
+			} else {
+				map.remove(&key);
+			}
+			assert_height_bound(&map);
+		}
+	}
+}
@@ -0,0 +1,173 @@
+//! This file defines conversions between maps of sets and sets of key-value pairs.
+
+use super::AATreeMap;
+use crate::{set::Range, AATreeSet};
+use alloc::vec::Vec;
+use core::{borrow::Borrow, ops::RangeBounds};
+
+impl<K, V> AATreeMap<K, AATreeSet<V>> {
+	/// Returns the total number of values in all sets of this map.
+	pub fn flat_len(&self) -> usize {
+		self.values().map(AATreeSet::len).sum()
+	}
+
+	/// Creates an iterator that visits all key-value pairs of this map of sets in
+	/// ascending order, without collecting them first.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, AATreeSet};
+	/// let map = AATreeMap::from([
+	/// 	(1, AATreeSet::from(['b', 'a'])),
+	/// 	(2, AATreeSet::from(['c']))
+	/// ]);
+	/// assert_eq!(map.iter_flat().collect::<Vec<_>>(), [
+	/// 	(&1, &'a'),
+	/// 	(&1, &'b'),
+	/// 	(&2, &'c')
+	/// ]);
+	/// ```
+	pub fn iter_flat(&self) -> impl Iterator<Item = (&K, &V)> {
+		self.iter()
+			.flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+	}
+
+	/// Creates an iterator over the values of `key` that are within `range`, in ascending
+	/// order. Since the values of every key are kept sorted, this takes O(log n + log m)
+	/// time to find the first value, where m is the number of values of `key`, instead
+	/// of scanning all values.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, AATreeSet};
+	/// // user id -> event timestamps
+	/// let events = AATreeMap::from([
+	/// 	(1, AATreeSet::from([100, 250, 300, 420])),
+	/// 	(2, AATreeSet::from([120]))
+	/// ]);
+	/// assert_eq!(events.get_all_range(&1, 200 .. 400).collect::<Vec<_>>(), [
+	/// 	&250, &300
+	/// ]);
+	/// assert_eq!(events.get_all_range(&3, ..).count(), 0);
+	/// ```
+	pub fn get_all_range<Q, W, R>(&self, key: &Q, range: R) -> Range<'_, V>
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized,
+		V: Borrow<W> + Ord,
+		W: Ord + ?Sized,
+		R: RangeBounds<W>
+	{
+		match self.get(key) {
+			Some(set) => set.range(range),
+			None => Range::empty()
+		}
+	}
+
+	/// Flattens this map of sets into a set of key-value pairs. Keys with an empty set are
+	/// dropped. Since the pairs are produced in ascending order, the resulting tree is
+	/// built in O(n) without any comparisons.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, AATreeSet};
+	/// let map = AATreeMap::from([(1, AATreeSet::from(['b', 'a'])), (2, AATreeSet::new())]);
+	/// assert_eq!(map.into_flat(), AATreeSet::from([(1, 'a'), (1, 'b')]));
+	/// ```
+	pub fn into_flat(self) -> AATreeSet<(K, V)>
+	where
+		K: Clone
+	{
+		let len = self.flat_len();
+		let pairs = self.into_iter().flat_map(|(key, set)| {
+			set.into_iter().map(move |value| (key.clone(), value))
+		});
+		AATreeSet::from_sorted_unchecked(pairs, len)
+	}
+}
+
+impl<K: PartialEq, V> AATreeSet<(K, V)> {
+	/// Groups this set of key-value pairs into a map of sets, the inverse of
+	/// [`AATreeMap::into_flat`]. Since the pairs are visited in ascending order, all trees
+	/// are built in O(n) without any comparisons.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, AATreeSet};
+	/// let set = AATreeSet::from([(2, 'c'), (1, 'a'), (1, 'b')]);
+	/// assert_eq!(
+	/// 	set.into_grouped(),
+	/// 	AATreeMap::from([
+	/// 		(1, AATreeSet::from(['a', 'b'])),
+	/// 		(2, AATreeSet::from(['c']))
+	/// 	])
+	/// );
+	/// ```
+	pub fn into_grouped(self) -> AATreeMap<K, AATreeSet<V>> {
+		let mut groups = Vec::new();
+		let mut values = Vec::new();
+		let mut current = None;
+		for (key, value) in self {
+			match current {
+				Some(ref current_key) if *current_key == key => {},
+				_ => {
+					if let Some(prev_key) = current.replace(key) {
+						let len = values.len();
+						groups.push((
+							prev_key,
+							AATreeSet::from_sorted_unchecked(values.drain(..), len)
+						));
+					}
+				},
+			}
+			values.push(value);
+		}
+		if let Some(key) = current {
+			let len = values.len();
+			groups.push((key, AATreeSet::from_sorted_unchecked(values, len)));
+		}
+		let len = groups.len();
+		AATreeMap::from_sorted_unchecked(groups, len)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeSet;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_flatten_roundtrip() {
+		let set: AATreeSet<(u32, u32)> = (0 .. 100).map(|x| (x % 7, x)).collect();
+		let grouped = set.clone().into_grouped();
+		grouped.assert_valid();
+		assert_eq!(grouped.len(), 7);
+		assert_eq!(grouped.flat_len(), 100);
+		for (key, values) in &grouped {
+			assert!(values.iter().all(|v| v % 7 == *key));
+		}
+
+		let flat: Vec<(u32, u32)> = grouped.iter_flat().map(|(k, v)| (*k, *v)).collect();
+		assert_eq!(flat, set.iter().copied().collect::<Vec<_>>());
+		assert_eq!(grouped.into_flat(), set);
+
+		assert!(AATreeSet::<(u8, u8)>::new().into_grouped().is_empty());
+	}
+
+	#[test]
+	fn test_get_all_range() {
+		let set: AATreeSet<(u32, u32)> = (0 .. 100).map(|x| (x % 7, x)).collect();
+		let grouped = set.into_grouped();
+		for key in 0 .. 8 {
+			for (start, end) in [(0, 100), (10, 50), (50, 10), (33, 34)] {
+				let values: Vec<_> = grouped.get_all_range(&key, start .. end).collect();
+				let expected: Vec<_> = (start .. end).filter(|x| x % 7 == key).collect();
+				assert_eq!(values.into_iter().copied().collect::<Vec<_>>(), expected);
+			}
+		}
+	}
+}
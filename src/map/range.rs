@@ -0,0 +1,504 @@
+//! This file defines operations on ranges of keys.
+
+use super::{AATreeMap, KeyValue};
+use crate::{
+	node::{AANode, Node},
+	search::{self, after_end, before_start},
+	sorted::SortedIterator
+};
+use alloc::vec::Vec;
+use core::{
+	borrow::Borrow,
+	fmt::{self, Debug},
+	iter::FusedIterator,
+	ops::{Bound, RangeBounds},
+	ptr
+};
+
+type Tree<K, V> = AANode<KeyValue<K, V>>;
+
+/// The content of a node together with its right subtree.
+type ContentAndRight<'a, K, V> = (&'a mut KeyValue<K, V>, &'a mut Tree<K, V>);
+
+/// An iterator over a contiguous range of elements of a tree, shared by the range
+/// iterators of maps and sets.
+pub(crate) struct RawRange<'a, T> {
+	/// The nodes whose content is visited next, with the next one on top. The left
+	/// subtrees of all of these nodes have already been visited or are out of range.
+	stack: Vec<&'a Node<T>>,
+	/// The last element within the range.
+	last: Option<&'a T>
+}
+
+impl<'a, T> RawRange<'a, T> {
+	/// Create an iterator over all elements for which neither `is_before` nor `is_after`
+	/// returns `true`. The tree must be partitioned by both predicates as described in
+	/// the [`search`] module.
+	pub(crate) fn new<B, A>(root: &'a AANode<T>, mut is_before: B, is_after: A) -> Self
+	where
+		B: FnMut(&T) -> bool,
+		A: FnMut(&T) -> bool
+	{
+		let mut range = Self::empty();
+		// the range is empty unless the last element at or before the end is at or after
+		// the start
+		range.last = search::upper_bound(root, is_after).filter(|last| !is_before(last));
+		if range.last.is_none() {
+			return range;
+		}
+
+		range.stack.reserve(root.level() as usize * 2);
+		let mut node = root.as_ref();
+		while let Some(n) = node {
+			node = if is_before(&n.content) {
+				n.right_child.as_ref()
+			} else {
+				range.stack.push(n);
+				n.left_child.as_ref()
+			};
+		}
+		range
+	}
+
+	/// Create an iterator that yields no elements.
+	pub(crate) fn empty() -> Self {
+		Self {
+			stack: Vec::new(),
+			last: None
+		}
+	}
+
+	fn push_left_spine(&mut self, mut node: &'a AANode<T>) {
+		while let Some(n) = node.as_ref() {
+			self.stack.push(n);
+			node = &n.left_child;
+		}
+	}
+}
+
+impl<'a, T> Iterator for RawRange<'a, T> {
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<&'a T> {
+		let node = self.stack.pop()?;
+		if self.last.map_or(true, |last| ptr::eq(last, &node.content)) {
+			self.stack.clear();
+			self.last = None;
+		} else {
+			self.push_left_spine(&node.right_child);
+		}
+		Some(&node.content)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(usize::from(!self.stack.is_empty()), None)
+	}
+}
+
+impl<T> FusedIterator for RawRange<'_, T> {}
+
+/// An iterator over a range of entries of an [`AATreeMap`], returned by
+/// [`AATreeMap::range`].
+pub struct Range<'a, K, V>(RawRange<'a, KeyValue<K, V>>);
+
+impl<K, V> Debug for Range<'_, K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Range").finish_non_exhaustive()
+	}
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+	type Item = (&'a K, &'a V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.next().map(KeyValue::as_tuple)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.0.size_hint()
+	}
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+impl<K, V> SortedIterator for Range<'_, K, V> {}
+
+/// A mutable iterator over a range of entries of an [`AATreeMap`], returned by
+/// [`AATreeMap::range_mut`].
+pub struct RangeMut<'a, K, V> {
+	/// The contents visited next together with their right subtrees, with the next one on
+	/// top. The left subtrees have already been visited or are out of range.
+	stack: Vec<ContentAndRight<'a, K, V>>,
+	/// The address of the last entry within the range. Only its address is stored since
+	/// a reference would alias with the mutable reference handed out for it.
+	last: usize
+}
+
+impl<K, V> Debug for RangeMut<'_, K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("RangeMut").finish_non_exhaustive()
+	}
+}
+
+impl<'a, K, V> RangeMut<'a, K, V> {
+	fn new<Q>(
+		root: &'a mut AANode<KeyValue<K, V>>,
+		start: Bound<&Q>,
+		end: Bound<&Q>
+	) -> Self
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let last = search::upper_bound(root, |kv| after_end(&kv.key, end))
+			.filter(|last| !before_start(&last.key, start))
+			.map(|last| last as *const KeyValue<K, V> as usize);
+		let mut range = Self {
+			stack: Vec::with_capacity(root.level() as usize * 2),
+			last: last.unwrap_or(0)
+		};
+		if last.is_none() {
+			return range;
+		}
+
+		let mut node = root;
+		while let Some(Node {
+			content,
+			left_child,
+			right_child,
+			..
+		}) = node.as_mut()
+		{
+			node = if before_start(&content.key, start) {
+				right_child
+			} else {
+				range.stack.push((content, right_child));
+				left_child
+			};
+		}
+		range
+	}
+
+	/// Create an iterator over all entries of the tree.
+	pub(super) fn full(root: &'a mut AANode<KeyValue<K, V>>) -> Self {
+		let mut last = 0;
+		let mut node = root.as_ref();
+		while let Some(n) = node {
+			last = &n.content as *const KeyValue<K, V> as usize;
+			node = n.right_child.as_ref();
+		}
+		let mut range = Self {
+			stack: Vec::with_capacity(root.level() as usize * 2),
+			last
+		};
+		range.push_left_spine(root);
+		range
+	}
+
+	fn push_left_spine(&mut self, mut node: &'a mut Tree<K, V>) {
+		while let Some(Node {
+			content,
+			left_child,
+			right_child,
+			..
+		}) = node.as_mut()
+		{
+			self.stack.push((content, right_child));
+			node = left_child;
+		}
+	}
+}
+
+impl<'a, K, V> Iterator for RangeMut<'a, K, V> {
+	type Item = (&'a K, &'a mut V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (content, right_child) = self.stack.pop()?;
+		if content as *const KeyValue<K, V> as usize == self.last {
+			self.stack.clear();
+		} else {
+			self.push_left_spine(right_child);
+		}
+		Some(content.as_tuple_mut())
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(usize::from(!self.stack.is_empty()), None)
+	}
+}
+
+impl<K, V> FusedIterator for RangeMut<'_, K, V> {}
+
+impl<K, V> SortedIterator for RangeMut<'_, K, V> {}
+
+/// Visit all entries in `node` with keys between `start` and `end` in ascending order.
+fn for_each_mut<K, V, Q, F>(
+	node: &mut AANode<KeyValue<K, V>>,
+	start: Bound<&Q>,
+	end: Bound<&Q>,
+	f: &mut F
+) where
+	K: Borrow<Q>,
+	Q: Ord + ?Sized,
+	F: FnMut(&K, &mut V)
+{
+	let Node {
+		content,
+		left_child,
+		right_child,
+		..
+	} = match node.as_mut() {
+		Some(node) => node,
+		None => return
+	};
+	let before = before_start(&content.key, start);
+	let after = after_end(&content.key, end);
+	if !before {
+		for_each_mut(left_child, start, end, f);
+	}
+	if !before && !after {
+		f(&content.key, &mut content.value);
+	}
+	if !after {
+		for_each_mut(right_child, start, end, f);
+	}
+}
+
+impl<K, V> AATreeMap<K, V> {
+	/// Creates an iterator over the entries with keys within `range` in ascending order.
+	///
+	/// The iterator starts at the first entry within the range, which is found in
+	/// O(log n), and stops after the last entry within the range without visiting any
+	/// other entries of the map. Unlike [`BTreeMap::range`](alloc::collections::BTreeMap::range),
+	/// this does not panic if the start of the range is after its end, but returns an
+	/// empty iterator.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// use std::ops::Bound;
+	///
+	/// let map: AATreeMap<u32, char> = (0 .. 10).zip('a' ..).collect();
+	/// assert_eq!(map.range(3 .. 6).collect::<Vec<_>>(), [
+	/// 	(&3, &'d'),
+	/// 	(&4, &'e'),
+	/// 	(&5, &'f')
+	/// ]);
+	/// assert_eq!(map.range(8 ..).count(), 2);
+	/// let bounds = (Bound::Excluded(3), Bound::Included(5));
+	/// assert_eq!(map.range(bounds).map(|(k, _)| *k).collect::<Vec<_>>(), [
+	/// 	4, 5
+	/// ]);
+	/// ```
+	pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		let (start, end) = (range.start_bound(), range.end_bound());
+		Range(RawRange::new(
+			&self.root,
+			|kv| before_start(&kv.key, start),
+			|kv| after_end(&kv.key, end)
+		))
+	}
+
+	/// Creates an iterator over the entries with keys within `range` in ascending order,
+	/// with mutable references to the values. See [`range`](Self::range) for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map: AATreeMap<u32, u32> = (0 .. 6).map(|k| (k, 0)).collect();
+	/// for (key, value) in map.range_mut(2 ..= 4) {
+	/// 	*value = key * 10;
+	/// }
+	/// assert_eq!(map.values().copied().collect::<Vec<_>>(), [
+	/// 	0, 0, 20, 30, 40, 0
+	/// ]);
+	/// ```
+	pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		RangeMut::new(&mut self.root, range.start_bound(), range.end_bound())
+	}
+
+	/// Calls `f` on all entries with keys within `range` in ascending order, giving it
+	/// mutable access to the values.
+	///
+	/// All values are visited in a single traversal that skips all subtrees outside of
+	/// the range, which is faster than looking up every key individually. Since `f` can
+	/// only modify values, the structure of the tree never changes.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map: AATreeMap<u32, u32> = (0 .. 10).map(|x| (x, 0)).collect();
+	/// map.for_each_value_in_range_mut(3 .. 6, |key, value| *value = key * 10);
+	/// assert_eq!(map.values().copied().collect::<Vec<_>>(), [
+	/// 	0, 0, 0, 30, 40, 50, 0, 0, 0, 0
+	/// ]);
+	/// ```
+	pub fn for_each_value_in_range_mut<Q, R, F>(&mut self, range: R, mut f: F)
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>,
+		F: FnMut(&K, &mut V)
+	{
+		for_each_mut(
+			&mut self.root,
+			range.start_bound(),
+			range.end_bound(),
+			&mut f
+		);
+	}
+
+	/// Creates a new map containing clones of all entries with keys within `range`.
+	///
+	/// The range is walked twice, once to count the entries and once to clone them into
+	/// a perfectly balanced tree that is built in O(m) without any comparisons. This
+	/// takes O(log n + m) time in total and allocates nothing but the new nodes.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, char> = (0 .. 10).zip('a' ..).collect();
+	/// let part = map.clone_range(3 .. 6);
+	/// assert_eq!(part, AATreeMap::from([(3, 'd'), (4, 'e'), (5, 'f')]));
+	/// ```
+	pub fn clone_range<Q, R>(&self, range: R) -> Self
+	where
+		K: Borrow<Q> + Clone,
+		V: Clone,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		let bounds = (range.start_bound(), range.end_bound());
+		let len = self.range(bounds).count();
+		let entries = self
+			.range(bounds)
+			.map(|(key, value)| (key.clone(), value.clone()));
+		Self::from_sorted_unchecked(entries, len)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::{collections::BTreeMap, vec::Vec};
+	use core::ops::{Bound, RangeBounds};
+
+	#[test]
+	fn test_range() {
+		let bounds = |x| [Bound::Included(x), Bound::Excluded(x), Bound::Unbounded];
+		for len in [0, 1, 2, 9, 50] {
+			let map: AATreeMap<u32, u32> = (0 .. len).map(|x| (x * 2, x)).collect();
+			let btree: BTreeMap<u32, u32> = map.iter().map(|(k, v)| (*k, *v)).collect();
+			for start in 0 .. len * 2 + 2 {
+				for end in start .. len * 2 + 2 {
+					for (start, end) in bounds(start).into_iter().flat_map(|start| {
+						bounds(end).into_iter().map(move |end| (start, end))
+					}) {
+						// BTreeMap panics for this empty range
+						if matches!((start, end), (Bound::Excluded(s), Bound::Excluded(e)) if s == e)
+						{
+							continue;
+						}
+						let actual: Vec<_> = map.range((start, end)).collect();
+						let expected: Vec<_> = btree.range((start, end)).collect();
+						assert_eq!(actual, expected, "{start:?} .. {end:?}");
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_range_mut() {
+		let bounds = |x| [Bound::Included(x), Bound::Excluded(x), Bound::Unbounded];
+		for start in 0 .. 12 {
+			for end in start + 1 .. 12 {
+				for (start, end) in bounds(start).into_iter().flat_map(|start| {
+					bounds(end).into_iter().map(move |end| (start, end))
+				}) {
+					let mut map: AATreeMap<u32, u32> =
+						(1 .. 10).map(|x| (x, 0)).collect();
+					let mut btree: BTreeMap<u32, u32> =
+						map.iter().map(|(k, v)| (*k, *v)).collect();
+					for (k, v) in map.range_mut((start, end)) {
+						*v = *k;
+					}
+					for (k, v) in btree.range_mut((start, end)) {
+						*v = *k;
+					}
+					let actual: Vec<_> = map.iter().collect();
+					let expected: Vec<_> = btree.iter().collect();
+					assert_eq!(actual, expected, "{start:?} .. {end:?}");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_range_start_after_end() {
+		let map: AATreeMap<u32, u32> = (0 .. 10).map(|x| (x, x)).collect();
+		assert_eq!(
+			map.range((Bound::Included(5), Bound::Included(3))).count(),
+			0
+		);
+		assert_eq!(
+			map.range((Bound::Excluded(5), Bound::Excluded(5))).count(),
+			0
+		);
+	}
+
+	#[test]
+	fn test_for_each_value_in_range_mut() {
+		let bounds = |x| [Bound::Included(x), Bound::Excluded(x), Bound::Unbounded];
+		for start in 0 .. 12 {
+			for end in 0 .. 12 {
+				for (start, end) in bounds(start).into_iter().flat_map(|start| {
+					bounds(end).into_iter().map(move |end| (start, end))
+				}) {
+					let mut map: AATreeMap<u32, u32> =
+						(1 .. 10).map(|x| (x, 0)).collect();
+					let mut visited = Vec::new();
+					map.for_each_value_in_range_mut((start, end), |k, v| {
+						visited.push(*k);
+						*v += 1;
+					});
+					let expected: Vec<u32> =
+						(1 .. 10).filter(|x| (start, end).contains(x)).collect();
+					assert_eq!(visited, expected);
+					let changed: Vec<u32> = map
+						.iter()
+						.filter(|(_, v)| **v == 1)
+						.map(|(k, _)| *k)
+						.collect();
+					assert_eq!(changed, expected);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_clone_range() {
+		let map: AATreeMap<u32, u32> = (0 .. 200).map(|x| (x, x * 2)).collect();
+		for (start, end) in [(0, 0), (0, 200), (10, 11), (50, 150), (199, 300)] {
+			let part = map.clone_range(start .. end);
+			part.assert_valid();
+			let expected: Vec<_> = map.range(start .. end).collect();
+			assert_eq!(part.iter().collect::<Vec<_>>(), expected);
+			assert_eq!(part.len(), expected.len());
+		}
+	}
+}
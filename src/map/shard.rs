@@ -0,0 +1,229 @@
+//! This file defines how to split a map into shards of roughly equal size, and how to
+//! move data between shards.
+
+use super::AATreeMap;
+use crate::node::should_rebuild;
+use alloc::vec::Vec;
+use core::{
+	borrow::Borrow,
+	fmt::{self, Debug, Display, Formatter},
+	iter, mem,
+	ops::{Bound, RangeBounds}
+};
+
+/// The error returned by [`AATreeMap::import_range`] if the imported keys interleave with
+/// the keys of the destination map.
+#[derive(Debug)]
+pub struct OverlapError<K>(K);
+
+impl<K> OverlapError<K> {
+	/// Returns a reference to the first key of the destination map that lies between
+	/// the smallest and the greatest imported key.
+	pub fn key(&self) -> &K {
+		&self.0
+	}
+
+	/// Returns the first key of the destination map that lies between the smallest and
+	/// the greatest imported key.
+	pub fn into_key(self) -> K {
+		self.0
+	}
+}
+
+impl<K: Debug> Display for OverlapError<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "Imported range overlaps with existing key {:?}", self.0)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K: Debug> std::error::Error for OverlapError<K> {}
+
+/// Returns the index of the first entry of shard `i` when splitting `len` entries into
+/// `shards` shards, that is `floor(i * len / shards)`. This is computed in `u128` so that
+/// the product cannot overflow.
+fn shard_start(i: usize, len: usize, shards: usize) -> usize {
+	(i as u128 * len as u128 / shards as u128) as usize
+}
+
+impl<K: Clone, V> AATreeMap<K, V> {
+	/// Returns up to `n - 1` keys in ascending order that split the map into `n`
+	/// contiguous shards of roughly equal size. The first shard contains all keys smaller
+	/// than the first returned key, the i-th shard contains all keys from the (i-1)-th up
+	/// to (excluding) the i-th returned key, and the last shard contains all remaining keys.
+	/// This can be used to partition work across threads or nodes.
+	///
+	/// The sizes of all shards differ by at most one. If the map has fewer than `n`
+	/// entries, fewer keys are returned so that no shard is empty.
+	///
+	/// Since the tree does not store the sizes of its subtrees, this walks the map from
+	/// the start to the last returned key, which takes O(len) time regardless of `n`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, ()> = (0 .. 10).map(|k| (k, ())).collect();
+	/// assert_eq!(map.shard_bounds(3), [3, 6]);
+	/// assert!(map.shard_bounds(1).is_empty());
+	///
+	/// let small: AATreeMap<u32, ()> = (0 .. 2).map(|k| (k, ())).collect();
+	/// assert_eq!(small.shard_bounds(5), [1]);
+	/// ```
+	pub fn shard_bounds(&self, n: usize) -> Vec<K> {
+		let shards = n.min(self.len);
+		let mut bounds = Vec::with_capacity(shards.saturating_sub(1));
+		let mut next = 1;
+		for (i, key) in self.keys().enumerate() {
+			if next >= shards {
+				break;
+			}
+			if i == shard_start(next, self.len, shards) {
+				bounds.push(key.clone());
+				next += 1;
+			}
+		}
+		bounds
+	}
+
+	/// Copies all entries with keys within `range` from `src` into this map, and returns
+	/// the number of copied entries. This is the typical operation when moving data
+	/// between shards.
+	///
+	/// The copied keys must not interleave with the keys of this map, i.e. this map must
+	/// not contain any key between the smallest and the greatest copied key. Otherwise,
+	/// an [`OverlapError`] is returned and this map is not modified.
+	///
+	/// If many entries are copied compared to the size of this map, both are merged
+	/// into a new balanced tree in O(n + m). Otherwise, the entries are inserted one by
+	/// one in O(m log n).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let src: AATreeMap<u32, char> = (0 .. 10).zip('a' ..).collect();
+	/// let mut dst = AATreeMap::from([(0, 'x'), (9, 'y')]);
+	/// assert_eq!(dst.import_range(&src, 3 .. 6).unwrap(), 3);
+	/// assert_eq!(dst.values().collect::<String>(), "xdefy");
+	///
+	/// let err = dst.import_range(&src, 8 ..).unwrap_err();
+	/// assert_eq!(err.key(), &9);
+	/// ```
+	pub fn import_range<Q, R>(
+		&mut self,
+		src: &Self,
+		range: R
+	) -> Result<usize, OverlapError<K>>
+	where
+		K: Borrow<Q> + Ord,
+		V: Clone,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		let entries: Vec<(K, V)> = src
+			.range(range)
+			.map(|(key, value)| (key.clone(), value.clone()))
+			.collect();
+		let (first, last) = match (entries.first(), entries.last()) {
+			(Some((first, _)), Some((last, _))) => (first, last),
+			_ => return Ok(0)
+		};
+		let bounds = (Bound::Included(first), Bound::Included(last));
+		if let Some((key, _)) = self.range::<K, _>(bounds).next() {
+			return Err(OverlapError(key.clone()));
+		}
+
+		let imported = entries.len();
+		if !should_rebuild(self.len, imported) {
+			self.extend(entries);
+			return Ok(imported);
+		}
+
+		let len = self.len + imported;
+		let mut old = mem::take(self).into_iter().peekable();
+		let mut new = entries.into_iter().peekable();
+		let merged = iter::from_fn(|| match (old.peek(), new.peek()) {
+			(Some((a, _)), Some((b, _))) if a < b => old.next(),
+			(_, Some(_)) => new.next(),
+			_ => old.next()
+		});
+		*self = Self::from_sorted_unchecked(merged, len);
+		Ok(imported)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::shard_start;
+	use crate::AATreeMap;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_shard_start_overflow() {
+		assert_eq!(shard_start(1, 10, 3), 3);
+		assert_eq!(shard_start(2, 10, 3), 6);
+		assert_eq!(shard_start(3, usize::MAX, 4), usize::MAX / 4 * 3 + 2);
+		assert_eq!(
+			shard_start(usize::MAX - 1, usize::MAX, usize::MAX),
+			usize::MAX - 1
+		);
+	}
+
+	#[test]
+	fn test_shard_bounds() {
+		let empty: AATreeMap<u32, ()> = AATreeMap::new();
+		assert!(empty.shard_bounds(4).is_empty());
+		assert!(empty.shard_bounds(0).is_empty());
+
+		for len in [1, 7, 100, 1001] {
+			let map: AATreeMap<u32, ()> = (0 .. len).map(|k| (k * 2, ())).collect();
+			for n in [1, 2, 3, 8, 2000] {
+				let bounds = map.shard_bounds(n);
+				assert_eq!(bounds.len(), (n as u32).min(len) as usize - 1);
+				let mut sizes = bounds
+					.iter()
+					.chain([&u32::MAX])
+					.scan(0, |start, end| {
+						let size =
+							map.keys().filter(|k| **k >= *start && *k < end).count();
+						*start = *end;
+						Some(size)
+					})
+					.collect::<Vec<_>>();
+				sizes.sort_unstable();
+				assert!(sizes[0] > 0);
+				assert!(sizes[sizes.len() - 1] - sizes[0] <= 1, "{sizes:?}");
+			}
+		}
+	}
+
+	#[test]
+	fn test_import_range() {
+		let src: AATreeMap<u32, u32> = (0 .. 1000).map(|k| (k, k)).collect();
+		// small imports are inserted, large ones are merged
+		for (dst_len, range) in [
+			(0, 0 .. 0),
+			(0, 10 .. 500),
+			(500, 600 .. 610),
+			(500, 500 .. 1000)
+		] {
+			let mut dst: AATreeMap<u32, u32> = (0 .. dst_len).map(|k| (k, k)).collect();
+			let imported = dst.import_range(&src, range.clone()).unwrap();
+			assert_eq!(imported, range.len());
+			dst.assert_valid();
+			let expected: Vec<_> = (0 .. dst_len).chain(range).collect();
+			assert_eq!(dst.keys().copied().collect::<Vec<_>>(), expected);
+			assert_eq!(dst.len(), expected.len());
+		}
+
+		let mut dst = AATreeMap::from([(100, 0), (200, 0)]);
+		let err = dst.import_range(&src, 150 ..).unwrap_err();
+		assert_eq!(err.into_key(), 200);
+		assert_eq!(dst.len(), 2);
+		assert_eq!(dst.import_range(&src, 0 .. 100).unwrap(), 100);
+		assert_eq!(dst.import_range(&src, 101 .. 200).unwrap(), 99);
+		dst.assert_valid();
+		assert_eq!(dst.len(), 201);
+	}
+}
@@ -0,0 +1,290 @@
+//! This file defines how to split a map into two maps. All of these operations move the
+//! entries into the new trees, so neither keys nor values need to implement [`Clone`].
+
+use super::{AATreeMap, KeyValue};
+use core::{
+	borrow::Borrow,
+	ops::{Bound, RangeBounds}
+};
+
+impl<K, V> AATreeMap<K, V> {
+	/// Splits the map into two at the given key. Returns everything after the given key,
+	/// including the key.
+	///
+	/// If only a few entries end up in one of the maps, they are removed one by one in
+	/// O(log n) each. Otherwise, both maps are rebuilt from the nodes of this map in O(n),
+	/// without cloning any entry or allocating any node.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut a: AATreeMap<u32, char> = (1 ..= 5).zip('a' ..).collect();
+	/// let b = a.split_off(&3);
+	/// assert_eq!(a.keys().copied().collect::<Vec<_>>(), [1, 2]);
+	/// assert_eq!(b.keys().copied().collect::<Vec<_>>(), [3, 4, 5]);
+	/// ```
+	pub fn split_off<Q>(&mut self, key: &Q) -> Self
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		let (root, len) = self
+			.root
+			.split_off(self.len, |kv: &KeyValue<K, V>| kv.key.borrow() >= key);
+		self.len -= len;
+		Self { root, len }
+	}
+
+	/// Removes all entries with keys within `range` and returns them as a new map.
+	///
+	/// If only a few entries are removed, they are removed one by one in O(log n) each.
+	/// Otherwise, both maps are rebuilt from the nodes of this map in O(n), without
+	/// cloning any entry or allocating any node.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut log: AATreeMap<u64, &str> = AATreeMap::from([
+	/// 	(100, "boot"),
+	/// 	(250, "login"),
+	/// 	(300, "error"),
+	/// 	(400, "logout")
+	/// ]);
+	/// let purged = log.remove_range(200 .. 350);
+	/// assert_eq!(purged.len(), 2);
+	/// assert_eq!(log.values().copied().collect::<Vec<_>>(), [
+	/// 	"boot", "logout"
+	/// ]);
+	/// ```
+	pub fn remove_range<Q, R>(&mut self, range: R) -> Self
+	where
+		K: Borrow<Q> + Ord,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		let start = |kv: &KeyValue<K, V>| match range.start_bound() {
+			Bound::Included(start) => kv.key.borrow() >= start,
+			Bound::Excluded(start) => kv.key.borrow() > start,
+			Bound::Unbounded => true
+		};
+		let end = |kv: &KeyValue<K, V>| match range.end_bound() {
+			Bound::Included(end) => kv.key.borrow() <= end,
+			Bound::Excluded(end) => kv.key.borrow() < end,
+			Bound::Unbounded => true
+		};
+		let (root, len) = self.root.remove_range(self.len, start, end);
+		self.len -= len;
+		Self { root, len }
+	}
+
+	/// Removes the subtree of the underlying tree whose root has the key `key`, and
+	/// returns its entries as a new map. These are all entries with keys between the
+	/// nearest ancestors of that node, so which entries are removed depends on the shape
+	/// of the tree, as shown by [`to_canonical_string`](Self::to_canonical_string).
+	/// Returns an empty map if `key` is not part of this map.
+	///
+	/// The subtree is moved into the new map as it is, without cloning any entry or
+	/// allocating any node. The remaining entries are rebuilt into a balanced tree in
+	/// O(n).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let mut map: AATreeMap<u32, char> = (1 ..= 7).zip('a' ..).collect();
+	/// assert_eq!(
+	/// 	map.to_canonical_string(),
+	/// 	"4: d => [3, (2: b => [2, 1: a, 3: c]), (6: f => [2, 5: e, 7: g])]"
+	/// );
+	/// let subtree = map.take_subtree(&6);
+	/// assert_eq!(subtree.keys().copied().collect::<Vec<_>>(), [5, 6, 7]);
+	/// assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+	/// assert!(map.take_subtree(&6).is_empty());
+	/// ```
+	pub fn take_subtree<Q>(&mut self, key: &Q) -> Self
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let (root, len) = self.root.take_subtree::<Q, K>(key);
+		self.len -= len;
+		Self { root, len }
+	}
+
+	/// Moves all entries into two new maps, the first one containing all entries for
+	/// which `pred` returns `true`, and the second one containing all other entries.
+	/// `pred` is called once for every entry in ascending order of the keys.
+	///
+	/// Both maps are rebuilt from the nodes of this map in O(n), without cloning any
+	/// entry or allocating any node.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map: AATreeMap<u32, char> = (1 ..= 5).zip('a' ..).collect();
+	/// let (even, odd) = map.partition(|key, _| key % 2 == 0);
+	/// assert_eq!(even.values().collect::<String>(), "bd");
+	/// assert_eq!(odd.values().collect::<String>(), "ace");
+	/// ```
+	pub fn partition<F>(self, mut pred: F) -> (Self, Self)
+	where
+		F: FnMut(&K, &V) -> bool
+	{
+		let ((left, left_len), (right, right_len)) =
+			self.root.partition(|kv| pred(&kv.key, &kv.value));
+		(
+			Self {
+				root: left,
+				len: left_len
+			},
+			Self {
+				root: right,
+				len: right_len
+			}
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{AATreeMap, AATreeSet};
+	use alloc::{string::String, vec::Vec};
+
+	/// A key type that owns a buffer and cannot be cloned.
+	#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+	struct Buffer(String);
+
+	fn map(keys: impl Iterator<Item = u32>) -> AATreeMap<Buffer, Vec<u32>> {
+		keys.map(|key| (Buffer(alloc::format!("{key:04}")), Vec::from([key])))
+			.collect()
+	}
+
+	fn keys(map: &AATreeMap<Buffer, Vec<u32>>) -> Vec<u32> {
+		map.values().map(|value| value[0]).collect()
+	}
+
+	#[test]
+	fn test_split_off() {
+		for len in [0, 1, 2, 3, 10, 100] {
+			for at in [0, 1, len / 2, len, len + 1] {
+				let mut a = map(0 .. len);
+				let b = a.split_off(&Buffer(alloc::format!("{at:04}")));
+				a.assert_valid();
+				b.assert_valid();
+				assert_eq!(keys(&a), (0 .. at.min(len)).collect::<Vec<_>>());
+				assert_eq!(keys(&b), (at.min(len) .. len).collect::<Vec<_>>());
+			}
+		}
+
+		let mut set: AATreeSet<Buffer> = (0 .. 10u32)
+			.map(|key| Buffer(alloc::format!("{key:04}")))
+			.collect();
+		let tail = set.split_off(&Buffer(String::from("0007")));
+		assert_eq!(set.len(), 7);
+		assert_eq!(tail.len(), 3);
+	}
+
+	#[test]
+	fn test_partition() {
+		for len in [0, 1, 2, 3, 10, 100] {
+			let (even, odd) = map(0 .. len).partition(|_, value| value[0] % 2 == 0);
+			even.assert_valid();
+			odd.assert_valid();
+			assert_eq!(keys(&even), (0 .. len).step_by(2).collect::<Vec<_>>());
+			assert_eq!(keys(&odd), (1 .. len).step_by(2).collect::<Vec<_>>());
+
+			let set: AATreeSet<Buffer> =
+				map(0 .. len).into_iter().map(|(k, _)| k).collect();
+			let (small, large) = set.partition(|key| key.0.as_str() < "0005");
+			assert_eq!(small.len(), len.min(5) as usize);
+			assert_eq!(large.len(), len.saturating_sub(5) as usize);
+		}
+	}
+
+	#[test]
+	fn test_remove_range() {
+		use core::ops::Bound::{self, Excluded, Included, Unbounded};
+
+		let bounds = |q| [Included(q), Excluded(q), Unbounded];
+		for lo in [0, 3, 10] {
+			for hi in [0, 5, 10, 20] {
+				for (lo, hi) in bounds(lo).into_iter().zip(bounds(hi)) {
+					let range: (Bound<u32>, Bound<u32>) = (lo, hi);
+					let mut a: AATreeMap<u32, u32> = (0 .. 10).map(|k| (k, k)).collect();
+					let removed = a.remove_range(range);
+					a.assert_valid();
+					removed.assert_valid();
+					let expected: Vec<u32> = (0 .. 10)
+						.filter(|k| core::ops::RangeBounds::contains(&range, k))
+						.collect();
+					assert_eq!(a.len() + removed.len(), 10);
+					assert_eq!(removed.into_keys().collect::<Vec<_>>(), expected);
+				}
+			}
+		}
+
+		let mut a = map(0 .. 10);
+		let removed =
+			a.remove_range(Buffer(String::from("0002")) ..= Buffer(String::from("0004")));
+		assert_eq!(keys(&removed), [2, 3, 4]);
+	}
+
+	#[test]
+	fn test_take_subtree() {
+		for len in [0u32, 1, 2, 3, 10, 100] {
+			for at in [0, 1, len / 2, len.saturating_sub(1), len] {
+				let mut a = map(0 .. len);
+				let b = a.take_subtree(&Buffer(alloc::format!("{at:04}")));
+				a.assert_valid();
+				b.assert_valid();
+				assert_eq!(b.is_empty(), at >= len);
+				let mut all: Vec<u32> = keys(&a).into_iter().chain(keys(&b)).collect();
+				all.sort_unstable();
+				assert_eq!(all, (0 .. len).collect::<Vec<_>>());
+				if let (Some(first), Some(last)) = (keys(&b).first(), keys(&b).last()) {
+					assert_eq!(keys(&b), (*first ..= *last).collect::<Vec<_>>());
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_remove_range_strategies() {
+		// with 1000 entries, up to 99 entries are removed one by one
+		for (lo, hi) in [
+			(0, 0),
+			(0, 5),
+			(500, 550),
+			(900, 1000),
+			(100, 900),
+			(0, 1000)
+		] {
+			let mut a: AATreeMap<u32, u32> = (0 .. 1000).map(|k| (k, k)).collect();
+			let removed = a.remove_range(lo .. hi);
+			a.assert_valid();
+			removed.assert_valid();
+			assert_eq!(removed.len(), (hi - lo) as usize);
+			assert!(removed.into_keys().eq(lo .. hi));
+			assert_eq!(a.len(), 1000 - (hi - lo) as usize);
+			assert!(a.into_keys().eq((0 .. lo).chain(hi .. 1000)));
+		}
+	}
+
+	#[test]
+	fn test_split_off_strategies() {
+		// with 1000 entries, up to 99 entries on either side are moved one by one
+		for at in [0, 1, 50, 500, 950, 999, 1000] {
+			let mut a: AATreeMap<u32, u32> = (0 .. 1000).map(|k| (k, k)).collect();
+			let b = a.split_off(&at);
+			a.assert_valid();
+			b.assert_valid();
+			assert_eq!(a.len(), at as usize);
+			assert_eq!(b.len(), 1000 - at as usize);
+			assert!(a.into_keys().eq(0 .. at));
+			assert!(b.into_keys().eq(at .. 1000));
+		}
+	}
+}
@@ -0,0 +1,159 @@
+//! This file defines conversions between maps and sorted streams of entries, e.g. for
+//! external sorting and merging.
+
+use super::{AATreeMap, KeyValue};
+use crate::{iter::AAIntoIter, sorted::SortedIterator};
+use alloc::vec::Vec;
+use core::{
+	fmt::{self, Debug, Display, Formatter},
+	iter::FusedIterator
+};
+
+/// The error returned by [`AATreeMap::from_sorted_stream`] if the keys are not in
+/// strictly ascending order.
+#[derive(Debug)]
+pub struct NotSortedError<K> {
+	key: K,
+	position: usize
+}
+
+impl<K> NotSortedError<K> {
+	/// Returns a reference to the first key that was not greater than its predecessor.
+	pub fn key(&self) -> &K {
+		&self.key
+	}
+
+	/// Returns the first key that was not greater than its predecessor.
+	pub fn into_key(self) -> K {
+		self.key
+	}
+
+	/// Returns the position of the key in the stream.
+	pub fn position(&self) -> usize {
+		self.position
+	}
+}
+
+impl<K: Debug> Display for NotSortedError<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"Key {:?} at position {} is not greater than its predecessor",
+			self.key, self.position
+		)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K: Debug> std::error::Error for NotSortedError<K> {}
+
+/// An owning iterator over the entries of an [`AATreeMap`] in ascending key order,
+/// returned by [`AATreeMap::into_sorted_stream`].
+pub struct SortedStream<K, V>(AAIntoIter<KeyValue<K, V>, (K, V)>);
+
+impl<K, V> Debug for SortedStream<K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SortedStream")
+			.field("len", &self.0.len())
+			.finish_non_exhaustive()
+	}
+}
+
+impl<K, V> Iterator for SortedStream<K, V> {
+	type Item = (K, V);
+
+	fn next(&mut self) -> Option<(K, V)> {
+		self.0.next()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.0.size_hint()
+	}
+}
+
+impl<K, V> ExactSizeIterator for SortedStream<K, V> {}
+
+impl<K, V> FusedIterator for SortedStream<K, V> {}
+
+impl<K, V> SortedIterator for SortedStream<K, V> {}
+
+impl<K, V> AATreeMap<K, V> {
+	/// Turn this map into a stream of its entries in strictly ascending key order, e.g.
+	/// to spill it to disk or merge it with other sorted runs. Use
+	/// [`from_sorted_stream`](Self::from_sorted_stream) to turn such a stream back into a
+	/// map.
+	pub fn into_sorted_stream(self) -> SortedStream<K, V> {
+		SortedStream(self.into_iter())
+	}
+}
+
+impl<K: Ord, V> AATreeMap<K, V> {
+	/// Build a map from a stream of entries in strictly ascending key order, like the
+	/// result of merging sorted runs. The order is verified while the entries are
+	/// collected, and the tree is then built in O(n) without inserting the entries one
+	/// by one.
+	///
+	/// Returns an error if a key is not greater than the key before it, which includes
+	/// duplicate keys.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeMap;
+	/// let map = AATreeMap::from([(1, 'a'), (3, 'c')]);
+	/// let other = AATreeMap::from([(2, 'b'), (4, 'd')]);
+	///
+	/// // merge two sorted streams
+	/// let mut a = map.into_sorted_stream().peekable();
+	/// let mut b = other.into_sorted_stream().peekable();
+	/// let merged = std::iter::from_fn(|| match (a.peek(), b.peek()) {
+	/// 	(Some(x), Some(y)) if y.0 < x.0 => b.next(),
+	/// 	(Some(_), _) => a.next(),
+	/// 	(None, _) => b.next()
+	/// });
+	/// let map = AATreeMap::from_sorted_stream(merged).unwrap();
+	/// assert_eq!(map.values().collect::<String>(), "abcd");
+	///
+	/// let err = AATreeMap::from_sorted_stream([(1, 'a'), (1, 'b')]).unwrap_err();
+	/// assert_eq!((*err.key(), err.position()), (1, 1));
+	/// ```
+	pub fn from_sorted_stream<I>(iter: I) -> Result<Self, NotSortedError<K>>
+	where
+		I: IntoIterator<Item = (K, V)>
+	{
+		let iter = iter.into_iter();
+		let mut entries: Vec<(K, V)> = Vec::with_capacity(iter.size_hint().0);
+		for (key, value) in iter {
+			if let Some((last, _)) = entries.last() {
+				if key <= *last {
+					let position = entries.len();
+					return Err(NotSortedError { key, position });
+				}
+			}
+			entries.push((key, value));
+		}
+		let len = entries.len();
+		Ok(Self::from_sorted_unchecked(entries, len))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+
+	#[test]
+	fn test_sorted_stream_roundtrip() {
+		for len in [0, 1, 2, 100, 1000] {
+			let map: AATreeMap<u32, u32> = (0 .. len).map(|k| (k * 3, k)).collect();
+			let stream = map.clone().into_sorted_stream();
+			assert_eq!(stream.len(), len as usize);
+			let rebuilt = AATreeMap::from_sorted_stream(stream).unwrap();
+			rebuilt.assert_valid();
+			assert_eq!(rebuilt, map);
+		}
+
+		let err = AATreeMap::from_sorted_stream([(1, ()), (3, ()), (2, ())]).unwrap_err();
+		assert_eq!(*err.key(), 2);
+		assert_eq!(err.position(), 2);
+	}
+}
@@ -0,0 +1,224 @@
+//! A map that keeps track of its smallest and greatest values.
+
+use crate::{AATreeMap, AATreeSet};
+use core::{
+	borrow::Borrow,
+	fmt::{self, Debug},
+	iter::FromIterator
+};
+
+/// An [`AATreeMap`] that can return the entries with the smallest and greatest value
+/// without scanning all entries.
+///
+/// Internally, this type keeps a second tree that contains all `(value, key)` pairs. Every
+/// update modifies both trees in O(log n), and [`min_value`](Self::min_value) and
+/// [`max_value`](Self::max_value) only walk down the outermost path of the second tree
+/// without comparing any values. Since keys and values are stored in both trees, both
+/// need to implement [`Clone`].
+///
+/// Values can only be modified through [`update`](Self::update), which keeps both trees
+/// in sync.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::MinMaxMap;
+/// let mut prices = MinMaxMap::new();
+/// prices.insert("apple", 3);
+/// prices.insert("banana", 1);
+/// prices.insert("cherry", 7);
+/// assert_eq!(prices.min_value(), Some((&"banana", &1)));
+/// assert_eq!(prices.max_value(), Some((&"cherry", &7)));
+///
+/// prices.update("banana", |price| *price = 10);
+/// assert_eq!(prices.min_value(), Some((&"apple", &3)));
+/// assert_eq!(prices.max_value(), Some((&"banana", &10)));
+/// ```
+#[derive(Clone)]
+pub struct MinMaxMap<K, V> {
+	map: AATreeMap<K, V>,
+	by_value: AATreeSet<(V, K)>
+}
+
+impl<K, V> Default for MinMaxMap<K, V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K: Debug, V: Debug> Debug for MinMaxMap<K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		Debug::fmt(&self.map, f)
+	}
+}
+
+impl<K, V> MinMaxMap<K, V> {
+	/// Construct a new, empty map.
+	pub const fn new() -> Self {
+		Self {
+			map: AATreeMap::new(),
+			by_value: AATreeSet::new()
+		}
+	}
+
+	/// Returns the number of entries in the map.
+	pub fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	/// Returns `true` if the map contains no entries.
+	pub fn is_empty(&self) -> bool {
+		self.map.is_empty()
+	}
+
+	/// Removes all entries from the map.
+	pub fn clear(&mut self) {
+		self.map.clear();
+		self.by_value.clear();
+	}
+
+	/// Creates an iterator that visits all entries with the keys in ascending order.
+	pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+		self.map.iter()
+	}
+
+	/// Creates an iterator that visits all entries in ascending order of their values.
+	/// Entries with equal values are visited in ascending order of their keys.
+	pub fn iter_by_value(&self) -> impl Iterator<Item = (&K, &V)> {
+		self.by_value.iter().map(|(value, key)| (key, value))
+	}
+
+	/// Returns the underlying map, dropping the tree ordered by values.
+	pub fn into_map(self) -> AATreeMap<K, V> {
+		self.map
+	}
+}
+
+impl<K: Ord + Clone, V: Ord + Clone> MinMaxMap<K, V> {
+	/// Returns a reference to the value corresponding to the key.
+	pub fn get<Q>(&self, key: &Q) -> Option<&V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.map.get(key)
+	}
+
+	/// Returns `true` if the map contains a value for the specified key.
+	pub fn contains_key<Q>(&self, key: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.map.contains_key(key)
+	}
+
+	/// Returns the entry with the smallest value. If multiple entries share the smallest
+	/// value, the one with the smallest key is returned.
+	pub fn min_value(&self) -> Option<(&K, &V)> {
+		self.by_value.first().map(|(value, key)| (key, value))
+	}
+
+	/// Returns the entry with the greatest value. If multiple entries share the greatest
+	/// value, the one with the greatest key is returned.
+	pub fn max_value(&self) -> Option<(&K, &V)> {
+		self.by_value.last().map(|(value, key)| (key, value))
+	}
+
+	/// Insert a new element into the map, or overwrite an existing element with the same
+	/// key. If a value was overwritten, the old value is returned.
+	pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+		let old = self.map.insert(key.clone(), value.clone());
+		let key = match old {
+			Some(old) => {
+				let entry = (old, key);
+				self.by_value.remove(&entry);
+				let (old, key) = entry;
+				self.by_value.insert((value, key));
+				return Some(old);
+			},
+			None => key
+		};
+		self.by_value.insert((value, key));
+		None
+	}
+
+	/// Remove a key from the map if it exists, and return the value that was previously
+	/// stored for the key.
+	pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let (key, value) = self.map.remove_entry(key)?;
+		let entry = (value, key);
+		self.by_value.remove(&entry);
+		Some(entry.0)
+	}
+
+	/// Modify the value corresponding to the key using `f`, and return the result of
+	/// `f`. Returns `None` if the key is not part of the map.
+	pub fn update<Q, F, R>(&mut self, key: &Q, f: F) -> Option<R>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized,
+		F: FnOnce(&mut V) -> R
+	{
+		let (key, value) = self.map.remove_entry(key)?;
+		let mut entry = (value, key);
+		self.by_value.remove(&entry);
+		let result = f(&mut entry.0);
+		let (value, key) = entry;
+		self.insert(key, value);
+		Some(result)
+	}
+}
+
+impl<K: Ord + Clone, V: Ord + Clone> Extend<(K, V)> for MinMaxMap<K, V> {
+	fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+		for (key, value) in iter {
+			self.insert(key, value);
+		}
+	}
+}
+
+impl<K: Ord + Clone, V: Ord + Clone> FromIterator<(K, V)> for MinMaxMap<K, V> {
+	fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+		let mut map = Self::new();
+		map.extend(iter);
+		map
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::MinMaxMap;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_min_max() {
+		let mut map: MinMaxMap<u32, i32> =
+			(0 .. 10).map(|k| (k, (k as i32 - 5).pow(2))).collect();
+		assert_eq!(map.min_value(), Some((&5, &0)));
+		// 0 and 10 would tie, but 10 is not part of the map
+		assert_eq!(map.max_value(), Some((&0, &25)));
+
+		assert_eq!(map.insert(9, 25), Some(16));
+		assert_eq!(map.max_value(), Some((&9, &25)));
+		assert_eq!(map.remove(&5), Some(0));
+		assert_eq!(map.remove(&5), None);
+		assert_eq!(map.min_value(), Some((&4, &1)));
+		assert_eq!(map.update(&4, |v| *v = 100), Some(()));
+		assert_eq!(map.update(&5, |v| *v = 100), None);
+		assert_eq!(map.max_value(), Some((&4, &100)));
+		assert_eq!(map.min_value(), Some((&6, &1)));
+
+		let by_value: Vec<_> = map.iter_by_value().map(|(k, _)| *k).collect();
+		assert_eq!(by_value, [6, 3, 7, 2, 8, 1, 0, 9, 4]);
+		assert_eq!(map.len(), 9);
+
+		map.clear();
+		assert_eq!(map.min_value(), None);
+		assert_eq!(map.max_value(), None);
+	}
+}
@@ -0,0 +1,348 @@
+use super::{AANode, Node};
+use alloc::{boxed::Box, vec::Vec};
+use core::{borrow::Borrow, cmp::Ordering, mem};
+
+/// Returns `true` if applying `ops` modifications to a tree with `len` values is cheaper
+/// by rebuilding the whole tree in O(n) than by applying the modifications one by one
+/// in O(log n) each.
+pub(crate) fn should_rebuild(len: usize, ops: usize) -> bool {
+	let height = (usize::BITS - len.leading_zeros()) as usize;
+	ops.saturating_mul(height) >= len
+}
+
+/// Returns the smallest number of modifications for which [`should_rebuild`] returns
+/// `true`.
+fn rebuild_threshold(len: usize) -> usize {
+	let height = (usize::BITS - len.leading_zeros()) as usize;
+	(len + height)
+		.saturating_sub(1)
+		.checked_div(height)
+		.unwrap_or(0)
+}
+
+impl<T> AANode<T> {
+	/// Build a perfectly balanced tree from the first `len` items of `iter` in O(n).
+	///
+	/// **The items must be strictly ascending**, otherwise the resulting tree is not sorted.
+	///
+	/// # Panics
+	///
+	/// Panics if `iter` yields less than `len` items.
+	///
+	/// Every subtree contains `n` nodes and has level `floor(log2(n + 1))`, with the left
+	/// subtree containing `floor((n - 1) / 2)` and the right subtree `ceil((n - 1) / 2)`
+	/// nodes. This is always a valid AA tree: The right child either has one level less
+	/// than its parent, or it has the same level and contains exactly `2^level - 1` nodes,
+	/// in which case its own children are again one level below.
+	pub(crate) fn from_sorted_iter<I>(iter: &mut I, len: usize) -> Self
+	where
+		I: Iterator<Item = T>
+	{
+		let mut nodes =
+			iter.map(|content| Box::new(Node::new(0, content, Self::new(), Self::new())));
+		Self::from_sorted_nodes(&mut nodes, len)
+	}
+
+	/// Build a perfectly balanced tree from the first `len` nodes of `iter` like
+	/// [`from_sorted_iter`](Self::from_sorted_iter), but reuse the allocations of the
+	/// nodes. The levels of the nodes are overwritten, and **their children must be
+	/// `Nil`**.
+	pub(crate) fn from_sorted_nodes<I>(iter: &mut I, len: usize) -> Self
+	where
+		I: Iterator<Item = Box<Node<T>>>
+	{
+		if len == 0 {
+			return Self::new();
+		}
+		let left_len = (len - 1) / 2;
+		let left_child = Self::from_sorted_nodes(iter, left_len);
+		let mut node = iter
+			.next()
+			.expect("iterator yielded less items than expected");
+		let right_child = Self::from_sorted_nodes(iter, len - 1 - left_len);
+		node.level = (usize::BITS - 1 - (len + 1).leading_zeros()) as u8;
+		node.left_child = left_child;
+		node.right_child = right_child;
+		Self(Some(node))
+	}
+}
+
+impl<T: Ord> AANode<T> {
+	/// Build a perfectly balanced tree from all items of `iter` in O(n) without knowing
+	/// their number in advance, and return the tree together with its length.
+	///
+	/// **The items must be strictly ascending**, which is only checked in debug builds.
+	pub(crate) fn from_sorted_items<I>(iter: I) -> (Self, usize)
+	where
+		I: Iterator<Item = T>
+	{
+		let nodes: Vec<_> = iter
+			.map(|content| Box::new(Node::new(0, content, Self::new(), Self::new())))
+			.collect();
+		debug_assert!(
+			nodes.windows(2).all(|w| w[0].content < w[1].content),
+			"items are not strictly ascending"
+		);
+		let len = nodes.len();
+		(Self::from_sorted_nodes(&mut nodes.into_iter(), len), len)
+	}
+}
+
+impl<T> AANode<T> {
+	/// Move the contents of this tree into two new balanced trees in O(n), depending on
+	/// whether `pred` returns `true` for them, and return both trees together with their
+	/// lengths. All nodes are moved, no content is cloned and no node is reallocated.
+	pub(crate) fn partition<F>(self, mut pred: F) -> ((Self, usize), (Self, usize))
+	where
+		F: FnMut(&mut T) -> bool
+	{
+		let (mut left, mut right) = (Vec::new(), Vec::new());
+		for mut node in IntoNodes::new(self) {
+			if pred(&mut node.content) {
+				left.push(node);
+			} else {
+				right.push(node);
+			}
+		}
+		let (left_len, right_len) = (left.len(), right.len());
+		(
+			(
+				Self::from_sorted_nodes(&mut left.into_iter(), left_len),
+				left_len
+			),
+			(
+				Self::from_sorted_nodes(&mut right.into_iter(), right_len),
+				right_len
+			)
+		)
+	}
+}
+
+impl<T> AANode<T> {
+	/// Count the values for which both `start` and `end` return `true`, but stop counting
+	/// once `limit` values have been found. `start` must return `false` for all values
+	/// below some point and `true` above it, and `end` must return `true` for all values
+	/// below some point and `false` above it. This takes O(log n + limit).
+	fn count_range<F, G>(&self, start: &mut F, end: &mut G, limit: usize) -> usize
+	where
+		F: FnMut(&T) -> bool,
+		G: FnMut(&T) -> bool
+	{
+		let node = match self.as_ref() {
+			Some(node) if limit > 0 => node,
+			_ => return 0
+		};
+		if !start(&node.content) {
+			return node.right_child.count_range(start, end, limit);
+		}
+		let mut count = node.left_child.count_range(start, end, limit);
+		if count < limit && end(&node.content) {
+			count += 1;
+			count += node.right_child.count_range(start, end, limit - count);
+		}
+		count
+	}
+
+	/// Remove the `count` smallest values for which `pred` returns `true` one by one in
+	/// O(log n) each, and return them as a new balanced tree. `pred` must behave like
+	/// for [`remove_first`](Self::remove_first).
+	fn remove_first_n<F>(&mut self, count: usize, pred: &mut F) -> Self
+	where
+		F: FnMut(&T) -> bool
+	{
+		let mut removed = (0 .. count).map(|_| {
+			self.remove_first(pred)
+				.expect("tree has less values than expected")
+		});
+		Self::from_sorted_iter(&mut removed, count)
+	}
+
+	/// Remove all values for which `pred` returns `true` from this tree containing `len`
+	/// values, and return them as a new tree together with their number. `pred` must
+	/// return `false` for all values below some point and `true` above it.
+	///
+	/// If only a few values are moved into the new tree, or only a few values remain in
+	/// this tree, those values are removed one by one. Otherwise, both trees are rebuilt
+	/// from the nodes of this tree in O(n) like [`partition`](Self::partition).
+	pub(crate) fn split_off<F>(&mut self, len: usize, mut pred: F) -> (Self, usize)
+	where
+		F: FnMut(&T) -> bool
+	{
+		let limit = rebuild_threshold(len);
+		let tail = self.count_range(&mut pred, &mut |_| true, limit);
+		if !should_rebuild(len, tail) {
+			return (self.remove_first_n(tail, &mut pred), tail);
+		}
+		let head_len = self.count_range(&mut |_| true, &mut |value| !pred(value), limit);
+		if !should_rebuild(len, head_len) {
+			let head = self.remove_first_n(head_len, &mut |_| true);
+			return (mem::replace(self, head), len - head_len);
+		}
+
+		let ((tail, tail_len), (head, _)) =
+			mem::take(self).partition(|value| pred(value));
+		*self = head;
+		(tail, tail_len)
+	}
+
+	/// Remove all values for which both `start` and `end` return `true` from this tree
+	/// containing `len` values, and return them as a new tree together with their number.
+	/// `start` and `end` must behave like for [`count_range`](Self::count_range).
+	///
+	/// If only a few values are removed, they are removed one by one. Otherwise, both
+	/// trees are rebuilt from the nodes of this tree in O(n) like
+	/// [`partition`](Self::partition).
+	pub(crate) fn remove_range<F, G>(
+		&mut self,
+		len: usize,
+		mut start: F,
+		mut end: G
+	) -> (Self, usize)
+	where
+		F: FnMut(&T) -> bool,
+		G: FnMut(&T) -> bool
+	{
+		let count = self.count_range(&mut start, &mut end, rebuild_threshold(len));
+		if !should_rebuild(len, count) {
+			return (self.remove_first_n(count, &mut start), count);
+		}
+
+		let ((removed, removed_len), (kept, _)) =
+			mem::take(self).partition(|value| start(value) && end(value));
+		*self = kept;
+		(removed, removed_len)
+	}
+
+	/// Detach the subtree whose root contains the value equal to `value` from this tree,
+	/// and return it together with its number of values. Returns an empty tree if there
+	/// is no such value.
+	///
+	/// The detached subtree is a valid AA tree on its own and is returned unchanged. The
+	/// remaining tree is rebuilt from its nodes in O(n) like [`retain`](Self::retain),
+	/// since removing a whole subtree cannot be repaired by rotations along one path.
+	pub(crate) fn take_subtree<Q, R>(&mut self, value: &Q) -> (Self, usize)
+	where
+		T: Borrow<R>,
+		R: Borrow<Q> + ?Sized,
+		Q: Ord + ?Sized
+	{
+		let mut node = &mut *self;
+		loop {
+			let ordering = match node.as_ref() {
+				None => return (Self::new(), 0),
+				Some(n) => T::borrow(&n.content).borrow().cmp(value)
+			};
+			let n = match ordering {
+				Ordering::Equal => break,
+				_ => node.as_mut().unwrap()
+			};
+			node = match ordering {
+				Ordering::Less => &mut n.right_child,
+				_ => &mut n.left_child
+			};
+		}
+		let subtree = node.take();
+		let len = subtree.count();
+		self.retain(|_| true);
+		(subtree, len)
+	}
+}
+
+/// An iterator that takes a tree apart into its nodes in ascending order. The children of
+/// the returned nodes are always `Nil`, so they can be passed to
+/// [`AANode::from_sorted_nodes`] again.
+pub(crate) struct IntoNodes<T> {
+	/// The nodes that are returned next, with the next one on top. Their left children
+	/// have already been detached.
+	stack: Vec<Box<Node<T>>>
+}
+
+impl<T> IntoNodes<T> {
+	pub(crate) fn new(root: AANode<T>) -> Self {
+		let mut this = Self {
+			stack: Vec::with_capacity(root.level() as usize * 2)
+		};
+		this.push_left_spine(root);
+		this
+	}
+
+	fn push_left_spine(&mut self, mut node: AANode<T>) {
+		while let Some(mut n) = node.0.take() {
+			node = n.left_child.take();
+			self.stack.push(n);
+		}
+	}
+}
+
+impl<T> Iterator for IntoNodes<T> {
+	type Item = Box<Node<T>>;
+
+	fn next(&mut self) -> Option<Box<Node<T>>> {
+		let mut node = self.stack.pop()?;
+		self.push_left_spine(node.right_child.take());
+		Some(node)
+	}
+}
+
+#[cfg(test)]
+impl<T: Ord> AANode<T> {
+	/// Assert that this tree is a valid AA tree with strictly ascending contents.
+	#[track_caller]
+	pub(crate) fn assert_valid(&self) {
+		fn check<T: Ord>(node: &AANode<T>, min: Option<&T>, max: Option<&T>) {
+			let node = match node.as_ref() {
+				Some(node) => node,
+				None => return
+			};
+			let level = node.level;
+			if let Some(min) = min {
+				assert!(&node.content > min, "contents are not ordered");
+			}
+			if let Some(max) = max {
+				assert!(&node.content < max, "contents are not ordered");
+			}
+			if node.left_child.is_nil() && node.right_child.is_nil() {
+				assert_eq!(level, 1, "leaf nodes must have level 1");
+			}
+			assert_eq!(
+				node.left_child.level() + 1,
+				level,
+				"left children must have one level less than their parent"
+			);
+			let right_level = node.right_child.level();
+			assert!(
+				right_level == level || right_level + 1 == level,
+				"right children must have the same level or one less than their parent"
+			);
+			if let Some(right) = node.right_child.as_ref() {
+				assert!(
+					right.right_child.level() < level,
+					"right grandchildren must have a lower level than their grandparent"
+				);
+			}
+			check(&node.left_child, min, Some(&node.content));
+			check(&node.right_child, Some(&node.content), max);
+		}
+		check(self, None, None);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AANode;
+
+	#[test]
+	fn test_from_sorted_iter() {
+		for len in 0 .. 300 {
+			let root = AANode::from_sorted_iter(&mut (0 .. len), len);
+			root.assert_valid();
+			assert_eq!(root.count(), len);
+		}
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_from_sorted_iter_short() {
+		AANode::from_sorted_iter(&mut (0 .. 5), 10);
+	}
+}
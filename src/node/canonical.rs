@@ -0,0 +1,305 @@
+//! Canonical single-line text encoding of the tree structure, intended for golden tests.
+
+use super::{AANode, Node};
+use alloc::{string::String, vec};
+use core::{
+	fmt::{self, Display, Formatter, Write as _},
+	str::FromStr
+};
+
+/// The error returned when parsing a canonical tree encoding fails. `E` is the error
+/// returned when parsing the content of a node fails.
+#[derive(Debug)]
+pub struct ParseCanonicalError<E> {
+	reason: &'static str,
+	content: Option<E>
+}
+
+impl<E> ParseCanonicalError<E> {
+	fn new(reason: &'static str) -> Self {
+		Self {
+			reason,
+			content: None
+		}
+	}
+
+	/// Returns the error returned when parsing the content of a node, if that is why
+	/// parsing the tree failed.
+	pub fn content_error(&self) -> Option<&E> {
+		self.content.as_ref()
+	}
+}
+
+impl<E: Display> Display for ParseCanonicalError<E> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "Invalid canonical tree encoding: {}", self.reason)?;
+		if let Some(err) = &self.content {
+			write!(f, ": {err}")?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for ParseCanonicalError<E> {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		self.content
+			.as_ref()
+			.map(|err| err as &(dyn std::error::Error + 'static))
+	}
+}
+
+impl<T: Display> AANode<T> {
+	/// Encode the structure of this tree, including the levels of all nodes, as a
+	/// deterministic single-line string.
+	///
+	/// The encoding mirrors the syntax of the [`aanode!`](crate::aanode) macro: an
+	/// empty tree is written as `Nil`, a leaf node at level 1 is written as its content,
+	/// and every other node is written as `content => [level, left, right]`, where
+	/// children that are not leaves are wrapped in parentheses.
+	///
+	/// Node contents are written using their [`Display`] implementation. The encoding
+	/// can only be parsed again if the displayed content does not contain any of `(`,
+	/// `)`, `[`, `]`, `,` or `=>`, is not `Nil`, and has no leading or trailing
+	/// whitespace.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::node::AANode;
+	/// let mut root = AANode::new();
+	/// for content in ['A', 'B', 'C', 'D', 'E', 'F', 'G'] {
+	/// 	root.insert(content);
+	/// }
+	/// assert_eq!(
+	/// 	root.to_canonical_string(),
+	/// 	"D => [3, (B => [2, A, C]), (F => [2, E, G])]"
+	/// );
+	/// ```
+	pub fn to_canonical_string(&self) -> String {
+		let mut buf = String::new();
+		// writing to a String cannot fail
+		let _ = self.write_canonical(&mut buf, false);
+		buf
+	}
+
+	fn write_canonical(&self, buf: &mut String, nested: bool) -> fmt::Result {
+		match self.as_ref() {
+			None => buf.write_str("Nil"),
+			Some(Node {
+				level: 1,
+				content,
+				left_child,
+				right_child,
+				..
+			}) if left_child.is_nil() && right_child.is_nil() => write!(buf, "{content}"),
+			Some(Node {
+				level,
+				content,
+				left_child,
+				right_child,
+				..
+			}) => {
+				if nested {
+					buf.write_char('(')?;
+				}
+				write!(buf, "{content} => [{level}, ")?;
+				left_child.write_canonical(buf, true)?;
+				buf.write_str(", ")?;
+				right_child.write_canonical(buf, true)?;
+				buf.write_char(']')?;
+				if nested {
+					buf.write_char(')')?;
+				}
+				Ok(())
+			}
+		}
+	}
+}
+
+impl<T: FromStr> AANode<T> {
+	/// Parse a tree from its canonical encoding, as produced by
+	/// [`to_canonical_string`](Self::to_canonical_string).
+	///
+	/// The tree is not checked for being a valid AA tree. Also, the encoding of nested
+	/// nodes may omit the parentheses when parsing.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::node::AANode;
+	/// let root = AANode::<u32>::from_canonical_str("2 => [2, 1, 3]").unwrap();
+	/// assert_eq!(root.to_canonical_string(), "2 => [2, 1, 3]");
+	/// ```
+	pub fn from_canonical_str(s: &str) -> Result<Self, ParseCanonicalError<T::Err>> {
+		let s = s.trim();
+		if s == "Nil" {
+			return Ok(Self::new());
+		}
+		if let Some(inner) = s.strip_prefix('(') {
+			let inner = inner
+				.strip_suffix(')')
+				.ok_or(ParseCanonicalError::new("unbalanced parentheses"))?;
+			return Self::from_canonical_str(inner);
+		}
+
+		let (content, children) = match s.split_once("=>") {
+			None => return Ok(parse_content::<T>(s)?.into()),
+			Some((content, children)) => (parse_content(content)?, children.trim())
+		};
+		let children = children
+			.strip_prefix('[')
+			.and_then(|children| children.strip_suffix(']'))
+			.ok_or(ParseCanonicalError::new("expected `[level, left, right]`"))?;
+		let mut parts = split_top_level(children)?;
+		let level = parts
+			.next()
+			.and_then(|level| level.trim().parse().ok())
+			.ok_or(ParseCanonicalError::new("invalid level"))?;
+		let left = Self::from_canonical_str(
+			parts
+				.next()
+				.ok_or(ParseCanonicalError::new("missing left child"))?
+		)?;
+		let right = Self::from_canonical_str(
+			parts
+				.next()
+				.ok_or(ParseCanonicalError::new("missing right child"))?
+		)?;
+		if parts.next().is_some() {
+			return Err(ParseCanonicalError::new("too many children"));
+		}
+		Ok(Node::new(level, content, left, right).into())
+	}
+}
+
+impl<T> AANode<T> {
+	/// Count the number of nodes in this tree.
+	pub(crate) fn count(&self) -> usize {
+		let mut count = 0;
+		let mut work = vec![self];
+		while let Some(node) = work.pop() {
+			if let Some(node) = node.as_ref() {
+				count += 1;
+				work.push(&node.left_child);
+				work.push(&node.right_child);
+			}
+		}
+		count
+	}
+}
+
+fn parse_content<T: FromStr>(s: &str) -> Result<T, ParseCanonicalError<T::Err>> {
+	s.trim().parse().map_err(|err| ParseCanonicalError {
+		reason: "invalid node content",
+		content: Some(err)
+	})
+}
+
+/// Split `s` at all commas that are not nested inside parentheses or brackets.
+fn split_top_level<E>(
+	s: &str
+) -> Result<impl Iterator<Item = &str>, ParseCanonicalError<E>> {
+	let mut depth = 0usize;
+	let mut start = 0;
+	let mut parts = [""; 4];
+	let mut count = 0;
+	for (i, ch) in s.char_indices() {
+		match ch {
+			'(' | '[' => depth += 1,
+			')' | ']' => {
+				depth = depth
+					.checked_sub(1)
+					.ok_or(ParseCanonicalError::new("unbalanced parentheses"))?
+			},
+			',' if depth == 0 => {
+				*parts
+					.get_mut(count)
+					.ok_or(ParseCanonicalError::new("too many children"))? = &s[start .. i];
+				count += 1;
+				start = i + 1;
+			},
+			_ => {}
+		}
+	}
+	if depth != 0 {
+		return Err(ParseCanonicalError::new("unbalanced parentheses"));
+	}
+	*parts
+		.get_mut(count)
+		.ok_or(ParseCanonicalError::new("too many children"))? = &s[start ..];
+	Ok(parts.into_iter().take(count + 1))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AANode;
+	use crate::{map::ParseKeyValueError, AATreeMap};
+	use alloc::string::ToString;
+
+	#[track_caller]
+	fn roundtrip(encoding: &str) {
+		let root = AANode::<u32>::from_canonical_str(encoding).unwrap();
+		assert_eq!(root.to_canonical_string(), encoding);
+	}
+
+	#[test]
+	fn test_roundtrip() {
+		roundtrip("Nil");
+		roundtrip("1");
+		roundtrip("1 => [1, Nil, 2]");
+		roundtrip("30 => [3, (15 => [2, 5, 20]), (70 => [3, (50 => [2, 35, (60 => [2, 55, 65])]), (85 => [2, 80, 90])])]");
+	}
+
+	#[test]
+	fn test_insert_matches_encoding() {
+		let mut root = AANode::new();
+		for content in [5, 4, 3, 2, 1] {
+			root.insert(content);
+		}
+		assert_eq!(root.to_canonical_string(), "2 => [2, 1, (4 => [2, 3, 5])]");
+	}
+
+	#[test]
+	fn test_invalid() {
+		for encoding in [
+			"",
+			"(1",
+			"1 => 2",
+			"1 => [x, Nil, Nil]",
+			"1 => [1, Nil]",
+			"1 => [1, Nil, Nil, Nil]",
+			"1 => [1, (Nil, Nil]",
+			"a"
+		] {
+			assert!(
+				AANode::<u32>::from_canonical_str(encoding).is_err(),
+				"{encoding:?} should be invalid"
+			);
+		}
+	}
+	#[test]
+	fn test_invalid_content() {
+		let err = AANode::<u32>::from_canonical_str("2 => [2, 1, x]").unwrap_err();
+		assert!(err.content_error().is_some());
+		assert_eq!(
+			err.to_string(),
+			"Invalid canonical tree encoding: invalid node content: invalid digit found in string"
+		);
+
+		let err = AATreeMap::<u32, char>::from_canonical_str("2: b => [2, 1: a, 3: cc]")
+			.unwrap_err();
+		assert!(matches!(
+			err.content_error(),
+			Some(ParseKeyValueError::Value(_))
+		));
+		assert_eq!(
+			err.to_string(),
+			"Invalid canonical tree encoding: invalid node content: Invalid value: too many characters in string"
+		);
+		let err = AATreeMap::<u32, char>::from_canonical_str("x: a").unwrap_err();
+		assert!(err
+			.to_string()
+			.ends_with("Invalid key: invalid digit found in string"));
+	}
+}
@@ -0,0 +1,140 @@
+//! Debugging helpers for inspecting the tree structure. Only available with the
+//! `debug-tools` feature.
+
+use super::{AANode, Node};
+use alloc::{format, string::String, vec::Vec};
+use core::{
+	fmt::{self, Debug, Display, Formatter, Write as _},
+	sync::atomic::{AtomicUsize, Ordering}
+};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A process-wide unique identifier of a node. Nodes keep their id when the tree is
+/// rebalanced, so rotations can be traced across mutations.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId(usize);
+
+impl NodeId {
+	pub(super) fn next() -> Self {
+		Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+	}
+
+	/// Return the numeric value of this id.
+	pub fn get(self) -> usize {
+		self.0
+	}
+}
+
+impl Display for NodeId {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "n{}", self.0)
+	}
+}
+
+impl<T> AANode<T> {
+	/// Return the id of this node, or `None` if this node is `Nil`.
+	pub fn id(&self) -> Option<NodeId> {
+		self.as_ref().map(|node| node.id)
+	}
+
+	/// Render this tree in the DOT format understood by Graphviz, using the [`Debug`]
+	/// output of the node content as label.
+	pub fn to_dot(&self) -> String
+	where
+		T: Debug
+	{
+		self.to_dot_with(|content| format!("{content:?}"))
+	}
+
+	/// Render this tree in the DOT format understood by Graphviz, using `label` to
+	/// create the label of each node. Horizontal links (right children with the same
+	/// level as their parent) are drawn dashed.
+	pub fn to_dot_with<F>(&self, label: F) -> String
+	where
+		F: Fn(&T) -> String
+	{
+		let mut dot = String::from("digraph {\n");
+		let mut stack: Vec<&Node<T>> = self.as_ref().into_iter().collect();
+		while let Some(node) = stack.pop() {
+			let label = escape(&label(&node.content));
+			// writing to a String cannot fail
+			let _ =
+				writeln!(dot, "\t{} [label=\"{} ({})\"];", node.id, label, node.level);
+			for (child, name) in [(&node.left_child, "L"), (&node.right_child, "R")] {
+				if let Some(child) = child.as_ref() {
+					let style = if child.level == node.level {
+						", style=dashed"
+					} else {
+						""
+					};
+					let _ = writeln!(
+						dot,
+						"\t{} -> {} [label=\"{}\"{}];",
+						node.id, child.id, name, style
+					);
+					stack.push(child);
+				}
+			}
+		}
+		dot.push('}');
+		dot.push('\n');
+		dot
+	}
+}
+
+fn escape(label: &str) -> String {
+	let mut escaped = String::with_capacity(label.len());
+	for ch in label.chars() {
+		if ch == '"' || ch == '\\' {
+			escaped.push('\\');
+		}
+		escaped.push(ch);
+	}
+	escaped
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AANode;
+
+	#[test]
+	fn test_ids_survive_rotations() {
+		let mut root = AANode::new();
+		root.insert('A');
+		let a = root.id().unwrap();
+		root.insert('B');
+		root.insert('C');
+
+		// 'B' is the new root after the split, 'A' is still the same node
+		let left = root.as_ref().unwrap().left_child.as_ref().unwrap();
+		assert_eq!(left.content, 'A');
+		assert_eq!(left.id, a);
+		assert_ne!(root.id(), Some(a));
+	}
+
+	#[test]
+	fn test_clone_assigns_new_ids() {
+		let mut root = AANode::new();
+		root.insert(1);
+		let clone = root.clone();
+		assert_eq!(root, clone);
+		assert_ne!(root.id(), clone.id());
+	}
+
+	#[test]
+	fn test_to_dot() {
+		let mut root = AANode::new();
+		root.insert("a");
+		root.insert("b");
+		let a = root.id().unwrap();
+		let b = root.as_ref().unwrap().right_child.id().unwrap();
+		let dot = root.to_dot();
+		assert_eq!(
+			dot,
+			format!(
+				"digraph {{\n\t{a} [label=\"\\\"a\\\" (1)\"];\n\t{a} -> {b} [label=\"R\", style=dashed];\n\t{b} [label=\"\\\"b\\\" (1)\"];\n}}\n"
+			)
+		);
+	}
+}
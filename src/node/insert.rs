@@ -35,6 +35,74 @@ impl<T: Ord> AANode<T> {
 		}
 	}
 
+	/// Insert a new node with `content`, which must not be part of the tree yet, and return
+	/// the path from the root of this subtree to the new node after rebalancing. Use
+	/// [`content_at`](Self::content_at) to obtain a reference to the content.
+	///
+	/// The position of the new node is tracked through all rotations while rebalancing, so
+	/// the content can be found again without comparing it to any other node.
+	pub(crate) fn insert_new(&mut self, content: T) -> Path {
+		let path = match self.as_mut() {
+			None => {
+				*self = content.into();
+				return Path::ROOT;
+			},
+			Some(Node {
+				content: c,
+				left_child,
+				..
+			}) if &content < c => left_child.insert_new(content).push_front(Step::Left),
+			Some(Node {
+				content: c,
+				right_child,
+				..
+			}) => {
+				debug_assert!(&content > c, "content must not be part of the tree");
+				right_child.insert_new(content).push_front(Step::Right)
+			}
+		};
+
+		let path = if self.needs_skew() { path.skew() } else { path };
+		let node = self.take().skew();
+		let path = if node.needs_split() {
+			path.split()
+		} else {
+			path
+		};
+		*self = node.split();
+		path
+	}
+
+	/// Returns a reference to the content of the node at `path`.
+	///
+	/// **Panics** if `path` leads out of the tree.
+	pub(crate) fn content_at(&mut self, path: Path) -> &mut T {
+		let mut node = self;
+		for step in path.steps() {
+			let n = node.as_mut().expect("path leads out of the tree");
+			node = match step {
+				Step::Left => &mut n.left_child,
+				Step::Right => &mut n.right_child
+			};
+		}
+		&mut node.as_mut().expect("path leads out of the tree").content
+	}
+
+	/// Returns `true` if [`skew`](Self::skew) rotates this node.
+	fn needs_skew(&self) -> bool {
+		self.as_ref()
+			.map_or(false, |n| n.left_child.level() == n.level)
+	}
+
+	/// Returns `true` if [`split`](Self::split) rotates this node.
+	fn needs_split(&self) -> bool {
+		self.as_ref().map_or(false, |n| {
+			n.right_child
+				.as_ref()
+				.map_or(false, |r| r.right_child.level() == n.level)
+		})
+	}
+
 	/// Insert a new node with `content` into the tree. If a node with this value already exists,
 	/// it will be replaced and the old content returned.
 	pub fn insert_or_replace(&mut self, content: T) -> Option<T> {
@@ -71,3 +139,117 @@ impl<T: Ord> AANode<T> {
 		}
 	}
 }
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Step {
+	Left,
+	Right
+}
+
+/// The position of a node relative to the root of a subtree, as the steps towards it.
+/// The tree is at most 2·log2(n + 1) levels deep, so 128 steps are always enough.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Path {
+	/// One bit per step, set for [`Step::Right`], with the first step in the lowest bit.
+	bits: u128,
+	len: u32
+}
+
+impl Path {
+	const ROOT: Self = Self { bits: 0, len: 0 };
+
+	fn first(self) -> Option<Step> {
+		match (self.len, self.bits & 1) {
+			(0, _) => None,
+			(_, 0) => Some(Step::Left),
+			_ => Some(Step::Right)
+		}
+	}
+
+	fn push_front(self, step: Step) -> Self {
+		debug_assert!(self.len < u128::BITS, "path is too long");
+		Self {
+			bits: self.bits << 1 | (step == Step::Right) as u128,
+			len: self.len + 1
+		}
+	}
+
+	fn pop_front(self) -> Self {
+		Self {
+			bits: self.bits >> 1,
+			len: self.len - 1
+		}
+	}
+
+	fn steps(self) -> impl Iterator<Item = Step> {
+		(0 .. self.len).map(move |i| match self.bits >> i & 1 {
+			0 => Step::Left,
+			_ => Step::Right
+		})
+	}
+
+	/// Map the path through a [skew](AANode::skew) of its root: The left child L becomes
+	/// the root, the old root T its right child, and the right child of L the left child
+	/// of T.
+	fn skew(self) -> Self {
+		match self.first() {
+			None => self.push_front(Step::Right),
+			Some(Step::Right) => self.push_front(Step::Right),
+			Some(Step::Left) => {
+				let rest = self.pop_front();
+				match rest.first() {
+					None | Some(Step::Left) => rest,
+					Some(Step::Right) => rest
+						.pop_front()
+						.push_front(Step::Left)
+						.push_front(Step::Right)
+				}
+			}
+		}
+	}
+
+	/// Map the path through a [split](AANode::split) of its root: The right child R
+	/// becomes the root, the old root T its left child, and the left child of R the right
+	/// child of T.
+	fn split(self) -> Self {
+		match self.first() {
+			None => self.push_front(Step::Left),
+			Some(Step::Left) => self.push_front(Step::Left),
+			Some(Step::Right) => {
+				let rest = self.pop_front();
+				match rest.first() {
+					None | Some(Step::Right) => rest,
+					Some(Step::Left) => rest
+						.pop_front()
+						.push_front(Step::Right)
+						.push_front(Step::Left)
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AANode;
+	use alloc::collections::BTreeSet;
+
+	#[test]
+	fn test_insert_new_finds_content() {
+		let mut root = AANode::new();
+		let mut seen = BTreeSet::new();
+		for i in 0 .. 500u32 {
+			let key = i.wrapping_mul(0x9E37_79B9) % 1009;
+			if !seen.insert(key) {
+				continue;
+			}
+			let path = root.insert_new(key);
+			assert_eq!(*root.content_at(path), key);
+		}
+		for i in (0 .. 200u32).rev() {
+			let key = 2000 + i;
+			let path = root.insert_new(key);
+			assert_eq!(*root.content_at(path), key);
+		}
+	}
+}
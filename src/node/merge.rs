@@ -0,0 +1,175 @@
+use super::{AANode, IntoNodes, Node};
+use alloc::{boxed::Box, vec::Vec};
+use core::{cmp::Ordering, iter::Peekable, mem};
+
+/// Inputs to [`AANode::extend`] and [`AANode::append`] with less items than this are always
+/// inserted one by one.
+const BULK_EXTEND_MIN: usize = 16;
+
+/// Which item to keep if both inputs of a merge contain equal items.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Keep {
+	/// Keep the item that is already in the tree, like [`AANode::insert`].
+	Existing,
+	/// Keep the new item, like [`AANode::insert_or_replace`].
+	New
+}
+
+impl<T: Ord> AANode<T> {
+	/// Merge `other` into this tree in O(n + m) and return the new length. The nodes of
+	/// both trees are taken apart in ascending order, merged like two sorted streams and
+	/// reassembled into a perfectly balanced tree, so no node is reallocated.
+	pub(crate) fn merge(&mut self, other: Self, keep: Keep) -> usize {
+		let mut existing = IntoNodes::new(self.take()).peekable();
+		let mut new = IntoNodes::new(other).peekable();
+		let mut nodes: Vec<Box<Node<T>>> = Vec::new();
+		loop {
+			let next = match (existing.peek(), new.peek()) {
+				(Some(e), Some(n)) => match e.content.cmp(&n.content) {
+					Ordering::Less => existing.next(),
+					Ordering::Greater => new.next(),
+					Ordering::Equal => pick(&mut existing, &mut new, keep)
+				},
+				(Some(_), None) => existing.next(),
+				(None, Some(_)) => new.next(),
+				(None, None) => break
+			};
+			nodes.extend(next);
+		}
+		let len = nodes.len();
+		*self = Self::from_sorted_nodes(&mut nodes.into_iter(), len);
+		len
+	}
+
+	/// Insert all items of `iter` into this tree of length `len` and return the new
+	/// length.
+	///
+	/// Small inputs are inserted one by one in O(m log(n + m)). If `iter` is known to
+	/// yield a significant amount of items compared to the size of the tree, the items are
+	/// sorted instead and [merged](Self::merge) into the tree in O(n + m log m).
+	pub(crate) fn extend<I>(&mut self, len: usize, iter: I, keep: Keep) -> usize
+	where
+		I: Iterator<Item = T>
+	{
+		if insert_one_by_one(len, iter.size_hint().0) {
+			return self.insert_all(len, iter, keep);
+		}
+
+		let mut items: Vec<T> = iter.collect();
+		// the sort is stable, so equal items keep the order in which they were yielded
+		items.sort();
+		items.dedup_by(|next, prev| {
+			let equal = next == prev;
+			if equal && keep == Keep::New {
+				mem::swap(next, prev);
+			}
+			equal
+		});
+		let items_len = items.len();
+		let other = Self::from_sorted_iter(&mut items.into_iter(), items_len);
+		self.merge(other, keep)
+	}
+}
+
+impl<T: Ord> AANode<T> {
+	/// Move all items of `other` of length `other_len` into this tree of length `len` and
+	/// return the new length.
+	///
+	/// Like for [`extend`](Self::extend), small trees are inserted one by one in
+	/// O(m log(n + m)). Otherwise, both trees are [merged](Self::merge) in O(n + m).
+	pub(crate) fn append(
+		&mut self,
+		len: usize,
+		other: Self,
+		other_len: usize,
+		keep: Keep
+	) -> usize {
+		if insert_one_by_one(len, other_len) {
+			let items = IntoNodes::new(other).map(|node| node.content);
+			return self.insert_all(len, items, keep);
+		}
+		self.merge(other, keep)
+	}
+
+	/// Insert all items of `iter` one by one into this tree of length `len` and return the
+	/// new length.
+	fn insert_all<I>(&mut self, mut len: usize, iter: I, keep: Keep) -> usize
+	where
+		I: Iterator<Item = T>
+	{
+		for item in iter {
+			let inserted = match keep {
+				Keep::Existing => self.insert(item),
+				Keep::New => self.insert_or_replace(item).is_none()
+			};
+			len += inserted as usize;
+		}
+		len
+	}
+}
+
+/// Returns `true` if inserting `additional` items into a tree of length `len` one by one
+/// is cheaper than merging them into the tree.
+fn insert_one_by_one(len: usize, additional: usize) -> bool {
+	additional < BULK_EXTEND_MIN || additional < len / 4
+}
+
+/// Consume the next item of both iterators, which are equal, and return the one to keep.
+fn pick<T, I>(
+	existing: &mut Peekable<I>,
+	new: &mut Peekable<I>,
+	keep: Keep
+) -> Option<Box<Node<T>>>
+where
+	I: Iterator<Item = Box<Node<T>>>
+{
+	let (existing, new) = (existing.next(), new.next());
+	match keep {
+		Keep::Existing => existing,
+		Keep::New => new
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::{collections::BTreeMap, vec::Vec};
+
+	#[test]
+	fn test_append_and_extend() {
+		for (n, m) in [
+			(0, 0),
+			(0, 100),
+			(100, 0),
+			(10, 100),
+			(100, 10),
+			(1000, 5),
+			(1000, 30)
+		] {
+			let mut map: AATreeMap<u32, u32> = (0 .. n).map(|x| (x * 3, x)).collect();
+			let mut btree: BTreeMap<u32, u32> =
+				map.iter().map(|(k, v)| (*k, *v)).collect();
+			let mut other: AATreeMap<u32, u32> =
+				(0 .. m).map(|x| (x * 2, x + 1)).collect();
+			let mut btree_other: BTreeMap<u32, u32> =
+				other.iter().map(|(k, v)| (*k, *v)).collect();
+
+			let mut extended = map.clone();
+			// duplicate keys in the input are resolved in favor of the last one
+			let input: Vec<(u32, u32)> = (0 .. m).rev().map(|x| (x % 50, x)).collect();
+			extended.extend(input.iter().copied());
+			let mut btree_extended = btree.clone();
+			btree_extended.extend(input);
+			extended.assert_valid();
+			assert!(extended.iter().eq(btree_extended.iter()));
+			assert_eq!(extended.len(), btree_extended.len());
+
+			map.append(&mut other);
+			btree.append(&mut btree_other);
+			map.assert_valid();
+			assert!(other.is_empty());
+			assert_eq!(map.len(), btree.len());
+			assert!(map.iter().eq(btree.iter()));
+		}
+	}
+}
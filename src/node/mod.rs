@@ -1,24 +1,149 @@
 //! Low-level implementation of an AA tree. You shouldn't have to use this directly; instead, use
 //! the implementations in [`AATreeSet`](crate::AATreeSet) and [`AATreeMap`](crate::AATreeMap).
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec, vec::Vec};
 use core::mem;
 
+/// Emit a [`tracing`] event for a rebalancing operation. Expands to nothing unless the
+/// `tracing` feature is enabled.
+macro_rules! trace_op {
+	($op:literal, $node:expr, $($fields:tt)*) => {
+		#[cfg(feature = "tracing")]
+		tracing::trace!(target: "aatree", op = $op, node = ?$node.trace_id(), $($fields)*);
+	};
+}
+
+mod build;
+mod canonical;
+#[cfg(feature = "debug-tools")]
+mod debug;
 mod insert;
+mod merge;
 mod remove;
+#[cfg(feature = "tracing")]
+pub mod trace;
 mod traverse;
 
+pub(crate) use build::{should_rebuild, IntoNodes};
+pub use canonical::ParseCanonicalError;
+#[cfg(feature = "debug-tools")]
+pub use debug::NodeId;
+pub(crate) use merge::Keep;
 pub use traverse::*;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Debug)]
 pub struct AANode<T>(Option<Box<Node<T>>>);
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Debug)]
 pub(super) struct Node<T> {
 	pub(super) level: u8,
 	pub(super) content: T,
 	pub(super) left_child: AANode<T>,
-	pub(super) right_child: AANode<T>
+	pub(super) right_child: AANode<T>,
+	#[cfg(feature = "debug-tools")]
+	pub(super) id: NodeId
+}
+
+impl<T> Node<T> {
+	pub(super) fn new(
+		level: u8,
+		content: T,
+		left_child: AANode<T>,
+		right_child: AANode<T>
+	) -> Self {
+		Self {
+			level,
+			content,
+			left_child,
+			right_child,
+			#[cfg(feature = "debug-tools")]
+			id: NodeId::next()
+		}
+	}
+}
+
+/// Cloned nodes are assigned a new id when the `debug-tools` feature is enabled.
+///
+/// The tree is cloned using an explicit work stack, so that cloning arbitrarily deep trees
+/// does not overflow the stack.
+impl<T: Clone> Clone for AANode<T> {
+	fn clone(&self) -> Self {
+		enum Work<'a, T> {
+			Visit(&'a AANode<T>),
+			Build(&'a Node<T>)
+		}
+
+		let mut work = vec![Work::Visit(self)];
+		let mut done = Vec::new();
+		while let Some(item) = work.pop() {
+			match item {
+				Work::Visit(node) => match node.as_ref() {
+					None => done.push(Self::new()),
+					Some(node) => {
+						work.push(Work::Build(node));
+						work.push(Work::Visit(&node.right_child));
+						work.push(Work::Visit(&node.left_child));
+					}
+				},
+				Work::Build(node) => {
+					let right_child = done.pop().unwrap_or_else(|| unreachable!());
+					let left_child = done.pop().unwrap_or_else(|| unreachable!());
+					done.push(
+						Node::new(
+							node.level,
+							node.content.clone(),
+							left_child,
+							right_child
+						)
+						.into()
+					);
+				}
+			}
+		}
+		done.pop().unwrap_or_else(|| unreachable!())
+	}
+}
+
+/// Node ids never participate in equality. Two trees are equal if they have the same
+/// structure, levels and contents.
+///
+/// The trees are compared using an explicit work stack, so that comparing arbitrarily
+/// deep trees does not overflow the stack.
+impl<T: PartialEq> PartialEq for AANode<T> {
+	fn eq(&self, other: &Self) -> bool {
+		let mut work = vec![(self, other)];
+		while let Some((a, b)) = work.pop() {
+			match (a.as_ref(), b.as_ref()) {
+				(None, None) => {},
+				(Some(a), Some(b)) if a.level == b.level && a.content == b.content => {
+					work.push((&a.right_child, &b.right_child));
+					work.push((&a.left_child, &b.left_child));
+				},
+				_ => return false
+			}
+		}
+		true
+	}
+}
+
+/// Dropping a tree flattens it using right rotations, so that dropping arbitrarily deep
+/// trees neither overflows the stack nor needs to allocate.
+impl<T> Drop for AANode<T> {
+	fn drop(&mut self) {
+		let mut current = self.0.take();
+		while let Some(mut node) = current {
+			current = match node.left_child.0.take() {
+				// rotate the left child up, so that the left spine gets shorter
+				Some(mut left) => {
+					node.left_child.0 = left.right_child.0.take();
+					left.right_child.0 = Some(node);
+					Some(left)
+				},
+				// without a left child, the node can be dropped without recursion
+				None => node.right_child.0.take()
+			};
+		}
+	}
 }
 
 impl<T> From<Node<T>> for AANode<T> {
@@ -35,30 +160,24 @@ impl<T> Default for AANode<T> {
 
 impl<T> From<T> for AANode<T> {
 	fn from(content: T) -> Self {
-		Node {
-			level: 1,
-			content,
-			left_child: Self(None),
-			right_child: Self(None)
-		}
-		.into()
+		Node::new(1, content, Self(None), Self(None)).into()
 	}
 }
 
 impl<T> AANode<T> {
-	pub(super) fn unbox(self) -> Option<Node<T>> {
-		self.0.map(|this| *this)
+	pub(super) fn unbox(mut self) -> Option<Node<T>> {
+		self.0.take().map(|this| *this)
 	}
 
 	pub(super) fn as_ref(&self) -> Option<&Node<T>> {
 		self.0.as_ref().map(Box::as_ref)
 	}
 
-	fn as_mut(&mut self) -> Option<&mut Node<T>> {
+	pub(super) fn as_mut(&mut self) -> Option<&mut Node<T>> {
 		self.0.as_mut().map(Box::as_mut)
 	}
 
-	fn take(&mut self) -> Self {
+	pub(super) fn take(&mut self) -> Self {
 		Self(self.0.take())
 	}
 
@@ -67,6 +186,20 @@ impl<T> AANode<T> {
 		Self(None)
 	}
 
+	/// Create a new node with the given level and children. It is the caller's
+	/// responsibility to ensure that the resulting tree is a valid AA tree, otherwise
+	/// all operations on the tree may produce unexpected results.
+	///
+	/// You probably want to use the [`aanode!`](crate::aanode) macro instead.
+	pub fn with_children(
+		content: T,
+		level: u8,
+		left_child: Self,
+		right_child: Self
+	) -> Self {
+		Node::new(level, content, left_child, right_child).into()
+	}
+
 	/// Return true if this node is `Nil`.
 	pub const fn is_nil(&self) -> bool {
 		self.0.is_none()
@@ -121,13 +254,32 @@ impl<T> AANode<T> {
 		}
 	}
 
-	pub(super) fn level(&self) -> u8 {
+	/// Return the level of this node, or `0` if this node is `Nil`.
+	pub fn level(&self) -> u8 {
 		match self.as_ref() {
 			None => 0,
 			Some(Node { level, .. }) => *level
 		}
 	}
 
+	/// Return the height of the tree rooted at this node, that is, the number of nodes on
+	/// the longest path from this node to a leaf, or `0` if this node is `Nil`. This
+	/// walks the whole tree and takes O(n) time.
+	///
+	/// In a valid AA tree, the height never exceeds `2 * level`.
+	pub fn height(&self) -> usize {
+		let mut height = 0;
+		let mut work = vec![(self, 1)];
+		while let Some((node, depth)) = work.pop() {
+			if let Some(node) = node.as_ref() {
+				height = height.max(depth);
+				work.push((&node.left_child, depth + 1));
+				work.push((&node.right_child, depth + 1));
+			}
+		}
+		height
+	}
+
 	fn content_mut(&mut self) -> Option<&mut T> {
 		self.as_mut().map(|Node { content, .. }| content)
 	}
@@ -173,6 +325,7 @@ impl<T> AANode<T> {
 					.right_child = self;
 
 				// L is our new node
+				trace_op!("skew", l_node, level = l_node.level());
 				l_node
 			}
 		}
@@ -212,81 +365,52 @@ impl<T> AANode<T> {
 				r_node_mut.left_child = self;
 
 				// R is our new node
+				trace_op!("split", r_node, level = r_node.level());
 				r_node
 			}
 		}
 	}
 }
 
-#[cfg(all(test, not(feature = "benchmark")))]
+#[cfg(test)]
 mod test {
 	use super::*;
 
-	macro_rules! tree {
-		() => {
-			AANode::new()
-		};
-		(Nil) => {
-			AANode::new()
-		};
-		($content:expr) => {
-			AANode::from($content)
-		};
-		($content:expr => [$level:expr, $left:tt, $right:tt]) => {
-			{
-				let _left = tree!(@internal $left);
-				let _right = tree!(@internal $right);
-				AANode(Some(Box::new(Node {
-					level: $level,
-					content: $content,
-					left_child: _left,
-					right_child: _right
-				})))
-			}
-		};
-		(@internal ($content:expr => [$level:expr, $left:tt, $right:tt])) => {
-			tree!($content => [$level, $left, $right])
-		};
-		(@internal $inner:tt) => {
-			tree!($inner)
-		};
-	}
-
 	// ### TEST SKEW ###
 
 	#[test]
 	fn test_skew_nil() {
-		let root: AANode<char> = tree!();
+		let root: AANode<char> = aanode!();
 		println!("Input: {:?}", root);
 		let skewed = root.skew();
-		let expected = tree!();
+		let expected = aanode!();
 		assert_eq!(skewed, expected);
 	}
 
 	#[test]
 	fn test_skew_leaf() {
-		let root = tree!('T');
+		let root = aanode!('T');
 		println!("Input: {:?}", root);
 		let skewed = root.skew();
-		let expected = tree!('T');
+		let expected = aanode!('T');
 		assert_eq!(skewed, expected);
 	}
 
 	#[test]
 	fn test_skew_simple() {
-		let root = tree!('T' => [2, ('L' => [2, Nil, Nil]), 'R']);
+		let root = aanode!('T' => [2, ('L' => [2, Nil, Nil]), 'R']);
 		println!("Input: {:?}", root);
 		let skewed = root.skew();
-		let expected = tree!('L' => [2, Nil, ('T' => [2, Nil, 'R'])]);
+		let expected = aanode!('L' => [2, Nil, ('T' => [2, Nil, 'R'])]);
 		assert_eq!(skewed, expected);
 	}
 
 	#[test]
 	fn test_skew_full() {
-		let root = tree!('T' => [2, ('L' => [2, 'A', 'B']), 'R']);
+		let root = aanode!('T' => [2, ('L' => [2, 'A', 'B']), 'R']);
 		println!("Input: {:?}", root);
 		let skewed = root.skew();
-		let expected = tree!('L' => [2, 'A', ('T' => [2, 'B', 'R'])]);
+		let expected = aanode!('L' => [2, 'A', ('T' => [2, 'B', 'R'])]);
 		assert_eq!(skewed, expected);
 	}
 
@@ -294,37 +418,38 @@ mod test {
 
 	#[test]
 	fn test_split_nil() {
-		let root: AANode<char> = tree!();
+		let root: AANode<char> = aanode!();
 		println!("Input: {:?}", root);
 		let splitted = root.split();
-		let expected = tree!();
+		let expected = aanode!();
 		assert_eq!(splitted, expected);
 	}
 
 	#[test]
 	fn test_split_leaf() {
-		let root = tree!('T');
+		let root = aanode!('T');
 		println!("Input: {:?}", root);
 		let splitted = root.split();
-		let expected = tree!('T');
+		let expected = aanode!('T');
 		assert_eq!(splitted, expected);
 	}
 
 	#[test]
 	fn test_split_good_tree() {
-		let root = tree!('T' => [2, 'A', ('R' => [2, 'B', 'X'])]);
+		let root = aanode!('T' => [2, 'A', ('R' => [2, 'B', 'X'])]);
 		println!("Input: {:?}", root);
 		let splitted = root.split();
-		let expected = tree!('T' => [2, 'A', ('R' => [2, 'B', 'X'])]);
+		let expected = aanode!('T' => [2, 'A', ('R' => [2, 'B', 'X'])]);
 		assert_eq!(splitted, expected);
 	}
 
 	#[test]
 	fn test_split_bad_tree() {
-		let root = tree!('T' => [2, 'A', ('R' => [2, 'B', ('X' => [2, 'Y', 'Z'])])]);
+		let root = aanode!('T' => [2, 'A', ('R' => [2, 'B', ('X' => [2, 'Y', 'Z'])])]);
 		println!("Input: {:?}", root);
 		let splitted = root.split();
-		let expected = tree!('R' => [3, ('T' => [2, 'A', 'B']), ('X' => [2, 'Y', 'Z'])]);
+		let expected =
+			aanode!('R' => [3, ('T' => [2, 'A', 'B']), ('X' => [2, 'Y', 'Z'])]);
 		assert_eq!(splitted, expected);
 	}
 
@@ -332,31 +457,32 @@ mod test {
 
 	#[test]
 	fn test_insert_greater() {
-		let mut root = tree!();
+		let mut root = aanode!();
 		for content in ['A', 'B', 'C', 'D', 'E', 'F', 'G'].iter() {
 			assert!(root.insert(*content));
 		}
-		let expected = tree!('D' => [3, ('B' => [2, 'A', 'C']), ('F' => [2, 'E', 'G'])]);
+		let expected =
+			aanode!('D' => [3, ('B' => [2, 'A', 'C']), ('F' => [2, 'E', 'G'])]);
 		assert_eq!(root, expected);
 	}
 
 	#[test]
 	fn test_insert_smaller() {
-		let mut root = tree!();
+		let mut root = aanode!();
 		for content in ['Z', 'Y', 'X', 'W', 'V'].iter() {
 			assert!(root.insert(*content));
 		}
-		let expected = tree!('W' => [2, 'V', ('Y' => [2, 'X', 'Z'])]);
+		let expected = aanode!('W' => [2, 'V', ('Y' => [2, 'X', 'Z'])]);
 		assert_eq!(root, expected);
 	}
 
 	#[test]
 	fn test_insert_multiple() {
-		let mut root = tree!();
+		let mut root = aanode!();
 		for content in ['A', 'A'].iter() {
 			root.insert(*content);
 		}
-		let expected = tree!('A');
+		let expected = aanode!('A');
 		assert_eq!(root, expected);
 	}
 
@@ -364,20 +490,20 @@ mod test {
 
 	#[test]
 	fn test_remove_successor() {
-		let mut root = tree!('B' => [1, Nil, 'C']);
+		let mut root = aanode!('B' => [1, Nil, 'C']);
 		println!("Input:  `{:?}`", root);
 		let removed = root.remove(&'B');
-		let expected = tree!('C');
+		let expected = aanode!('C');
 		assert_eq!(removed, Some('B'));
 		assert_eq!(root, expected);
 	}
 
 	#[test]
 	fn test_remove_predecessor() {
-		let mut root = tree!('B' => [2, 'A', 'C']);
+		let mut root = aanode!('B' => [2, 'A', 'C']);
 		println!("Input:  `{:?}`", root);
 		let removed = root.remove(&'B');
-		let expected = tree!('A' => [1, Nil, 'C']);
+		let expected = aanode!('A' => [1, Nil, 'C']);
 		assert_eq!(removed, Some('B'));
 		assert_eq!(root, expected);
 	}
@@ -385,11 +511,42 @@ mod test {
 	#[test]
 	fn test_remove_complex() {
 		// example taken from https://web.eecs.umich.edu/~sugih/courses/eecs281/f11/lectures/12-AAtrees+Treaps.pdf
-		let mut root = tree!(30 => [3, (15 => [2, 5, 20]), (70 => [3, (50 => [2, 35, (60 => [2, 55, 65])]), (85 => [2, 80, 90])])]);
+		let mut root = aanode!(30 => [3, (15 => [2, 5, 20]), (70 => [3, (50 => [2, 35, (60 => [2, 55, 65])]), (85 => [2, 80, 90])])]);
 		println!("Input:  `{:?}`", root);
 		let removed = root.remove(&5);
-		let expected = tree!(50 => [3, (30 => [2, (15 => [1, Nil, 20]), 35]), (70 => [3, (60 => [2, 55, 65]), (85 => [2, 80, 90])])]);
+		let expected = aanode!(50 => [3, (30 => [2, (15 => [1, Nil, 20]), 35]), (70 => [3, (60 => [2, 55, 65]), (85 => [2, 80, 90])])]);
 		assert_eq!(removed, Some(5));
 		assert_eq!(root, expected);
 	}
+
+	// ### TEST DEEP TREES ###
+
+	/// Build a degenerate tree where every node has only a left or only a right child,
+	/// which is invalid but can be constructed using the public node API.
+	fn deep_tree(depth: u32, left: bool) -> AANode<u32> {
+		let mut root = AANode::new();
+		for i in 0 .. depth {
+			root = match left {
+				true => AANode::with_children(i, 1, root, AANode::new()),
+				false => AANode::with_children(i, 1, AANode::new(), root)
+			};
+		}
+		root
+	}
+
+	#[test]
+	fn test_deep_tree_is_stack_safe() {
+		// deep enough to overflow the stack with recursion, but miri is too slow for that
+		const DEPTH: u32 = if cfg!(miri) { 1000 } else { 1_000_000 };
+		for left in [true, false] {
+			let root = deep_tree(DEPTH, left);
+			assert_eq!(root.height(), DEPTH as usize);
+			assert_eq!(root.count(), DEPTH as usize);
+			let clone = root.clone();
+			assert!(clone == root);
+			assert!(clone != deep_tree(DEPTH, !left));
+			drop(clone);
+			drop(root);
+		}
+	}
 }
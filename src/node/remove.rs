@@ -1,4 +1,5 @@
-use super::{AANode, Node};
+use super::{AANode, IntoNodes, Node};
+use alloc::vec::Vec;
 use core::{borrow::Borrow, cmp::Ordering, mem};
 
 impl<T> AANode<T> {
@@ -24,20 +25,41 @@ impl<T> AANode<T> {
 		};
 
 		if equal {
-			// if we have a left child, use the predecessor
-			if let Some(left_child) = self.left_child_mut() {
-				let pred = left_child.remove_predecessor();
-				removed = Some(mem::replace(self.content_mut().unwrap(), pred.unwrap()));
-			}
-			// if we have a right child but no left child, use the successor
-			else if let Some(right_child) = self.right_child_mut() {
-				let suc = right_child.remove_successor();
-				removed = Some(mem::replace(self.content_mut().unwrap(), suc.unwrap()));
-			}
-			// else we have a leaf, so just delete it
-			else {
-				removed = Some(self.take().unbox().unwrap().content);
-			}
+			removed = Some(self.remove_root());
+		}
+
+		if removed.is_some() {
+			self.remove_cleanup();
+		}
+		removed
+	}
+
+	/// Remove the smallest value for which `pred` returns `true` from this tree. `pred`
+	/// must return `false` for all values below some point and `true` for all values
+	/// above it, so that it can be used to find the value in O(log n).
+	pub(crate) fn remove_first<F>(&mut self, pred: &mut F) -> Option<T>
+	where
+		F: FnMut(&T) -> bool
+	{
+		let (here, mut removed) = match self.as_mut() {
+			None => return None,
+			Some(Node {
+				content,
+				left_child,
+				right_child,
+				..
+			}) => {
+				if pred(content) {
+					let removed = left_child.remove_first(pred);
+					(removed.is_none(), removed)
+				} else {
+					(false, right_child.remove_first(pred))
+				}
+			},
+		};
+
+		if here {
+			removed = Some(self.remove_root());
 		}
 
 		if removed.is_some() {
@@ -46,6 +68,69 @@ impl<T> AANode<T> {
 		removed
 	}
 
+	/// Remove the content of the root of this tree, replacing it with its predecessor or
+	/// successor, and return it. The caller has to call [`remove_cleanup`] afterwards.
+	///
+	/// **Panics** if this tree is empty.
+	///
+	/// [`remove_cleanup`]: Self::remove_cleanup
+	fn remove_root(&mut self) -> T {
+		// if we have a left child, use the predecessor
+		if let Some(left_child) = self.left_child_mut() {
+			let pred = left_child.remove_predecessor();
+			let removed = mem::replace(self.content_mut().unwrap(), pred.unwrap());
+			trace_op!(
+				"replace_root",
+				self,
+				level = self.level(),
+				with = "predecessor"
+			);
+			removed
+		}
+		// if we have a right child but no left child, use the successor
+		else if let Some(right_child) = self.right_child_mut() {
+			let suc = right_child.remove_successor();
+			let removed = mem::replace(self.content_mut().unwrap(), suc.unwrap());
+			trace_op!(
+				"replace_root",
+				self,
+				level = self.level(),
+				with = "successor"
+			);
+			removed
+		}
+		// else we have a leaf, so just delete it
+		else {
+			self.take().unbox().unwrap().content
+		}
+	}
+
+	/// Remove all values for which `f` returns `false`, and return the number of removed
+	/// values. The values are visited in ascending order.
+	///
+	/// The tree is taken apart into its nodes in a single in-order pass, and the nodes
+	/// that are kept are linked into a perfectly balanced tree afterwards. This takes O(n)
+	/// time independently of the number of removed values, and reuses the allocations of
+	/// the remaining nodes. If `f` panics, the tree is left empty.
+	pub(crate) fn retain<F>(&mut self, mut f: F) -> usize
+	where
+		F: FnMut(&mut T) -> bool
+	{
+		let mut kept = Vec::new();
+		let mut removed = 0;
+		for mut node in IntoNodes::new(self.take()) {
+			if f(&mut node.content) {
+				kept.push(node);
+			} else {
+				removed += 1;
+			}
+		}
+
+		let len = kept.len();
+		*self = Self::from_sorted_nodes(&mut kept.into_iter(), len);
+		removed
+	}
+
 	/// Remove the successor (smallest node) of the parent of this node and return its content.
 	pub(crate) fn remove_successor(&mut self) -> Option<T> {
 		let suc = if let Some(left_child) = self.left_child_mut() {
@@ -97,6 +182,8 @@ impl<T> AANode<T> {
 	/// Run fixes necessary after removing/replacing `self` or one of the child nodes to retain
 	/// the AA tree properties.
 	fn remove_cleanup(&mut self) {
+		#[cfg(feature = "tracing")]
+		let old_level = self.level();
 		if let Some(Node {
 			level,
 			left_child,
@@ -113,6 +200,11 @@ impl<T> AANode<T> {
 				}
 			}
 
+			#[cfg(feature = "tracing")]
+			if old_level != self.level() {
+				trace_op!("level_change", self, level = self.level(), from = old_level);
+			}
+
 			// rebalance the tree by applying 3x skew and 2x split
 			let mut node = self.take();
 			node = node.skew();
@@ -133,3 +225,32 @@ impl<T> AANode<T> {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeMap;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_retain() {
+		for len in [0, 1, 2, 3, 10, 100, 1000] {
+			for modulus in [1, 2, 3, 100] {
+				let mut map: AATreeMap<u32, u32> = (0 .. len).map(|x| (x, x)).collect();
+				let mut visited = Vec::new();
+				map.retain(|&key, value| {
+					visited.push(key);
+					*value += 1;
+					key % modulus != 0
+				});
+				map.assert_valid();
+				assert_eq!(visited, (0 .. len).collect::<Vec<_>>());
+				let expected: Vec<_> = (0 .. len)
+					.filter(|x| x % modulus != 0)
+					.map(|x| (x, x + 1))
+					.collect();
+				assert_eq!(map.len(), expected.len());
+				assert_eq!(map.into_iter().collect::<Vec<_>>(), expected);
+			}
+		}
+	}
+}
@@ -0,0 +1,220 @@
+//! Optional instrumentation of the rebalancing operations using [`tracing`]. Only
+//! available with the `tracing` feature.
+//!
+//! All events are emitted with target `aatree` at trace level and contain an `op` field
+//! (one of `skew`, `split`, `level_change` and `replace_root`) as well as the `level` of
+//! the affected node. If the `debug-tools` feature is enabled, the `node` field contains
+//! the [`NodeId`](super::NodeId) of the affected node.
+//!
+//! Node contents are not recorded since that would require all tree operations to be
+//! bounded by `Debug`. Use the node ids together with `to_dot` to relate events to keys.
+//!
+//! Additionally, [`AATreeMap`](crate::AATreeMap) and [`AATreeSet`](crate::AATreeSet) can
+//! emit events at warn level after inserting, which act as an early warning for
+//! pathological trees. These are disabled by default and can be enabled using
+//! [`set_depth_warning`] and [`set_height_warning`]. Their `op` field is either
+//! `depth_warning` or `height_warning`, and they also contain the `level` of the root,
+//! the resulting worst-case `depth` of an operation, and the `len` of the collection.
+//!
+//! Since the height of the tree is not stored, the checks use the worst-case depth of an
+//! operation, which is `2 * level - 1` for a root with level `level`.
+
+use super::AANode;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// 0 means disabled
+static DEPTH_WARNING: AtomicUsize = AtomicUsize::new(0);
+/// 0 means disabled
+static HEIGHT_WARNING: AtomicUsize = AtomicUsize::new(0);
+
+/// Emit a warning whenever the worst-case depth of an operation exceeds `depth`. Pass
+/// `None` to disable the warning again.
+pub fn set_depth_warning(depth: Option<usize>) {
+	DEPTH_WARNING.store(depth.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Emit a warning whenever the worst-case depth of an operation exceeds
+/// `factor * log2(len)`, rounded up to the next integer logarithm. Pass `None` to disable
+/// the warning again.
+pub fn set_height_warning(factor: Option<usize>) {
+	HEIGHT_WARNING.store(factor.unwrap_or(0), Ordering::Relaxed);
+}
+
+impl<T> AANode<T> {
+	/// Emit the warnings configured using [`set_depth_warning`] and
+	/// [`set_height_warning`] for this tree containing `len` nodes.
+	pub(crate) fn check_height(&self, len: usize) {
+		let level = self.level();
+		let depth = (2 * level as usize).saturating_sub(1);
+		let max_depth = DEPTH_WARNING.load(Ordering::Relaxed);
+		if max_depth != 0 && depth > max_depth {
+			tracing::warn!(
+				target: "aatree",
+				op = "depth_warning",
+				level,
+				depth,
+				len,
+				"operations on this tree exceed the configured depth of {max_depth}"
+			);
+		}
+		let factor = HEIGHT_WARNING.load(Ordering::Relaxed);
+		let log2 = (usize::BITS - len.leading_zeros()) as usize;
+		if factor != 0 && depth > factor.saturating_mul(log2) {
+			tracing::warn!(
+				target: "aatree",
+				op = "height_warning",
+				level,
+				depth,
+				len,
+				"the height of this tree exceeds {factor} * log2(len)"
+			);
+		}
+	}
+
+	pub(super) fn trace_id(&self) -> Option<usize> {
+		#[cfg(feature = "debug-tools")]
+		{
+			self.id().map(super::NodeId::get)
+		}
+		#[cfg(not(feature = "debug-tools"))]
+		{
+			None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{set_depth_warning, set_height_warning};
+	use crate::AATreeSet;
+	use std::{
+		cell::RefCell,
+		mem,
+		string::{String, ToString},
+		sync::Once,
+		vec::Vec
+	};
+	use tracing::{
+		field::{Field, Visit},
+		span, Event, Metadata, Subscriber
+	};
+
+	thread_local! {
+		/// The events emitted on each thread, so that tests running in parallel do not see
+		/// each other's events.
+		static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+	}
+
+	struct Collector;
+
+	struct OpVisitor(Option<String>);
+
+	impl Visit for OpVisitor {
+		fn record_str(&mut self, field: &Field, value: &str) {
+			if field.name() == "op" {
+				self.0 = Some(value.to_string());
+			}
+		}
+
+		fn record_debug(&mut self, _: &Field, _: &dyn core::fmt::Debug) {}
+	}
+
+	impl Subscriber for Collector {
+		fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+			metadata.target() == "aatree"
+		}
+
+		fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+			span::Id::from_u64(1)
+		}
+
+		fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+		fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+		fn event(&self, event: &Event<'_>) {
+			let mut visitor = OpVisitor(None);
+			event.record(&mut visitor);
+			if let Some(op) = visitor.0 {
+				EVENTS.with(|events| events.borrow_mut().push(op));
+			}
+		}
+
+		fn enter(&self, _: &span::Id) {}
+
+		fn exit(&self, _: &span::Id) {}
+	}
+
+	fn init() {
+		static INIT: Once = Once::new();
+		INIT.call_once(|| tracing::subscriber::set_global_default(Collector).unwrap());
+	}
+
+	/// Return all events of this thread. Warnings are filtered out unless `warnings` is
+	/// true since they depend on global configuration that other tests might change.
+	fn events_with(warnings: bool) -> Vec<String> {
+		let events = EVENTS.with(|events| mem::take(&mut *events.borrow_mut()));
+		events
+			.into_iter()
+			.filter(|op| warnings || !op.ends_with("_warning"))
+			.collect()
+	}
+
+	fn events() -> Vec<String> {
+		events_with(false)
+	}
+
+	fn warnings() -> Vec<String> {
+		let mut events = events_with(true);
+		events.retain(|op| op.ends_with("_warning"));
+		events
+	}
+
+	#[test]
+	fn test_rebalancing_events() {
+		init();
+
+		let mut set = AATreeSet::new();
+		set.insert('A');
+		set.insert('B');
+		assert!(events().is_empty());
+		set.insert('C');
+		assert_eq!(events(), ["split"]);
+
+		set.insert('D');
+		set.insert('E');
+		events();
+		// the tree is now B => [2, A, (D => [2, C, E])]
+		set.remove(&'B');
+		let ops = events();
+		assert_eq!(ops[0], "replace_root");
+		assert!(ops.iter().any(|op| op == "level_change"));
+	}
+
+	#[test]
+	fn test_warnings() {
+		init();
+
+		let mut set = AATreeSet::new();
+		for i in 0 .. 7 {
+			set.insert(i);
+		}
+		// the tree now has 7 nodes with level 3
+		warnings();
+
+		set_depth_warning(Some(4));
+		set.insert(7);
+		set_depth_warning(None);
+		assert_eq!(warnings(), ["depth_warning"]);
+
+		set_height_warning(Some(1));
+		set.insert(8);
+		set_height_warning(None);
+		assert_eq!(warnings(), ["height_warning"]);
+
+		set_height_warning(Some(2));
+		set.insert(9);
+		set_height_warning(None);
+		assert!(warnings().is_empty());
+	}
+}
@@ -1,12 +1,18 @@
 use super::{AANode, Node};
-use core::fmt::{self, Debug, Display, Formatter};
+use core::{
+	fmt::{self, Debug, Display, Formatter},
+	ops::ControlFlow
+};
 
 /// This type specifies the requested step for [`traverse`](AANode::traverse).
 #[derive(Debug)]
 pub enum TraverseStep<R> {
 	Left,
 	Right,
-	Value(Option<R>)
+	Value(Option<R>),
+	/// Abort the whole traversal and return the value without calling the up callback
+	/// for any node.
+	Stop(R)
 }
 
 impl<T> AANode<T> {
@@ -14,8 +20,8 @@ impl<T> AANode<T> {
 	///
 	/// `down_callback` is called for each node on the way down the tree. It is passed the
 	/// value contained in the current node and may return either `Left` or `Right` to
-	/// continue the traversal in that direction, or `Value` to stop the traversal, for
-	/// example because a value was found.
+	/// continue the traversal in that direction, `Value` to stop the traversal, for
+	/// example because a value was found, or `Stop` to return a value immediately.
 	///
 	/// `up_callback` is called while going back up with the content of each node and the
 	/// result of traversing so far (i.e., `None` for the first call when the search hit a
@@ -25,38 +31,109 @@ impl<T> AANode<T> {
 		F: Fn(&'a T) -> TraverseStep<R> + Copy,
 		G: Fn(&'a T, Option<R>) -> Option<R> + Copy
 	{
-		self.as_ref().and_then(
-			|Node {
-			     content,
-			     left_child,
-			     right_child,
-			     ..
-			 }| {
-				let child = match down_callback(content) {
-					TraverseStep::Left => left_child,
-					TraverseStep::Right => right_child,
-					TraverseStep::Value(v) => return v
-				};
-				up_callback(content, child.traverse(down_callback, up_callback))
-			}
-		)
+		self.try_traverse(down_callback, |content, sub| {
+			ControlFlow::Continue(up_callback(content, sub))
+		})
 	}
-}
 
-pub(crate) struct TraverseMutError(&'static str);
+	/// Traverse the tree like [`traverse`](Self::traverse), but allow `up_callback` to
+	/// abort the traversal early by returning [`ControlFlow::Break`]. The value is then
+	/// returned immediately without calling `up_callback` for the remaining ancestors.
+	///
+	/// # Example
+	///
+	/// Find the smallest value that is greater than or equal to 4. Once a candidate was
+	/// found, no ancestor can be a better match, so there is no need to look at them.
+	///
+	/// ```rust
+	/// # use aatree::{aanode, node::TraverseStep};
+	/// # use core::ops::ControlFlow;
+	/// let root = aanode!(3 => [2, 1, (5 => [2, Nil, 7])]);
+	/// let found = root.try_traverse(
+	/// 	|&content| match content < 4 {
+	/// 		true => TraverseStep::Right,
+	/// 		false => TraverseStep::Left
+	/// 	},
+	/// 	|&content, sub: Option<u32>| match sub {
+	/// 		Some(sub) => ControlFlow::Break(sub),
+	/// 		None if content >= 4 => ControlFlow::Break(content),
+	/// 		None => ControlFlow::Continue(None)
+	/// 	}
+	/// );
+	/// assert_eq!(found, Some(5));
+	/// ```
+	pub fn try_traverse<'a, F, G, R>(
+		&'a self,
+		down_callback: F,
+		up_callback: G
+	) -> Option<R>
+	where
+		F: Fn(&'a T) -> TraverseStep<R> + Copy,
+		G: Fn(&'a T, Option<R>) -> ControlFlow<R, Option<R>> + Copy
+	{
+		match self.traverse_impl(down_callback, up_callback) {
+			ControlFlow::Continue(value) => value,
+			ControlFlow::Break(value) => Some(value)
+		}
+	}
 
-impl Display for TraverseMutError {
-	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-		write!(f, "Attempt to turn {} but there is no such child", self.0)
+	fn traverse_impl<'a, F, G, R>(
+		&'a self,
+		down_callback: F,
+		up_callback: G
+	) -> ControlFlow<R, Option<R>>
+	where
+		F: Fn(&'a T) -> TraverseStep<R> + Copy,
+		G: Fn(&'a T, Option<R>) -> ControlFlow<R, Option<R>> + Copy
+	{
+		let Node {
+			content,
+			left_child,
+			right_child,
+			..
+		} = match self.as_ref() {
+			Some(node) => node,
+			None => return ControlFlow::Continue(None)
+		};
+		let child = match down_callback(content) {
+			TraverseStep::Left => left_child,
+			TraverseStep::Right => right_child,
+			TraverseStep::Value(v) => return ControlFlow::Continue(v),
+			TraverseStep::Stop(v) => return ControlFlow::Break(v)
+		};
+		match child.traverse_impl(down_callback, up_callback) {
+			ControlFlow::Continue(sub) => up_callback(content, sub),
+			ControlFlow::Break(v) => ControlFlow::Break(v)
+		}
 	}
 }
 
-impl Debug for TraverseMutError {
+/// An error that occurs when navigating through a tree.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum TraverseError {
+	/// Attempt to turn left, but the current node has no left child.
+	NoLeftChild,
+	/// Attempt to turn right, but the current node has no right child.
+	NoRightChild
+}
+
+impl Display for TraverseError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-		Display::fmt(self, f)
+		match self {
+			Self::NoLeftChild => {
+				f.write_str("Attempt to turn left but there is no such child")
+			},
+			Self::NoRightChild => {
+				f.write_str("Attempt to turn right but there is no such child")
+			},
+		}
 	}
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for TraverseError {}
+
 pub(crate) struct TraverseMut<'a, T> {
 	node: &'a mut AANode<T>
 }
@@ -102,7 +179,7 @@ impl<'a, T> TraverseMut<'a, T> {
 	}
 
 	/// Continue traversing the tree with the left child of the current node.
-	pub(crate) fn turn_left(self) -> Result<Self, TraverseMutError> {
+	pub(crate) fn turn_left(self) -> Result<Self, TraverseError> {
 		Ok(Self {
 			node: self
 				.node
@@ -110,7 +187,7 @@ impl<'a, T> TraverseMut<'a, T> {
 				.and_then(|node| {
 					(!node.left_child.is_nil()).then(|| &mut node.left_child)
 				})
-				.ok_or(TraverseMutError("left"))?
+				.ok_or(TraverseError::NoLeftChild)?
 		})
 	}
 
@@ -130,7 +207,7 @@ impl<'a, T> TraverseMut<'a, T> {
 	}
 
 	/// Continue traversing the tree with the left child of the current node.
-	pub(crate) fn turn_right(self) -> Result<Self, TraverseMutError> {
+	pub(crate) fn turn_right(self) -> Result<Self, TraverseError> {
 		Ok(Self {
 			node: self
 				.node
@@ -138,7 +215,7 @@ impl<'a, T> TraverseMut<'a, T> {
 				.and_then(|node| {
 					(!node.right_child.is_nil()).then(|| &mut node.right_child)
 				})
-				.ok_or(TraverseMutError("right"))?
+				.ok_or(TraverseError::NoRightChild)?
 		})
 	}
 }
@@ -152,3 +229,68 @@ impl<T> AANode<T> {
 		(!self.is_nil()).then(|| TraverseMut { node: self })
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{TraverseError, TraverseStep};
+	use alloc::{string::ToString, vec::Vec};
+	use core::{cell::RefCell, ops::ControlFlow};
+
+	#[test]
+	fn test_traverse_stop() {
+		let root = aanode!(4 => [2, 2, (6 => [2, Nil, 8])]);
+		let visited = RefCell::new(Vec::new());
+		// always go right and stop at 6, the up callback must never be called
+		let found = root.traverse(
+			|&content| match content {
+				6 => TraverseStep::Stop(content),
+				_ => TraverseStep::Right
+			},
+			|&content, sub| {
+				visited.borrow_mut().push(content);
+				sub
+			}
+		);
+		assert_eq!(found, Some(6));
+		assert!(visited.borrow().is_empty());
+	}
+
+	#[test]
+	fn test_try_traverse_break() {
+		let root = aanode!(4 => [2, 2, (6 => [2, Nil, 8])]);
+		let visited = RefCell::new(Vec::new());
+		// walk down to 8, then break at 6 so that 4 is never visited on the way up
+		let found = root.try_traverse(
+			|_| TraverseStep::Right,
+			|&content, sub: Option<i32>| {
+				visited.borrow_mut().push(content);
+				match content {
+					6 => ControlFlow::Break(sub.unwrap_or(0) + content),
+					_ => ControlFlow::Continue(Some(sub.unwrap_or(0) + content))
+				}
+			}
+		);
+		assert_eq!(found, Some(14));
+		assert_eq!(*visited.borrow(), [8, 6]);
+	}
+
+	#[test]
+	fn test_traverse_mut_errors() {
+		let mut root = aanode!(4 => [2, 2, Nil]);
+		let traverse = root.traverse_mut().unwrap();
+		let left = traverse.turn_left().ok().unwrap();
+		assert_eq!(*left.peek(), 2);
+		let err = left.turn_right().err().unwrap();
+		assert_eq!(err, TraverseError::NoRightChild);
+		assert_eq!(
+			err.to_string(),
+			"Attempt to turn right but there is no such child"
+		);
+
+		let traverse = root.traverse_mut().unwrap();
+		assert_eq!(
+			traverse.turn_right().err(),
+			Some(TraverseError::NoRightChild)
+		);
+	}
+}
@@ -0,0 +1,360 @@
+//! A price-level order book that aggregates the quantity of all orders at the same price.
+//!
+//! The book keeps one [`AATreeMap`] per side and caches the best level of both sides, so
+//! that [`best_bid`](PriceLevelBook::best_bid) and [`best_ask`](PriceLevelBook::best_ask)
+//! never touch the trees. Matching consumes levels in price order using
+//! [`pop_first`](AATreeMap::pop_first) and [`pop_last`](AATreeMap::pop_last).
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::orderbook::{PriceLevelBook, Side};
+//!
+//! let mut book = PriceLevelBook::new();
+//! book.insert_order(Side::Bid, 99, 10);
+//! book.insert_order(Side::Bid, 98, 5);
+//! book.insert_order(Side::Ask, 101, 4);
+//! book.insert_order(Side::Ask, 102, 8);
+//! assert_eq!(book.best_bid(), Some((99, 10)));
+//! assert_eq!(book.best_ask(), Some((101, 4)));
+//!
+//! // a market buy order for 6 takes all of 101 and 2 from 102
+//! assert_eq!(book.match_against(Side::Bid, 6), [(101, 4), (102, 2)]);
+//! assert_eq!(book.best_ask(), Some((102, 6)));
+//! ```
+
+use crate::AATreeMap;
+use alloc::vec::Vec;
+use core::{
+	cmp,
+	ops::{Add, RangeBounds, Sub}
+};
+
+/// The side of an order.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Side {
+	/// A buy order. The best bid is the one with the highest price.
+	Bid,
+	/// A sell order. The best ask is the one with the lowest price.
+	Ask
+}
+
+impl Side {
+	/// Returns the other side.
+	pub fn opposite(self) -> Self {
+		match self {
+			Self::Bid => Self::Ask,
+			Self::Ask => Self::Bid
+		}
+	}
+
+	/// Returns `true` if `a` is a strictly better price than `b` on this side.
+	fn is_better<P: Ord>(self, a: &P, b: &P) -> bool {
+		match self {
+			Self::Bid => a > b,
+			Self::Ask => a < b
+		}
+	}
+}
+
+/// An order book that stores the total quantity of every price level of both sides.
+///
+/// Prices and quantities are expected to be small copyable types like integers or
+/// fixed-point numbers. The [`Default`] value of the quantity type is treated as zero;
+/// levels are removed as soon as their quantity drops to zero.
+///
+/// Inserting an order never matches it against the other side, even if its price
+/// crosses the spread. Call [`match_against`](Self::match_against) first to execute the
+/// marketable part of an order.
+#[derive(Clone, Debug)]
+pub struct PriceLevelBook<P, Q> {
+	bids: AATreeMap<P, Q>,
+	asks: AATreeMap<P, Q>,
+	best_bid: Option<(P, Q)>,
+	best_ask: Option<(P, Q)>
+}
+
+impl<P, Q> Default for PriceLevelBook<P, Q> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<P, Q> PriceLevelBook<P, Q> {
+	/// Construct a new, empty book.
+	pub const fn new() -> Self {
+		Self {
+			bids: AATreeMap::new(),
+			asks: AATreeMap::new(),
+			best_bid: None,
+			best_ask: None
+		}
+	}
+
+	/// Returns the number of price levels on the given side.
+	pub fn level_count(&self, side: Side) -> usize {
+		self.side(side).len()
+	}
+
+	/// Returns `true` if neither side contains any levels.
+	pub fn is_empty(&self) -> bool {
+		self.bids.is_empty() && self.asks.is_empty()
+	}
+
+	/// Removes all levels from both sides.
+	pub fn clear(&mut self) {
+		self.bids.clear();
+		self.asks.clear();
+		self.best_bid = None;
+		self.best_ask = None;
+	}
+
+	/// Creates an iterator over all bid levels, starting with the highest price.
+	pub fn bids(&self) -> impl Iterator<Item = (&P, &Q)> {
+		self.bids.iter_descending()
+	}
+
+	/// Creates an iterator over all ask levels, starting with the lowest price.
+	pub fn asks(&self) -> impl Iterator<Item = (&P, &Q)> {
+		self.asks.iter()
+	}
+
+	fn side(&self, side: Side) -> &AATreeMap<P, Q> {
+		match side {
+			Side::Bid => &self.bids,
+			Side::Ask => &self.asks
+		}
+	}
+
+	fn side_mut(&mut self, side: Side) -> (&mut AATreeMap<P, Q>, &mut Option<(P, Q)>) {
+		match side {
+			Side::Bid => (&mut self.bids, &mut self.best_bid),
+			Side::Ask => (&mut self.asks, &mut self.best_ask)
+		}
+	}
+}
+
+impl<P, Q> PriceLevelBook<P, Q>
+where
+	P: Ord + Copy,
+	Q: Ord + Copy + Default + Add<Output = Q> + Sub<Output = Q>
+{
+	/// Returns the bid level with the highest price and its quantity. This does not
+	/// access the underlying tree.
+	pub fn best_bid(&self) -> Option<(P, Q)> {
+		self.best_bid
+	}
+
+	/// Returns the ask level with the lowest price and its quantity. This does not
+	/// access the underlying tree.
+	pub fn best_ask(&self) -> Option<(P, Q)> {
+		self.best_ask
+	}
+
+	/// Returns the total quantity at the given price, or zero if there is no such level.
+	pub fn level(&self, side: Side, price: &P) -> Q {
+		self.side(side).get(price).copied().unwrap_or_default()
+	}
+
+	/// Returns the total quantity of all levels on the given side whose price lies in
+	/// `range`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::orderbook::{PriceLevelBook, Side};
+	/// let mut book = PriceLevelBook::new();
+	/// book.insert_order(Side::Ask, 101, 4);
+	/// book.insert_order(Side::Ask, 102, 8);
+	/// book.insert_order(Side::Ask, 105, 1);
+	/// assert_eq!(book.depth(Side::Ask, ..= 102), 12);
+	/// assert_eq!(book.depth(Side::Ask, 102 ..), 9);
+	/// ```
+	pub fn depth<R: RangeBounds<P>>(&self, side: Side, range: R) -> Q {
+		self.side(side)
+			.range(range)
+			.fold(Q::default(), |total, (_, qty)| total + *qty)
+	}
+
+	/// Add `qty` to the level at `price`, creating the level if necessary, and return the
+	/// new total quantity of the level. Adding a zero quantity does not create a level.
+	pub fn insert_order(&mut self, side: Side, price: P, qty: Q) -> Q {
+		let zero = Q::default();
+		let (levels, best) = self.side_mut(side);
+		if qty == zero {
+			return levels.get(&price).copied().unwrap_or(zero);
+		}
+
+		let level = levels.entry(price).or_insert(zero);
+		*level = *level + qty;
+		let total = *level;
+		match best {
+			Some((best_price, _)) if side.is_better(best_price, &price) => {},
+			_ => *best = Some((price, total))
+		}
+		total
+	}
+
+	/// Remove up to `qty` from the level at `price` and return the quantity that was
+	/// actually removed. The level is removed once its quantity reaches zero.
+	pub fn remove_order(&mut self, side: Side, price: &P, qty: Q) -> Q {
+		let zero = Q::default();
+		let (levels, best) = self.side_mut(side);
+		let level = match levels.get_mut(price) {
+			Some(level) => level,
+			None => return zero
+		};
+		let removed = cmp::min(qty, *level);
+		*level = *level - removed;
+		let remaining = *level;
+		if remaining == zero {
+			levels.remove(price);
+		}
+
+		if best.map_or(false, |(best_price, _)| best_price == *price) {
+			*best = if remaining == zero {
+				best_level(levels, side)
+			} else {
+				Some((*price, remaining))
+			};
+		}
+		removed
+	}
+
+	/// Match an incoming order for `qty` from the `taker` side against the levels of the
+	/// opposite side, starting with the best price. Returns the price and quantity of
+	/// every level that was (partially) consumed, in the order they were consumed. The
+	/// unfilled quantity is `qty` minus the sum of all fills.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::orderbook::{PriceLevelBook, Side};
+	/// let mut book = PriceLevelBook::new();
+	/// book.insert_order(Side::Bid, 99, 10);
+	/// book.insert_order(Side::Bid, 98, 5);
+	/// // a market sell order for 20 clears the bid side
+	/// assert_eq!(book.match_against(Side::Ask, 20), [(99, 10), (98, 5)]);
+	/// assert_eq!(book.best_bid(), None);
+	/// ```
+	pub fn match_against(&mut self, taker: Side, qty: Q) -> Vec<(P, Q)> {
+		let zero = Q::default();
+		let side = taker.opposite();
+		let (levels, best) = self.side_mut(side);
+		let mut remaining = qty;
+		let mut fills = Vec::new();
+		while remaining > zero {
+			let (price, available) = match *best {
+				Some(level) => level,
+				None => break
+			};
+			if available <= remaining {
+				// the cached best level is always the outermost entry of the tree
+				match side {
+					Side::Bid => levels.pop_last(),
+					Side::Ask => levels.pop_first()
+				};
+				fills.push((price, available));
+				remaining = remaining - available;
+				*best = best_level(levels, side);
+			} else {
+				let left = available - remaining;
+				if let Some(level) = levels.get_mut(&price) {
+					*level = left;
+				}
+				fills.push((price, remaining));
+				remaining = zero;
+				*best = Some((price, left));
+			}
+		}
+		fills
+	}
+}
+
+/// Look up the best level of one side in the tree.
+fn best_level<P: Ord + Copy, Q: Copy>(
+	levels: &AATreeMap<P, Q>,
+	side: Side
+) -> Option<(P, Q)> {
+	let level = match side {
+		Side::Bid => levels.last_key_value(),
+		Side::Ask => levels.first_key_value()
+	};
+	level.map(|(price, qty)| (*price, *qty))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{PriceLevelBook, Side};
+	use alloc::vec::Vec;
+
+	fn levels<'a>(iter: impl Iterator<Item = (&'a u32, &'a u32)>) -> Vec<(u32, u32)> {
+		iter.map(|(price, qty)| (*price, *qty)).collect()
+	}
+
+	#[test]
+	fn test_insert_remove() {
+		let mut book = PriceLevelBook::new();
+		assert!(book.is_empty());
+		assert_eq!(book.insert_order(Side::Bid, 10, 5), 5);
+		assert_eq!(book.insert_order(Side::Bid, 12, 1), 1);
+		assert_eq!(book.insert_order(Side::Bid, 12, 2), 3);
+		assert_eq!(book.insert_order(Side::Bid, 11, 0), 0);
+		assert_eq!(book.best_bid(), Some((12, 3)));
+		assert_eq!(book.best_ask(), None);
+		assert_eq!(book.level_count(Side::Bid), 2);
+		assert_eq!(levels(book.bids()), [(12, 3), (10, 5)]);
+
+		assert_eq!(book.remove_order(Side::Bid, &12, 1), 1);
+		assert_eq!(book.best_bid(), Some((12, 2)));
+		assert_eq!(book.remove_order(Side::Bid, &12, 5), 2);
+		assert_eq!(book.best_bid(), Some((10, 5)));
+		assert_eq!(book.remove_order(Side::Bid, &12, 5), 0);
+		assert_eq!(book.remove_order(Side::Bid, &10, 2), 2);
+		assert_eq!(book.best_bid(), Some((10, 3)));
+		assert_eq!(book.level(Side::Bid, &10), 3);
+		assert_eq!(book.level(Side::Ask, &10), 0);
+
+		book.insert_order(Side::Ask, 20, 1);
+		book.insert_order(Side::Ask, 15, 1);
+		book.insert_order(Side::Ask, 17, 1);
+		assert_eq!(book.best_ask(), Some((15, 1)));
+		assert_eq!(book.remove_order(Side::Ask, &17, 1), 1);
+		assert_eq!(book.best_ask(), Some((15, 1)));
+		assert_eq!(levels(book.asks()), [(15, 1), (20, 1)]);
+
+		book.clear();
+		assert!(book.is_empty());
+		assert_eq!(book.best_bid(), None);
+		assert_eq!(book.best_ask(), None);
+	}
+
+	#[test]
+	fn test_match_against() {
+		let mut book: PriceLevelBook<u32, u32> = PriceLevelBook::new();
+		for price in 1 ..= 5 {
+			book.insert_order(Side::Ask, 100 + price, price);
+			book.insert_order(Side::Bid, 100 - price, price);
+		}
+
+		assert_eq!(book.match_against(Side::Bid, 0), []);
+		assert_eq!(book.match_against(Side::Bid, 4), [
+			(101, 1),
+			(102, 2),
+			(103, 1)
+		]);
+		assert_eq!(book.best_ask(), Some((103, 2)));
+		assert_eq!(book.match_against(Side::Ask, 1), [(99, 1)]);
+		assert_eq!(book.best_bid(), Some((98, 2)));
+		assert_eq!(book.match_against(Side::Bid, 100), [
+			(103, 2),
+			(104, 4),
+			(105, 5)
+		]);
+		assert_eq!(book.best_ask(), None);
+		assert_eq!(book.level_count(Side::Ask), 0);
+		assert_eq!(book.match_against(Side::Bid, 1), []);
+		assert_eq!(book.depth(Side::Bid, ..), 2 + 3 + 4 + 5);
+		assert_eq!(book.depth(Side::Bid, 96 .. 98), 3 + 4);
+	}
+}
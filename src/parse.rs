@@ -0,0 +1,137 @@
+//! Parse sets and maps from simple delimiter-separated strings, e.g. for configuration
+//! through environment variables or command line arguments.
+//!
+//! Every entry is trimmed before being parsed, and empty entries are skipped. This means
+//! that `"1, 2,3,"` is parsed the same as `"1,2,3"`, and an empty string results in an
+//! empty collection. Later entries overwrite earlier entries with the same key.
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::parse::{parse_map, parse_set};
+//!
+//! let set = parse_set::<u32>("3, 1, 2", ",").unwrap();
+//! assert_eq!(set.into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+//!
+//! let map = parse_map::<String, u32>("b=2; a=1", "=", ";").unwrap();
+//! assert_eq!(map.get("a"), Some(&1));
+//! assert_eq!(map.get("b"), Some(&2));
+//! ```
+
+use crate::{AATreeMap, AATreeSet};
+use core::{
+	fmt::{self, Display, Formatter},
+	str::FromStr
+};
+
+/// The error returned when parsing a set or map fails.
+#[derive(Debug)]
+pub struct ParseError {
+	entry: usize,
+	reason: &'static str
+}
+
+impl ParseError {
+	/// Returns the index of the entry that failed to parse. Empty entries are counted,
+	/// too.
+	pub fn entry(&self) -> usize {
+		self.entry
+	}
+}
+
+impl Display for ParseError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "Invalid entry {}: {}", self.entry, self.reason)
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Split `s` at `sep` into trimmed, non-empty entries and their index.
+fn entries<'a>(s: &'a str, sep: &'a str) -> impl Iterator<Item = (usize, &'a str)> {
+	s.split(sep)
+		.map(str::trim)
+		.enumerate()
+		.filter(|(_, entry)| !entry.is_empty())
+}
+
+/// Parse a set from a string of elements separated by `sep`.
+pub fn parse_set<T>(s: &str, sep: &str) -> Result<AATreeSet<T>, ParseError>
+where
+	T: FromStr + Ord
+{
+	entries(s, sep)
+		.map(|(entry, value)| {
+			value.parse().map_err(|_| ParseError {
+				entry,
+				reason: "invalid element"
+			})
+		})
+		.collect()
+}
+
+/// Parse a map from a string of entries separated by `entry_sep`, where each entry
+/// consists of a key and a value separated by `kv_sep`.
+pub fn parse_map<K, V>(
+	s: &str,
+	kv_sep: &str,
+	entry_sep: &str
+) -> Result<AATreeMap<K, V>, ParseError>
+where
+	K: FromStr + Ord,
+	V: FromStr
+{
+	entries(s, entry_sep)
+		.map(|(entry, kv)| {
+			let err = |reason| ParseError { entry, reason };
+			let (key, value) = kv
+				.split_once(kv_sep)
+				.ok_or_else(|| err("missing key-value separator"))?;
+			let key = key.trim().parse().map_err(|_| err("invalid key"))?;
+			let value = value.trim().parse().map_err(|_| err("invalid value"))?;
+			Ok((key, value))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{parse_map, parse_set};
+	use crate::{AATreeMap, AATreeSet};
+	use alloc::string::ToString;
+
+	#[test]
+	fn test_parse_set() {
+		assert_eq!(
+			parse_set("2,1,3,1", ",").unwrap(),
+			AATreeSet::from([1u8, 2, 3])
+		);
+		assert_eq!(
+			parse_set(" 2 ;; 1; ", ";").unwrap(),
+			AATreeSet::from([1u8, 2])
+		);
+		assert!(parse_set::<u8>("", ",").unwrap().is_empty());
+
+		let err = parse_set::<u8>("1,,x", ",").unwrap_err();
+		assert_eq!(err.entry(), 2);
+		assert_eq!(err.to_string(), "Invalid entry 2: invalid element");
+	}
+
+	#[test]
+	fn test_parse_map() {
+		assert_eq!(
+			parse_map("a=1; b = 2; a=3", "=", ";").unwrap(),
+			AATreeMap::from([('a', 3u8), ('b', 2)])
+		);
+		assert_eq!(
+			parse_map::<u8, u8>("1=>2", "=>", ",").unwrap(),
+			AATreeMap::from([(1, 2)])
+		);
+
+		let err = |s| parse_map::<char, u8>(s, "=", ";").unwrap_err().to_string();
+		assert_eq!(err("a=1;b"), "Invalid entry 1: missing key-value separator");
+		assert_eq!(err("ab=1"), "Invalid entry 0: invalid key");
+		assert_eq!(err("a=-1"), "Invalid entry 0: invalid value");
+	}
+}
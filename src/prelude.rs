@@ -0,0 +1,24 @@
+//! Re-exports of the most commonly used types and traits of this crate.
+//!
+//! ```rust
+//! use aatree::prelude::*;
+//!
+//! let mut map: AATreeMap<u32, &str> = AATreeMap::new();
+//! match map.entry(1) {
+//! 	Entry::Vacant(entry) => entry.insert("a"),
+//! 	Entry::Occupied(entry) => entry.into_mut()
+//! };
+//! let set: AATreeSet<u32> = map.keys().copied().collect();
+//! assert_eq!(OrderedSet::len(&set), 1);
+//! ```
+
+pub use crate::{
+	iter::{AAIntoIter, AAIter, AARevIter},
+	map::{
+		AATimestampMap, AATreeMap, BoxedAATreeMap, Entry, EntryRef, OccupiedEntry,
+		VacantEntry, VacantEntryRef
+	},
+	set::AATreeSet,
+	sorted::{FromSortedIterator, SortedIterator},
+	traits::{OrderedMap, OrderedSet}
+};
@@ -0,0 +1,395 @@
+//! Bound queries on trees and sorted slices.
+//!
+//! All queries in this module take a predicate instead of a key, so that they can be used
+//! with arbitrary tree contents. The tree (or slice) must be partitioned by the predicate:
+//! For [`lower_bound`], `is_before` must return `true` for a prefix of all elements in
+//! ascending order and `false` for the rest. For [`upper_bound`], `is_after` must return
+//! `false` for a prefix and `true` for the rest.
+//!
+//! The bound queries of [`AATreeSet`](crate::AATreeSet) and
+//! [`AATreeMap`](crate::AATreeMap) are implemented using these primitives.
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::{aanode, search};
+//!
+//! let root = aanode!(20 => [2, 10, 30]);
+//! assert_eq!(search::lower_bound(&root, |x| *x < 15), Some(&20));
+//! assert_eq!(search::upper_bound(&root, |x| *x > 15), Some(&10));
+//! assert_eq!(search::lower_bound(&root, |x| *x < 35), None);
+//! ```
+
+use crate::{
+	key::Successor,
+	node::{AANode, Node}
+};
+use core::{borrow::Borrow, ops::Bound};
+
+/// Returns the first element of the tree for which `is_before` returns `false`.
+pub fn lower_bound<T, F>(root: &AANode<T>, mut is_before: F) -> Option<&T>
+where
+	F: FnMut(&T) -> bool
+{
+	let mut candidate = None;
+	let mut node = root.as_ref();
+	while let Some(n) = node {
+		node = if is_before(&n.content) {
+			n.right_child.as_ref()
+		} else {
+			candidate = Some(&n.content);
+			n.left_child.as_ref()
+		};
+	}
+	candidate
+}
+
+/// Returns the last element of the tree for which `is_after` returns `false`.
+pub fn upper_bound<T, F>(root: &AANode<T>, mut is_after: F) -> Option<&T>
+where
+	F: FnMut(&T) -> bool
+{
+	let mut candidate = None;
+	let mut node = root.as_ref();
+	while let Some(n) = node {
+		node = if is_after(&n.content) {
+			n.left_child.as_ref()
+		} else {
+			candidate = Some(&n.content);
+			n.right_child.as_ref()
+		};
+	}
+	candidate
+}
+
+/// Returns a mutable reference to the first element of the tree for which `is_before`
+/// returns `false`.
+///
+/// Modifying the element such that its ordering relative to the other elements changes
+/// is a logic error.
+pub fn lower_bound_mut<T, F>(root: &mut AANode<T>, mut is_before: F) -> Option<&mut T>
+where
+	F: FnMut(&T) -> bool
+{
+	fn search<'a, T, F>(node: &'a mut AANode<T>, is_before: &mut F) -> Option<&'a mut T>
+	where
+		F: FnMut(&T) -> bool
+	{
+		let Node {
+			content,
+			left_child,
+			right_child,
+			..
+		} = node.as_mut()?;
+		if is_before(content) {
+			search(right_child, is_before)
+		} else {
+			search(left_child, is_before).or(Some(content))
+		}
+	}
+	search(root, &mut is_before)
+}
+
+/// Returns a mutable reference to the last element of the tree for which `is_after`
+/// returns `false`.
+///
+/// Modifying the element such that its ordering relative to the other elements changes
+/// is a logic error.
+pub fn upper_bound_mut<T, F>(root: &mut AANode<T>, mut is_after: F) -> Option<&mut T>
+where
+	F: FnMut(&T) -> bool
+{
+	fn search<'a, T, F>(node: &'a mut AANode<T>, is_after: &mut F) -> Option<&'a mut T>
+	where
+		F: FnMut(&T) -> bool
+	{
+		let Node {
+			content,
+			left_child,
+			right_child,
+			..
+		} = node.as_mut()?;
+		if is_after(content) {
+			search(left_child, is_after)
+		} else {
+			search(right_child, is_after).or(Some(content))
+		}
+	}
+	search(root, &mut is_after)
+}
+
+/// Returns `true` if `key` is before the range starting at `bound`.
+pub(crate) fn before_start<K, Q>(key: &K, bound: Bound<&Q>) -> bool
+where
+	K: Borrow<Q> + ?Sized,
+	Q: Ord + ?Sized
+{
+	match bound {
+		Bound::Included(start) => key.borrow() < start,
+		Bound::Excluded(start) => key.borrow() <= start,
+		Bound::Unbounded => false
+	}
+}
+
+/// Returns `true` if `key` is after the range ending at `bound`.
+pub(crate) fn after_end<K, Q>(key: &K, bound: Bound<&Q>) -> bool
+where
+	K: Borrow<Q> + ?Sized,
+	Q: Ord + ?Sized
+{
+	match bound {
+		Bound::Included(end) => key.borrow() > end,
+		Bound::Excluded(end) => key.borrow() >= end,
+		Bound::Unbounded => false
+	}
+}
+
+/// The result of [`estimate_rank`]: Bounds and an estimate of the number of elements
+/// before some position in a tree.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RankEstimate {
+	min: usize,
+	max: usize,
+	estimate: usize
+}
+
+impl RankEstimate {
+	/// Returns the smallest possible rank.
+	pub fn min(&self) -> usize {
+		self.min
+	}
+
+	/// Returns the largest possible rank.
+	pub fn max(&self) -> usize {
+		self.max
+	}
+
+	/// Returns an estimate of the rank between [`min`](Self::min) and
+	/// [`max`](Self::max).
+	pub fn estimate(&self) -> usize {
+		self.estimate
+	}
+}
+
+/// Returns the minimum and maximum number of nodes in a valid subtree whose root has
+/// level `level`. Every level contains at least one and at most two nodes on every
+/// path, so the tree branches at least two-way and at most three-way per level.
+fn subtree_size_bounds(level: u8) -> (usize, usize) {
+	let level = level as u32;
+	(
+		2usize.saturating_pow(level) - 1,
+		3usize.saturating_pow(level) - 1
+	)
+}
+
+/// Estimates the number of elements for which `is_before` returns `true`, i.e. the rank
+/// of the first element for which it returns `false`, in a tree with `len` elements. The
+/// tree must be partitioned by `is_before` as described in the [module
+/// documentation](self).
+///
+/// The tree does not store the sizes of its subtrees, so the exact rank cannot be
+/// computed without visiting all elements before it. Instead, this walks down a single
+/// path in O(log n) and bounds the size of every subtree that is skipped on the way by
+/// its level: A subtree whose root has level `l` contains between `2^l - 1` and
+/// `3^l - 1` elements. The estimate splits the elements that are not accounted for by
+/// the lower bounds proportionally between both sides of the path.
+///
+/// The bounds are guaranteed to contain the exact rank for any valid AA tree. They are
+/// tight for trees that were built in bulk, and get wider the more the tree deviates
+/// from a perfectly balanced binary tree.
+pub fn estimate_rank<T, F>(root: &AANode<T>, len: usize, mut is_before: F) -> RankEstimate
+where
+	F: FnMut(&T) -> bool
+{
+	let (mut before_min, mut before_max) = (0usize, 0usize);
+	let (mut after_min, mut after_max) = (0usize, 0usize);
+	let mut node = root.as_ref();
+	while let Some(n) = node {
+		node = if is_before(&n.content) {
+			let (min, max) = subtree_size_bounds(n.left_child.level());
+			before_min = before_min.saturating_add(min + 1);
+			before_max = before_max.saturating_add(max).saturating_add(1);
+			n.right_child.as_ref()
+		} else {
+			let (min, max) = subtree_size_bounds(n.right_child.level());
+			after_min = after_min.saturating_add(min + 1);
+			after_max = after_max.saturating_add(max).saturating_add(1);
+			n.left_child.as_ref()
+		};
+	}
+
+	let min = before_min.max(len.saturating_sub(after_max));
+	let max = before_max.min(len.saturating_sub(after_min)).max(min);
+	let known = before_min as u128 + after_min as u128;
+	let estimate = match known {
+		0 => 0,
+		known => (len as u128 * before_min as u128 / known) as usize
+	};
+	RankEstimate {
+		min,
+		max,
+		estimate: estimate.max(min).min(max)
+	}
+}
+
+/// Returns the smallest key at or after `start` that is not part of the tree, or `None`
+/// if all keys from `start` up to the largest possible key are taken.
+///
+/// The tree is walked in order, starting at the first key at or after `start`, until a
+/// gap is found. This takes O(log n + r) time, where r is the length of the run of
+/// consecutive keys starting at `start`.
+pub(crate) fn next_free<T, K, F>(root: &AANode<T>, start: &K, key: F) -> Option<K>
+where
+	K: Successor + Clone,
+	F: Fn(&T) -> &K
+{
+	enum Flow {
+		Continue,
+		Found,
+		Overflow
+	}
+
+	fn walk<T, K, F>(node: &AANode<T>, candidate: &mut K, key: &F) -> Flow
+	where
+		K: Successor,
+		F: Fn(&T) -> &K
+	{
+		let node = match node.as_ref() {
+			Some(node) => node,
+			None => return Flow::Continue
+		};
+		let node_key = key(&node.content);
+		if *node_key >= *candidate {
+			match walk(&node.left_child, candidate, key) {
+				Flow::Continue => {},
+				flow => return flow
+			}
+			if *node_key > *candidate {
+				return Flow::Found;
+			}
+			*candidate = match candidate.successor() {
+				Some(next) => next,
+				None => return Flow::Overflow
+			};
+		}
+		walk(&node.right_child, candidate, key)
+	}
+
+	let mut candidate = start.clone();
+	match walk(root, &mut candidate, &key) {
+		Flow::Continue | Flow::Found => Some(candidate),
+		Flow::Overflow => None
+	}
+}
+
+/// Returns the index of the first element of `slice` for which `is_less` returns
+/// `false`, or the length of the slice if there is no such element. `slice` must be
+/// partitioned such that `is_less` returns `true` for all elements before that index.
+///
+/// Unlike [`slice::binary_search_by`], the loop does not branch on the result of the
+/// comparison, so it compiles to conditional moves and does not suffer from branch
+/// mispredictions. It always performs ⌈log₂(n)⌉ + 1 comparisons.
+pub(crate) fn lower_bound_by<T, F>(slice: &[T], mut is_less: F) -> usize
+where
+	F: FnMut(&T) -> bool
+{
+	let mut size = slice.len();
+	if size == 0 {
+		return 0;
+	}
+	let mut base = 0;
+	while size > 1 {
+		let half = size / 2;
+		let mid = base + half;
+		base = if is_less(&slice[mid]) { mid } else { base };
+		size -= half;
+	}
+	base + is_less(&slice[base]) as usize
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		estimate_rank, lower_bound, lower_bound_by, lower_bound_mut, upper_bound,
+		upper_bound_mut
+	};
+	use crate::node::AANode;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_lower_bound() {
+		for len in 0 .. 40 {
+			let slice: Vec<u32> = (0 .. len).map(|x| x * 2).collect();
+			for key in 0 .. len * 2 + 2 {
+				assert_eq!(
+					lower_bound_by(&slice, |x| *x < key),
+					slice.partition_point(|x| *x < key),
+					"len={len} key={key}"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn test_tree_bounds() {
+		for len in 0 .. 40 {
+			let slice: Vec<u32> = (0 .. len).map(|x| x * 2).collect();
+			let mut root = AANode::new();
+			for x in &slice {
+				root.insert(*x);
+			}
+			for key in 0 .. len * 2 + 2 {
+				let lower = slice.iter().find(|x| **x >= key);
+				let upper = slice.iter().rev().find(|x| **x <= key);
+				assert_eq!(lower_bound(&root, |x| *x < key), lower);
+				assert_eq!(upper_bound(&root, |x| *x > key), upper);
+				assert_eq!(
+					lower_bound_mut(&mut root, |x| *x < key).copied(),
+					lower.copied()
+				);
+				assert_eq!(
+					upper_bound_mut(&mut root, |x| *x > key).copied(),
+					upper.copied()
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn test_estimate_rank() {
+		let mut sequential = AANode::new();
+		let mut random = AANode::new();
+		let mut len = 0;
+		let mut state = 7u32;
+		for x in 0 .. 2000u32 {
+			sequential.insert(x * 2);
+			state = state.wrapping_mul(1103515245).wrapping_add(12345);
+			len += random.insert(state % 4000) as usize;
+		}
+		let mut random_keys: Vec<u32> = Vec::new();
+		for x in 0 .. 4000 {
+			if lower_bound(&random, |y| *y < x) == Some(&x) {
+				random_keys.push(x);
+			}
+		}
+		assert_eq!(random_keys.len(), len);
+
+		for key in 0 .. 4001 {
+			for (root, len, exact) in [
+				(&sequential, 2000, ((key + 1) / 2) as usize),
+				(&random, len, random_keys.partition_point(|x| *x < key))
+			] {
+				let estimate = estimate_rank(root, len, |x| *x < key);
+				assert!(
+					estimate.min() <= exact && exact <= estimate.max(),
+					"key={key} exact={exact} {estimate:?}"
+				);
+				assert!(estimate.min() <= estimate.estimate());
+				assert!(estimate.estimate() <= estimate.max());
+			}
+		}
+
+		let empty: AANode<u32> = AANode::new();
+		assert_eq!(estimate_rank(&empty, 0, |x| *x < 5).max(), 0);
+	}
+}
@@ -0,0 +1,397 @@
+//! A self-describing buffer for arbitrary serde data. This allows deserializing a value
+//! from the buffer and recovering from errors without leaving the original deserializer
+//! in an inconsistent state.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
+	fmt::{self, Formatter},
+	marker::PhantomData
+};
+use serde::{
+	de::{
+		self,
+		value::{MapDeserializer, SeqDeserializer},
+		Deserialize, Deserializer, IntoDeserializer, Unexpected
+	},
+	forward_to_deserialize_any
+};
+
+#[derive(Debug)]
+pub(super) enum Content {
+	Bool(bool),
+	U64(u64),
+	I64(i64),
+	F64(f64),
+	Char(char),
+	String(String),
+	Bytes(Vec<u8>),
+	None,
+	Some(Box<Content>),
+	Unit,
+	Newtype(Box<Content>),
+	Seq(Vec<Content>),
+	Map(Vec<(Content, Content)>)
+}
+
+impl Content {
+	fn unexpected(&self) -> Unexpected<'_> {
+		match self {
+			Self::Bool(b) => Unexpected::Bool(*b),
+			Self::U64(n) => Unexpected::Unsigned(*n),
+			Self::I64(n) => Unexpected::Signed(*n),
+			Self::F64(n) => Unexpected::Float(*n),
+			Self::Char(c) => Unexpected::Char(*c),
+			Self::String(s) => Unexpected::Str(s),
+			Self::Bytes(b) => Unexpected::Bytes(b),
+			Self::None | Self::Some(_) => Unexpected::Option,
+			Self::Unit => Unexpected::Unit,
+			Self::Newtype(_) => Unexpected::NewtypeStruct,
+			Self::Seq(_) => Unexpected::Seq,
+			Self::Map(_) => Unexpected::Map
+		}
+	}
+}
+
+struct ContentVisitor;
+
+impl<'de> de::Visitor<'de> for ContentVisitor {
+	type Value = Content;
+
+	fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("any value")
+	}
+
+	fn visit_bool<E>(self, v: bool) -> Result<Content, E> {
+		Ok(Content::Bool(v))
+	}
+
+	fn visit_i64<E>(self, v: i64) -> Result<Content, E> {
+		Ok(Content::I64(v))
+	}
+
+	fn visit_u64<E>(self, v: u64) -> Result<Content, E> {
+		Ok(Content::U64(v))
+	}
+
+	fn visit_f64<E>(self, v: f64) -> Result<Content, E> {
+		Ok(Content::F64(v))
+	}
+
+	fn visit_char<E>(self, v: char) -> Result<Content, E> {
+		Ok(Content::Char(v))
+	}
+
+	fn visit_str<E>(self, v: &str) -> Result<Content, E> {
+		Ok(Content::String(v.into()))
+	}
+
+	fn visit_string<E>(self, v: String) -> Result<Content, E> {
+		Ok(Content::String(v))
+	}
+
+	fn visit_bytes<E>(self, v: &[u8]) -> Result<Content, E> {
+		Ok(Content::Bytes(v.into()))
+	}
+
+	fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Content, E> {
+		Ok(Content::Bytes(v))
+	}
+
+	fn visit_none<E>(self) -> Result<Content, E> {
+		Ok(Content::None)
+	}
+
+	fn visit_some<D>(self, deserializer: D) -> Result<Content, D::Error>
+	where
+		D: Deserializer<'de>
+	{
+		Ok(Content::Some(Box::new(Content::deserialize(deserializer)?)))
+	}
+
+	fn visit_unit<E>(self) -> Result<Content, E> {
+		Ok(Content::Unit)
+	}
+
+	fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Content, D::Error>
+	where
+		D: Deserializer<'de>
+	{
+		Ok(Content::Newtype(Box::new(Content::deserialize(
+			deserializer
+		)?)))
+	}
+
+	fn visit_seq<A>(self, mut acc: A) -> Result<Content, A::Error>
+	where
+		A: de::SeqAccess<'de>
+	{
+		let mut seq = Vec::new();
+		while let Some(next) = acc.next_element()? {
+			seq.push(next);
+		}
+		Ok(Content::Seq(seq))
+	}
+
+	fn visit_map<A>(self, mut acc: A) -> Result<Content, A::Error>
+	where
+		A: de::MapAccess<'de>
+	{
+		let mut map = Vec::new();
+		while let Some(entry) = acc.next_entry()? {
+			map.push(entry);
+		}
+		Ok(Content::Map(map))
+	}
+}
+
+impl<'de> Deserialize<'de> for Content {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>
+	{
+		deserializer.deserialize_any(ContentVisitor)
+	}
+}
+
+/// Deserializes a value from a [`Content`] buffer.
+///
+/// Map keys are usually strings in self-describing formats, even if they represent
+/// numbers. Therefore, if `is_key` is set, strings are parsed when a number or boolean is
+/// requested.
+pub(super) struct ContentDeserializer<E> {
+	content: Content,
+	is_key: bool,
+	_err: PhantomData<E>
+}
+
+impl<E> ContentDeserializer<E> {
+	pub(super) fn new(content: Content, is_key: bool) -> Self {
+		Self {
+			content,
+			is_key,
+			_err: PhantomData
+		}
+	}
+}
+
+impl<'de, E: de::Error> IntoDeserializer<'de, E> for Content {
+	type Deserializer = ContentDeserializer<E>;
+
+	fn into_deserializer(self) -> Self::Deserializer {
+		ContentDeserializer::new(self, false)
+	}
+}
+
+impl<'de, E: de::Error> IntoDeserializer<'de, E> for ContentDeserializer<E> {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self {
+		self
+	}
+}
+
+macro_rules! deserialize_parsed_key {
+	($($method:ident => $visit:ident: $ty:ty),*) => {
+		$(
+			fn $method<V>(self, visitor: V) -> Result<V::Value, E>
+			where
+				V: de::Visitor<'de>
+			{
+				match self.content {
+					Content::String(s) if self.is_key => match s.parse::<$ty>() {
+						Ok(v) => visitor.$visit(v),
+						Err(_) => Err(E::invalid_value(Unexpected::Str(&s), &visitor))
+					},
+					content => ContentDeserializer::new(content, false).deserialize_any(visitor)
+				}
+			}
+		)*
+	};
+}
+
+impl<'de, E: de::Error> Deserializer<'de> for ContentDeserializer<E> {
+	type Error = E;
+
+	fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, E>
+	where
+		V: de::Visitor<'de>
+	{
+		match self.content {
+			Content::Bool(v) => visitor.visit_bool(v),
+			Content::U64(v) => visitor.visit_u64(v),
+			Content::I64(v) => visitor.visit_i64(v),
+			Content::F64(v) => visitor.visit_f64(v),
+			Content::Char(v) => visitor.visit_char(v),
+			Content::String(v) => visitor.visit_string(v),
+			Content::Bytes(v) => visitor.visit_byte_buf(v),
+			Content::None => visitor.visit_none(),
+			Content::Some(v) => visitor.visit_some(v.into_deserializer()),
+			Content::Unit => visitor.visit_unit(),
+			Content::Newtype(v) => visitor.visit_newtype_struct(v.into_deserializer()),
+			Content::Seq(v) => {
+				let mut seq = SeqDeserializer::new(v.into_iter());
+				let value = visitor.visit_seq(&mut seq)?;
+				seq.end()?;
+				Ok(value)
+			},
+			Content::Map(v) => {
+				let mut map = MapDeserializer::new(
+					v.into_iter()
+						.map(|(k, v)| (ContentDeserializer::new(k, true), v))
+				);
+				let value = visitor.visit_map(&mut map)?;
+				map.end()?;
+				Ok(value)
+			}
+		}
+	}
+
+	fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, E>
+	where
+		V: de::Visitor<'de>
+	{
+		match self.content {
+			Content::None | Content::Unit => visitor.visit_none(),
+			Content::Some(v) => visitor.visit_some(v.into_deserializer()),
+			content => visitor.visit_some(ContentDeserializer::new(content, self.is_key))
+		}
+	}
+
+	fn deserialize_newtype_struct<V>(
+		self,
+		_name: &'static str,
+		visitor: V
+	) -> Result<V::Value, E>
+	where
+		V: de::Visitor<'de>
+	{
+		match self.content {
+			Content::Newtype(v) => visitor.visit_newtype_struct(v.into_deserializer()),
+			content => visitor
+				.visit_newtype_struct(ContentDeserializer::new(content, self.is_key))
+		}
+	}
+
+	fn deserialize_enum<V>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V
+	) -> Result<V::Value, E>
+	where
+		V: de::Visitor<'de>
+	{
+		let (variant, value) = match self.content {
+			Content::String(variant) => (Content::String(variant), None),
+			Content::Map(map) if map.len() == 1 => {
+				let (variant, value) = map.into_iter().next().unwrap();
+				(variant, Some(value))
+			},
+			content => return Err(E::invalid_type(content.unexpected(), &"an enum"))
+		};
+		visitor.visit_enum(EnumDeserializer {
+			variant,
+			value,
+			_err: PhantomData
+		})
+	}
+
+	deserialize_parsed_key! {
+		deserialize_bool => visit_bool: bool,
+		deserialize_u8 => visit_u8: u8,
+		deserialize_u16 => visit_u16: u16,
+		deserialize_u32 => visit_u32: u32,
+		deserialize_u64 => visit_u64: u64,
+		deserialize_i8 => visit_i8: i8,
+		deserialize_i16 => visit_i16: i16,
+		deserialize_i32 => visit_i32: i32,
+		deserialize_i64 => visit_i64: i64,
+		deserialize_f32 => visit_f32: f32,
+		deserialize_f64 => visit_f64: f64
+	}
+
+	forward_to_deserialize_any! {
+		i128 u128 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+		map struct identifier ignored_any
+	}
+}
+
+struct EnumDeserializer<E> {
+	variant: Content,
+	value: Option<Content>,
+	_err: PhantomData<E>
+}
+
+impl<'de, E: de::Error> de::EnumAccess<'de> for EnumDeserializer<E> {
+	type Error = E;
+	type Variant = VariantDeserializer<E>;
+
+	fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), E>
+	where
+		V: de::DeserializeSeed<'de>
+	{
+		let variant = seed.deserialize(ContentDeserializer::new(self.variant, true))?;
+		Ok((variant, VariantDeserializer {
+			value: self.value,
+			_err: PhantomData
+		}))
+	}
+}
+
+struct VariantDeserializer<E> {
+	value: Option<Content>,
+	_err: PhantomData<E>
+}
+
+impl<'de, E: de::Error> de::VariantAccess<'de> for VariantDeserializer<E> {
+	type Error = E;
+
+	fn unit_variant(self) -> Result<(), E> {
+		match self.value {
+			None | Some(Content::Unit) => Ok(()),
+			Some(content) => Err(E::invalid_type(content.unexpected(), &"unit variant"))
+		}
+	}
+
+	fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, E>
+	where
+		T: de::DeserializeSeed<'de>
+	{
+		match self.value {
+			Some(content) => seed.deserialize(content.into_deserializer()),
+			None => Err(E::invalid_type(Unexpected::UnitVariant, &"newtype variant"))
+		}
+	}
+
+	fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, E>
+	where
+		V: de::Visitor<'de>
+	{
+		match self.value {
+			Some(content @ Content::Seq(_)) => {
+				content.into_deserializer().deserialize_any(visitor)
+			},
+			Some(content) => Err(E::invalid_type(content.unexpected(), &"tuple variant")),
+			None => Err(E::invalid_type(Unexpected::UnitVariant, &"tuple variant"))
+		}
+	}
+
+	fn struct_variant<V>(
+		self,
+		_fields: &'static [&'static str],
+		visitor: V
+	) -> Result<V::Value, E>
+	where
+		V: de::Visitor<'de>
+	{
+		match self.value {
+			Some(content @ (Content::Map(_) | Content::Seq(_))) => {
+				content.into_deserializer().deserialize_any(visitor)
+			},
+			Some(content) => {
+				Err(E::invalid_type(content.unexpected(), &"struct variant"))
+			},
+			None => Err(E::invalid_type(Unexpected::UnitVariant, &"struct variant"))
+		}
+	}
+}
@@ -0,0 +1,198 @@
+use super::content::{Content, ContentDeserializer};
+use crate::{AATreeMap, AATreeSet};
+use alloc::{format, string::String, vec::Vec};
+use core::{
+	fmt::{self, Formatter},
+	marker::PhantomData
+};
+use serde::de::{self, Deserialize, Deserializer};
+
+/// A collection that was deserialized in an error-tolerant way.
+///
+/// Deserializing `Lossy<AATreeMap<K, V>>` or `Lossy<AATreeSet<T>>` skips all entries
+/// that fail to deserialize instead of failing altogether, and records their errors in
+/// [`errors`](Self::errors). Only errors of individual entries are tolerated; if the
+/// input is not a map or sequence at all, deserialization still fails.
+///
+/// Every entry is first deserialized into an intermediate buffer, so that an invalid
+/// entry cannot corrupt the state of the deserializer. This requires a self-describing
+/// format like JSON.
+///
+/// # Example
+///
+/// ```rust
+/// use aatree::{serde::Lossy, AATreeMap};
+///
+/// let json = r#"{"1": "a", "two": "b", "3": 4, "5": "e"}"#;
+/// let lossy: Lossy<AATreeMap<u32, String>> = serde_json::from_str(json).unwrap();
+/// assert_eq!(lossy.value.keys().collect::<Vec<_>>(), [&1, &5]);
+/// assert_eq!(lossy.errors.len(), 2);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Lossy<T> {
+	/// The collection containing all entries that were deserialized successfully.
+	pub value: T,
+	/// The errors of all entries that failed to deserialize, in input order.
+	pub errors: Vec<String>
+}
+
+impl<T> Lossy<T> {
+	/// Returns the collection, discarding all errors.
+	pub fn into_inner(self) -> T {
+		self.value
+	}
+}
+
+fn entry_error<E: fmt::Display>(idx: usize, err: E) -> String {
+	format!("entry {idx}: {err}")
+}
+
+// ### Deserialize Lossy<AATreeSet>
+
+struct LossySetVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for LossySetVisitor<T>
+where
+	T: Deserialize<'de> + Ord
+{
+	type Value = Lossy<AATreeSet<T>>;
+
+	fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("a set")
+	}
+
+	fn visit_seq<A>(self, mut acc: A) -> Result<Self::Value, A::Error>
+	where
+		A: de::SeqAccess<'de>
+	{
+		let mut lossy = Lossy::<AATreeSet<T>>::default();
+		let mut idx = 0;
+		while let Some(next) = acc.next_element::<Content>()? {
+			match T::deserialize(ContentDeserializer::<A::Error>::new(next, false)) {
+				Ok(value) => {
+					lossy.value.insert(value);
+				},
+				Err(err) => lossy.errors.push(entry_error(idx, err))
+			}
+			idx += 1;
+		}
+		Ok(lossy)
+	}
+}
+
+impl<'de, T> Deserialize<'de> for Lossy<AATreeSet<T>>
+where
+	T: Deserialize<'de> + Ord
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>
+	{
+		deserializer.deserialize_seq(LossySetVisitor(PhantomData))
+	}
+}
+
+// ### Deserialize Lossy<AATreeMap>
+
+struct LossyMapVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> de::Visitor<'de> for LossyMapVisitor<K, V>
+where
+	K: Deserialize<'de> + Ord,
+	V: Deserialize<'de>
+{
+	type Value = Lossy<AATreeMap<K, V>>;
+
+	fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("a map")
+	}
+
+	fn visit_map<A>(self, mut acc: A) -> Result<Self::Value, A::Error>
+	where
+		A: de::MapAccess<'de>
+	{
+		let mut lossy = Lossy::<AATreeMap<K, V>>::default();
+		let mut idx = 0;
+		while let Some((key, value)) = acc.next_entry::<Content, Content>()? {
+			let key = K::deserialize(ContentDeserializer::<A::Error>::new(key, true));
+			let value =
+				V::deserialize(ContentDeserializer::<A::Error>::new(value, false));
+			match (key, value) {
+				(Ok(key), Ok(value)) => {
+					lossy.value.insert(key, value);
+				},
+				(Err(err), _) | (_, Err(err)) => lossy.errors.push(entry_error(idx, err))
+			}
+			idx += 1;
+		}
+		Ok(lossy)
+	}
+}
+
+impl<'de, K, V> Deserialize<'de> for Lossy<AATreeMap<K, V>>
+where
+	K: Deserialize<'de> + Ord,
+	V: Deserialize<'de>
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>
+	{
+		deserializer.deserialize_map(LossyMapVisitor(PhantomData))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Lossy;
+	use crate::{AATreeMap, AATreeSet};
+	use alloc::{string::String, vec::Vec};
+	use serde::Deserialize;
+
+	#[derive(Debug, Deserialize, PartialEq)]
+	enum Shape {
+		Empty,
+		Circle(u32),
+		Rect { w: u32, h: u32 }
+	}
+
+	#[derive(Debug, Deserialize, PartialEq)]
+	struct Item {
+		name: String,
+		tags: Vec<String>,
+		shape: Shape,
+		note: Option<String>
+	}
+
+	#[test]
+	fn test_lossy_map() {
+		let json = r#"{
+			"1": {"name": "a", "tags": [], "shape": "Empty", "note": null},
+			"2": {"name": "b", "tags": ["x"], "shape": {"Circle": 3}},
+			"3": {"name": "c", "tags": "oops", "shape": "Empty"},
+			"4": {"name": "d", "tags": [], "shape": {"Rect": {"w": 1, "h": 2}}, "note": "hi"},
+			"-5": {"name": "e", "tags": [], "shape": "Empty"},
+			"6": {"name": "f", "tags": [], "shape": "Triangle"}
+		}"#;
+		let lossy: Lossy<AATreeMap<u8, Item>> = serde_json::from_str(json).unwrap();
+		assert_eq!(lossy.value.keys().copied().collect::<Vec<_>>(), [1, 2, 4]);
+		assert_eq!(lossy.value[&2].shape, Shape::Circle(3));
+		assert_eq!(lossy.value[&4].shape, Shape::Rect { w: 1, h: 2 });
+		assert_eq!(lossy.value[&4].note.as_deref(), Some("hi"));
+		assert_eq!(lossy.errors.len(), 3);
+		assert!(lossy.errors[0].starts_with("entry 2: "));
+	}
+
+	#[test]
+	fn test_lossy_set() {
+		let json = r#"[3, "x", 1, -1, 2, [4]]"#;
+		let lossy: Lossy<AATreeSet<u8>> = serde_json::from_str(json).unwrap();
+		assert_eq!(lossy.value, AATreeSet::from([1, 2, 3]));
+		assert_eq!(lossy.errors.len(), 3);
+	}
+
+	#[test]
+	fn test_lossy_not_a_map() {
+		assert!(serde_json::from_str::<Lossy<AATreeMap<u8, u8>>>("[1, 2]").is_err());
+	}
+}
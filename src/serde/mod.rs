@@ -0,0 +1,298 @@
+//! [`serde`] support for the collections of this crate. Only available with the `serde`
+//! feature.
+//!
+//! Besides implementing [`Serialize`] and [`Deserialize`], this module contains helpers
+//! for customizing how collections are deserialized.
+//!
+//! # Stability
+//!
+//! [`AATreeSet`] is serialized as a sequence and [`AATreeMap`] as a map, both with their
+//! elements in ascending order. This representation is part of the public API and will
+//! not change in a semver-compatible release, so serialized collections can be stored
+//! long-term. Deserialization accepts the elements in any order.
+
+use crate::{AATreeMap, AATreeSet};
+use core::{
+	fmt::{self, Formatter},
+	marker::PhantomData
+};
+use serde::{
+	de::{self, Deserialize, DeserializeSeed, Deserializer, IgnoredAny},
+	ser::{Serialize, Serializer}
+};
+
+mod content;
+mod lossy;
+
+pub use lossy::Lossy;
+
+// ### Deserialize AATreeSet
+
+struct AATreeSetVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for AATreeSetVisitor<T>
+where
+	T: Deserialize<'de> + Ord
+{
+	type Value = AATreeSet<T>;
+
+	fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("a set")
+	}
+
+	fn visit_seq<A>(self, mut acc: A) -> Result<Self::Value, A::Error>
+	where
+		A: de::SeqAccess<'de>
+	{
+		let mut set = AATreeSet::new();
+		while let Some(next) = acc.next_element()? {
+			set.insert(next);
+		}
+		Ok(set)
+	}
+}
+
+impl<'de, T> Deserialize<'de> for AATreeSet<T>
+where
+	T: Deserialize<'de> + Ord
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>
+	{
+		deserializer.deserialize_seq(AATreeSetVisitor(PhantomData))
+	}
+}
+
+// ### Deserialize AATreeMap
+
+struct AATreeMapVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> de::Visitor<'de> for AATreeMapVisitor<K, V>
+where
+	K: Deserialize<'de> + Ord,
+	V: Deserialize<'de>
+{
+	type Value = AATreeMap<K, V>;
+
+	fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("a map")
+	}
+
+	fn visit_map<A>(self, mut acc: A) -> Result<Self::Value, A::Error>
+	where
+		A: de::MapAccess<'de>
+	{
+		let mut map = AATreeMap::new();
+		while let Some((key, value)) = acc.next_entry()? {
+			map.insert(key, value);
+		}
+		Ok(map)
+	}
+}
+
+impl<'de, K, V> Deserialize<'de> for AATreeMap<K, V>
+where
+	K: Deserialize<'de> + Ord,
+	V: Deserialize<'de>
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>
+	{
+		deserializer.deserialize_map(AATreeMapVisitor(PhantomData))
+	}
+}
+
+// ### Deserialize filtered AATreeMap
+
+/// Deserialize an [`AATreeMap`], skipping all entries whose key does not match `pred`.
+///
+/// The values of skipped entries are never deserialized, which avoids loading a huge map
+/// only to [`retain`] a small part of it afterwards. Since `deserialize_with` requires a
+/// function path, wrap the call in a function:
+///
+/// ```rust
+/// use aatree::AATreeMap;
+/// use serde::{de::DeserializeSeed, Deserialize, Deserializer};
+///
+/// fn recent<'de, D>(deserializer: D) -> Result<AATreeMap<u64, String>, D::Error>
+/// where
+/// 	D: Deserializer<'de>
+/// {
+/// 	aatree::serde::filtered(|ts: &u64| *ts >= 1000).deserialize(deserializer)
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Snapshot {
+/// 	#[serde(deserialize_with = "recent")]
+/// 	events: AATreeMap<u64, String>
+/// }
+///
+/// let json = r#"{"events": {"500": "old", "1000": "new", "1500": "newer"}}"#;
+/// let snapshot: Snapshot = serde_json::from_str(json).unwrap();
+/// assert_eq!(snapshot.events.keys().collect::<Vec<_>>(), [&1000, &1500]);
+/// ```
+///
+/// [`retain`]: https://doc.rust-lang.org/std/collections/struct.BTreeMap.html#method.retain
+pub fn filtered<K, V, F>(pred: F) -> Filtered<K, V, F>
+where
+	F: FnMut(&K) -> bool
+{
+	Filtered {
+		pred,
+		_ty: PhantomData
+	}
+}
+
+/// A [`DeserializeSeed`] that deserializes an [`AATreeMap`], skipping all entries whose
+/// key does not match a predicate. See [`filtered`] for details.
+pub struct Filtered<K, V, F> {
+	pred: F,
+	_ty: PhantomData<(K, V)>
+}
+
+impl<K, V, F> fmt::Debug for Filtered<K, V, F> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Filtered").finish_non_exhaustive()
+	}
+}
+
+impl<'de, K, V, F> DeserializeSeed<'de> for Filtered<K, V, F>
+where
+	K: Deserialize<'de> + Ord,
+	V: Deserialize<'de>,
+	F: FnMut(&K) -> bool
+{
+	type Value = AATreeMap<K, V>;
+
+	fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+	where
+		D: Deserializer<'de>
+	{
+		deserializer.deserialize_map(self)
+	}
+}
+
+impl<'de, K, V, F> de::Visitor<'de> for Filtered<K, V, F>
+where
+	K: Deserialize<'de> + Ord,
+	V: Deserialize<'de>,
+	F: FnMut(&K) -> bool
+{
+	type Value = AATreeMap<K, V>;
+
+	fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("a map")
+	}
+
+	fn visit_map<A>(mut self, mut acc: A) -> Result<Self::Value, A::Error>
+	where
+		A: de::MapAccess<'de>
+	{
+		let mut map = AATreeMap::new();
+		while let Some(key) = acc.next_key()? {
+			if (self.pred)(&key) {
+				map.insert(key, acc.next_value()?);
+			} else {
+				acc.next_value::<IgnoredAny>()?;
+			}
+		}
+		Ok(map)
+	}
+}
+
+// ### Serialize AATreeSet
+
+impl<T> Serialize for AATreeSet<T>
+where
+	T: Serialize
+{
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer
+	{
+		serializer.collect_seq(self)
+	}
+}
+
+// ### Serialize AATreeMap
+
+impl<K, V> Serialize for AATreeMap<K, V>
+where
+	K: Serialize,
+	V: Serialize
+{
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer
+	{
+		serializer.collect_map(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{key::TotalF64, AATreeMap, AATreeSet};
+	use core::fmt::Debug;
+	use serde::{
+		de::{DeserializeOwned, DeserializeSeed},
+		Serialize
+	};
+
+	#[track_caller]
+	fn test<T>(value: &T, json: &str)
+	where
+		T: DeserializeOwned + Serialize + Debug + PartialEq
+	{
+		assert_eq!(serde_json::to_string(value).unwrap(), json);
+		assert_eq!(&serde_json::from_str::<T>(json).unwrap(), value);
+	}
+
+	#[test]
+	fn test_set() {
+		let mut set = AATreeSet::new();
+		test(&set, "[]");
+
+		set.insert(5);
+		test(&set, "[5]");
+
+		set.insert(7);
+		test(&set, "[5,7]");
+
+		set.insert(6);
+		test(&set, "[5,6,7]");
+	}
+
+	#[test]
+	fn test_total_float_set() {
+		let set: AATreeSet<TotalF64> =
+			[2.5, -1.0, 0.0].into_iter().map(TotalF64).collect();
+		test(&set, "[-1.0,0.0,2.5]");
+	}
+
+	#[test]
+	fn test_filtered() {
+		let json = r#"{"1":{"a":[1,2]},"2":3,"3":4,"4":[]}"#;
+		let mut deserializer = serde_json::Deserializer::from_str(json);
+		let map: AATreeMap<u32, u32> = super::filtered(|k: &u32| *k == 2 || *k == 3)
+			.deserialize(&mut deserializer)
+			.unwrap();
+		assert_eq!(map, AATreeMap::from([(2, 3), (3, 4)]));
+	}
+
+	#[test]
+	fn test_map() {
+		let mut map = AATreeMap::new();
+		test(&map, "{}");
+
+		map.insert(5, 50);
+		test(&map, r#"{"5":50}"#);
+
+		map.insert(7, 70);
+		test(&map, r#"{"5":50,"7":70}"#);
+
+		map.insert(6, 60);
+		test(&map, r#"{"5":50,"6":60,"7":70}"#);
+	}
+}
@@ -1,14 +1,23 @@
 use crate::{
+	fmt::{DebugTruncated, DisplaySet},
 	iter::{AAIntoIter, AAIter},
-	node::{AANode, TraverseStep}
+	key::Successor,
+	map::{closest, group_fold, DuplicateKeyError, RawRange, Tie},
+	node::{AANode, Keep, ParseCanonicalError, TraverseStep},
+	search::{self, after_end, before_start, RankEstimate},
+	sorted::SortedIterator,
+	AATreeMap
 };
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use core::{
 	borrow::Borrow,
 	cmp::Ordering,
-	fmt::{self, Debug},
-	iter::FromIterator,
-	mem
+	fmt::{self, Debug, Display},
+	hash::{Hash, Hasher},
+	iter::{FromIterator, FusedIterator, Peekable},
+	mem,
+	ops::{Bound, RangeBounds, Sub},
+	str::FromStr
 };
 
 /// A set based on an AA-Tree.
@@ -85,6 +94,17 @@ impl<T: PartialEq> PartialEq for AATreeSet<T> {
 
 impl<T: Eq> Eq for AATreeSet<T> {}
 
+/// The hash only depends on the values of the set in ascending order, so sets with equal
+/// contents hash identically regardless of the order in which they were inserted.
+impl<T: Hash> Hash for AATreeSet<T> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		state.write_usize(self.len);
+		for value in self {
+			value.hash(state);
+		}
+	}
+}
+
 impl<T: PartialOrd> PartialOrd for AATreeSet<T> {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
 		self.iter().partial_cmp(other.iter())
@@ -106,6 +126,39 @@ impl<T> AATreeSet<T> {
 		}
 	}
 
+	/// Build a set from exactly `len` strictly ascending elements in O(n).
+	pub(crate) fn from_sorted_unchecked<I>(iter: I, len: usize) -> Self
+	where
+		I: IntoIterator<Item = T>
+	{
+		Self {
+			root: AANode::from_sorted_iter(&mut iter.into_iter(), len),
+			len
+		}
+	}
+
+	/// Build a set from an iterator that is known to yield strictly ascending elements,
+	/// in O(n) without comparing any elements. Debug builds panic if the elements are
+	/// not strictly ascending.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set: AATreeSet<u32> = (0 .. 10).collect();
+	/// let even = AATreeSet::from_sorted_iter(set.iter().copied().step_by(2));
+	/// assert_eq!(even.into_iter().collect::<Vec<_>>(), [0, 2, 4, 6, 8]);
+	/// ```
+	pub fn from_sorted_iter<I>(iter: I) -> Self
+	where
+		T: Ord,
+		I: IntoIterator<Item = T>,
+		I::IntoIter: SortedIterator
+	{
+		let (root, len) = AANode::from_sorted_items(iter.into_iter());
+		Self { root, len }
+	}
+
 	/// Returns the number of elements in the set.
 	pub fn len(&self) -> usize {
 		self.len
@@ -116,6 +169,34 @@ impl<T> AATreeSet<T> {
 		self.len == 0
 	}
 
+	/// Returns the level of the root of the tree, or `0` if the set is empty. This takes
+	/// O(1) time.
+	///
+	/// The level of the root never exceeds `log2(n + 1)` for a set with `n` elements.
+	/// Every path from the root to a leaf visits at most two nodes per level, so the
+	/// [`height`](Self::height) of the tree, and thereby the number of nodes visited by
+	/// any operation, never exceeds `2 * level`, which is at most `2 * log2(n + 1)`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set: AATreeSet<u32> = (1 ..= 100).collect();
+	/// assert!(set.level() <= 6);
+	/// assert!(set.height() <= 2 * set.level() as usize);
+	/// ```
+	pub fn level(&self) -> u8 {
+		self.root.level()
+	}
+
+	/// Returns the height of the tree, that is, the number of nodes on the longest path
+	/// from the root to a leaf, or `0` if the set is empty. This walks the whole tree and
+	/// takes O(n) time. Use [`level`](Self::level) for an upper bound that can be
+	/// computed in O(1) time.
+	pub fn height(&self) -> usize {
+		self.root.height()
+	}
+
 	/// Clears the set, removing all elements.
 	///
 	/// # Example
@@ -136,6 +217,99 @@ impl<T> AATreeSet<T> {
 	pub fn iter(&self) -> AAIter<'_, T, &T> {
 		self.into_iter()
 	}
+
+	/// Count the values of this set per bucket, where the bucket of each value is derived
+	/// using `bucket`. The resulting map yields the buckets in ascending order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{AATreeMap, AATreeSet};
+	/// let set = AATreeSet::from([1, 5, 12, 18, 19, 40]);
+	/// assert_eq!(
+	/// 	set.group_count_by(|x| x / 10),
+	/// 	AATreeMap::from([(0, 2), (1, 3), (4, 1)])
+	/// );
+	/// ```
+	pub fn group_count_by<B, F>(&self, mut bucket: F) -> AATreeMap<B, usize>
+	where
+		B: Ord,
+		F: FnMut(&T) -> B
+	{
+		group_fold(self.iter(), |x| bucket(x), 0, |count, _| count + 1)
+	}
+
+	/// Returns an adapter that formats this set like its [`Debug`] implementation, but
+	/// prints only the first and last `n` elements and the number of omitted elements.
+	/// This is useful to log sets that might be huge.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set: AATreeSet<u32> = (0 .. 1000).collect();
+	/// assert_eq!(
+	/// 	format!("{:?}", set.debug_truncated(3)),
+	/// 	"[0, 1, 2, ... 994 more ..., 997, 998, 999]"
+	/// );
+	/// ```
+	pub fn debug_truncated(&self, n: usize) -> DebugTruncated<'_, Self>
+	where
+		T: Debug
+	{
+		DebugTruncated::new(self, n)
+	}
+
+	/// Render the structure of this set in the DOT format understood by Graphviz.
+	#[cfg(feature = "debug-tools")]
+	pub fn to_dot(&self) -> alloc::string::String
+	where
+		T: Debug
+	{
+		self.root.to_dot()
+	}
+}
+
+impl<T: Display> AATreeSet<T> {
+	/// Returns an adapter that displays all elements of this set in order, separated by
+	/// `sep`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set = AATreeSet::from(["b", "c", "a"]);
+	/// assert_eq!(set.display_with(", ").to_string(), "a, b, c");
+	/// ```
+	pub fn display_with<'a>(&'a self, sep: &'a str) -> DisplaySet<'a, T> {
+		DisplaySet::new(self, sep)
+	}
+
+	/// Encode the structure of this set as a deterministic single-line string. See
+	/// [`AANode::to_canonical_string`] for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set = AATreeSet::from([1, 2, 3]);
+	/// assert_eq!(set.to_canonical_string(), "2 => [2, 1, 3]");
+	/// ```
+	pub fn to_canonical_string(&self) -> String {
+		self.root.to_canonical_string()
+	}
+}
+
+impl<T: FromStr> AATreeSet<T> {
+	/// Parse a set from the canonical encoding of its structure. See
+	/// [`AANode::from_canonical_str`] for details.
+	///
+	/// The encoded tree is not checked for being a valid AA tree with ordered elements.
+	pub fn from_canonical_str(s: &str) -> Result<Self, ParseCanonicalError<T::Err>> {
+		let root = AANode::from_canonical_str(s)?;
+		let len = root.count();
+		Ok(Self { root, len })
+	}
 }
 
 impl<T: Ord> AATreeSet<T> {
@@ -157,11 +331,45 @@ impl<T: Ord> AATreeSet<T> {
 		let inserted = self.root.insert(value);
 		if inserted {
 			self.len += 1;
+			#[cfg(feature = "tracing")]
+			self.root.check_height(self.len);
 		}
 		inserted
 	}
 
-	/// Moves all elements from `other` into `self`, leaving `other` empty.
+	/// Creates a set from an iterator, like [`FromIterator::from_iter`], but returns an
+	/// error if the iterator contains the same value more than once.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set = AATreeSet::from_iter_unique(["a", "b"]).unwrap();
+	/// assert_eq!(set.len(), 2);
+	///
+	/// let err = AATreeSet::from_iter_unique(["a", "b", "a"]).unwrap_err();
+	/// assert_eq!(*err.key(), "a");
+	/// ```
+	pub fn from_iter_unique<I>(iter: I) -> Result<Self, DuplicateKeyError<T>>
+	where
+		I: IntoIterator<Item = T>
+	{
+		let mut set = Self::new();
+		for value in iter {
+			if set.contains(&value) {
+				return Err(DuplicateKeyError::new(value));
+			}
+			set.insert(value);
+		}
+		Ok(set)
+	}
+
+	/// Moves all elements from `other` into `self`, leaving `other` empty. If a value
+	/// from `other` is already present in `self`, the value in `self` is kept.
+	///
+	/// If `other` is small compared to `self`, its elements are inserted one by one in
+	/// O(m log(n + m)). Otherwise, both sets are merged in O(n + m) and rebuilt into a
+	/// balanced tree, without reallocating any node.
 	///
 	/// # Examples
 	///
@@ -190,7 +398,91 @@ impl<T: Ord> AATreeSet<T> {
 	/// assert!(a.contains(&5));
 	/// ```
 	pub fn append(&mut self, other: &mut Self) {
-		self.extend(mem::take(other));
+		let other = mem::take(other);
+		self.len = self
+			.root
+			.append(self.len, other.root, other.len, Keep::Existing);
+	}
+
+	/// Splits the set into two at the given value. Returns everything after the given
+	/// value, including the value.
+	///
+	/// If only a few elements end up in one of the sets, they are removed one by one in
+	/// O(log n) each. Otherwise, both sets are rebuilt from the nodes of this set in O(n),
+	/// without cloning any element or allocating any node.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let mut a: AATreeSet<u32> = (1 ..= 5).collect();
+	/// let b = a.split_off(&3);
+	/// assert_eq!(a.into_iter().collect::<Vec<_>>(), [1, 2]);
+	/// assert_eq!(b.into_iter().collect::<Vec<_>>(), [3, 4, 5]);
+	/// ```
+	pub fn split_off<Q>(&mut self, value: &Q) -> Self
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let (root, len) = self.root.split_off(self.len, |v: &T| v.borrow() >= value);
+		self.len -= len;
+		Self { root, len }
+	}
+
+	/// Removes the subtree of the underlying tree whose root is `value`, and returns its
+	/// elements as a new set. See [`AATreeMap::take_subtree`] for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let mut set: AATreeSet<u32> = (1 ..= 7).collect();
+	/// let subtree = set.take_subtree(&2);
+	/// assert_eq!(subtree.into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+	/// assert_eq!(set.into_iter().collect::<Vec<_>>(), [4, 5, 6, 7]);
+	/// ```
+	pub fn take_subtree<Q>(&mut self, value: &Q) -> Self
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		let (root, len) = self.root.take_subtree::<Q, T>(value);
+		self.len -= len;
+		Self { root, len }
+	}
+
+	/// Moves all elements into two new sets, the first one containing all elements for
+	/// which `pred` returns `true`, and the second one containing all other elements.
+	/// `pred` is called once for every element in ascending order.
+	///
+	/// Both sets are rebuilt from the nodes of this set in O(n), without cloning any
+	/// element or allocating any node.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set: AATreeSet<u32> = (1 ..= 5).collect();
+	/// let (even, odd) = set.partition(|value| value % 2 == 0);
+	/// assert_eq!(even.into_iter().collect::<Vec<_>>(), [2, 4]);
+	/// assert_eq!(odd.into_iter().collect::<Vec<_>>(), [1, 3, 5]);
+	/// ```
+	pub fn partition<F>(self, mut pred: F) -> (Self, Self)
+	where
+		F: FnMut(&T) -> bool
+	{
+		let ((left, left_len), (right, right_len)) = self.root.partition(|v| pred(v));
+		(
+			Self {
+				root: left,
+				len: left_len
+			},
+			Self {
+				root: right,
+				len: right_len
+			}
+		)
 	}
 
 	/// Returns the first/smallest element of the set.
@@ -245,7 +537,9 @@ impl<T: Ord> AATreeSet<T> {
 	/// assert_eq!(set.pop_first(), None);
 	/// ```
 	pub fn pop_first(&mut self) -> Option<T> {
-		self.root.remove_successor()
+		let value = self.root.remove_successor()?;
+		self.len -= 1;
+		Some(value)
 	}
 
 	/// Remove and return the last/largest element of the set.
@@ -264,7 +558,9 @@ impl<T: Ord> AATreeSet<T> {
 	/// assert_eq!(set.pop_last(), None);
 	/// ```
 	pub fn pop_last(&mut self) -> Option<T> {
-		self.root.remove_predecessor()
+		let value = self.root.remove_predecessor()?;
+		self.len -= 1;
+		Some(value)
 	}
 
 	/// Returns `true` if the set contains an element with the given value.
@@ -295,6 +591,31 @@ impl<T: Ord> AATreeSet<T> {
 			.is_some()
 	}
 
+	/// Returns a reference to the element in the set, if any, that is equal to the value.
+	///
+	/// # Example
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let mut set: AATreeSet<Box<str>> = AATreeSet::new();
+	/// set.insert("foo".into());
+	/// assert_eq!(set.get("foo").map(|v| &**v), Some("foo"));
+	/// assert_eq!(set.get("bar"), None);
+	/// ```
+	pub fn get<Q>(&self, value: &Q) -> Option<&T>
+	where
+		T: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		self.root.traverse(
+			|content| match content.borrow().cmp(value) {
+				Ordering::Greater => TraverseStep::Left,
+				Ordering::Less => TraverseStep::Right,
+				Ordering::Equal => TraverseStep::Value(Some(content))
+			},
+			|_, sub| sub
+		)
+	}
+
 	/// Returns the first/smallest element of the set that is greater or equal to `x`.
 	///
 	/// # Example
@@ -312,17 +633,7 @@ impl<T: Ord> AATreeSet<T> {
 		T: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		self.root.traverse(
-			|content| match content.borrow().cmp(value) {
-				Ordering::Greater => TraverseStep::Left,
-				Ordering::Less => TraverseStep::Right,
-				Ordering::Equal => TraverseStep::Value(Some(content))
-			},
-			|content, sub| match sub {
-				None if content.borrow() > value => Some(content),
-				sub => sub
-			}
-		)
+		self.lower_bound(Bound::Included(value))
 	}
 
 	/// Returns the last/largest element of the set that is smaller or equal to `x`.
@@ -342,17 +653,145 @@ impl<T: Ord> AATreeSet<T> {
 		T: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		self.root.traverse(
-			|content| match content.borrow().cmp(value) {
-				Ordering::Greater => TraverseStep::Left,
-				Ordering::Less => TraverseStep::Right,
-				Ordering::Equal => TraverseStep::Value(Some(content))
-			},
-			|content, sub| match sub {
-				None if content.borrow() < value => Some(content),
-				sub => sub
+		self.upper_bound(Bound::Included(value))
+	}
+
+	/// Creates an iterator over the values within `range` in ascending order. See
+	/// [`AATreeMap::range`] for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set: AATreeSet<u32> = (0 .. 10).collect();
+	/// assert_eq!(set.range(3 .. 6).collect::<Vec<_>>(), [&3, &4, &5]);
+	/// assert_eq!(set.range(8 ..).collect::<Vec<_>>(), [&8, &9]);
+	/// ```
+	pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		let (start, end) = (range.start_bound(), range.end_bound());
+		Range(RawRange::new(
+			&self.root,
+			|value| before_start(value, start),
+			|value| after_end(value, end)
+		))
+	}
+
+	/// Creates a new set containing clones of all values within `range`. See
+	/// [`AATreeMap::clone_range`] for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set: AATreeSet<u32> = (0 .. 10).collect();
+	/// assert_eq!(set.clone_range(7 ..), AATreeSet::from([7, 8, 9]));
+	/// ```
+	pub fn clone_range<Q, R>(&self, range: R) -> Self
+	where
+		T: Borrow<Q> + Clone,
+		Q: Ord + ?Sized,
+		R: RangeBounds<Q>
+	{
+		let bounds = (range.start_bound(), range.end_bound());
+		let len = self.range(bounds).count();
+		Self::from_sorted_unchecked(self.range(bounds).cloned(), len)
+	}
+
+	/// Returns the first element of the set that is within a range starting at `bound`.
+	/// This is the first element greater than (or equal to, if the bound is inclusive)
+	/// the bound, or the first element of the set if it is unbounded.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// use std::ops::Bound;
+	///
+	/// let set = AATreeSet::from([10, 20, 30]);
+	/// assert_eq!(set.lower_bound(Bound::Included(&20)), Some(&20));
+	/// assert_eq!(set.lower_bound(Bound::Excluded(&20)), Some(&30));
+	/// assert_eq!(set.lower_bound(Bound::Excluded(&30)), None);
+	/// ```
+	pub fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<&T>
+	where
+		T: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		search::lower_bound(&self.root, |content| before_start(content, bound))
+	}
+
+	/// Returns the last element of the set that is within a range ending at `bound`. This
+	/// is the last element less than (or equal to, if the bound is inclusive) the bound,
+	/// or the last element of the set if it is unbounded.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// use std::ops::Bound;
+	///
+	/// let set = AATreeSet::from([10, 20, 30]);
+	/// assert_eq!(set.upper_bound(Bound::Included(&20)), Some(&20));
+	/// assert_eq!(set.upper_bound(Bound::Excluded(&20)), Some(&10));
+	/// assert_eq!(set.upper_bound(Bound::Excluded(&10)), None);
+	/// ```
+	pub fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<&T>
+	where
+		T: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		search::upper_bound(&self.root, |content| after_end(content, bound))
+	}
+
+	/// Returns the element that is closest to `value`, which is either an element equal to
+	/// `value`, or whichever of its predecessor and successor is nearer. If both are
+	/// equally far away, `tie` decides which one is returned.
+	///
+	/// # Example
+	/// ```rust
+	/// # use aatree::{map::Tie, AATreeSet};
+	/// let set = AATreeSet::from([10, 20, 40]);
+	/// assert_eq!(set.closest(&13, Tie::Lower), Some(&10));
+	/// assert_eq!(set.closest(&30, Tie::Upper), Some(&40));
+	/// ```
+	pub fn closest<Q, D>(&self, value: &Q, tie: Tie) -> Option<&T>
+	where
+		T: Borrow<Q> + Ord,
+		Q: Ord + ?Sized,
+		for<'q> &'q Q: Sub<&'q Q, Output = D>,
+		D: Ord
+	{
+		let lower = self.last_at_or_before(value);
+		if let Some(lower) = lower {
+			if lower.borrow() == value {
+				return Some(lower);
 			}
-		)
+		}
+		closest(value, lower, self.first_at_or_after(value), tie)
+	}
+
+	/// Returns the element equal to `value` if it is present, otherwise the largest
+	/// element smaller than `value`, or the smallest element of the set if `value` is
+	/// below all of them. Only returns `None` if the set is empty.
+	///
+	/// # Example
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set = AATreeSet::from([10, 20, 40]);
+	/// assert_eq!(set.clamp_value(&30), Some(&20));
+	/// assert_eq!(set.clamp_value(&5), Some(&10));
+	/// ```
+	pub fn clamp_value<Q>(&self, value: &Q) -> Option<&T>
+	where
+		T: Borrow<Q> + Ord,
+		Q: Ord + ?Sized
+	{
+		self.last_at_or_before(value).or_else(|| self.first())
 	}
 
 	/// Removes a value from the set, and returns `true` if it was removed.
@@ -361,7 +800,7 @@ impl<T: Ord> AATreeSet<T> {
 		T: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		self.root.remove(x).is_some()
+		self.take(x).is_some()
 	}
 
 	/// Removes a value from the set, and returns the value that was removed.
@@ -370,10 +809,174 @@ impl<T: Ord> AATreeSet<T> {
 		T: Borrow<Q> + Ord,
 		Q: Ord + ?Sized
 	{
-		self.root.remove(x)
+		let value = self.root.remove::<Q, T>(x)?;
+		self.len -= 1;
+		Some(value)
+	}
+
+	/// Retains only the values for which `f` returns `true`, visiting the values in
+	/// ascending order. See [`AATreeMap::retain`] for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let mut set: AATreeSet<u32> = (0 .. 8).collect();
+	/// set.retain(|x| x % 2 == 0);
+	/// assert_eq!(set.into_iter().collect::<Vec<_>>(), [0, 2, 4, 6]);
+	/// ```
+	pub fn retain<F>(&mut self, mut f: F)
+	where
+		F: FnMut(&T) -> bool
+	{
+		let len = mem::take(&mut self.len);
+		let removed = self.root.retain(|value| f(value));
+		self.len = len - removed;
+	}
+
+	/// Returns the smallest value at or after `start` that is not part of the set, or
+	/// `None` if all values from `start` up to the maximum value of the type are taken.
+	/// See [`AATreeMap::next_free_key`] for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set = AATreeSet::from([1, 2, 3, 5]);
+	/// assert_eq!(set.next_free(&1), Some(4));
+	/// assert_eq!(set.next_free(&4), Some(4));
+	/// ```
+	pub fn next_free(&self, start: &T) -> Option<T>
+	where
+		T: Successor + Clone
+	{
+		search::next_free(&self.root, start, |value| value)
+	}
+
+	/// Estimates the number of values in the set that are less than `value` in O(log n)
+	/// time. See [`AATreeMap::estimate_rank`] for details.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let set: AATreeSet<u32> = (0 .. 1000).collect();
+	/// let rank = set.estimate_rank(&250);
+	/// assert!(rank.min() <= 250 && 250 <= rank.max());
+	/// ```
+	pub fn estimate_rank<Q>(&self, value: &Q) -> RankEstimate
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		search::estimate_rank(&self.root, self.len, |x| x.borrow() < value)
+	}
+
+	/// Returns `true` if `value` is estimated to be in the first half of the set. See
+	/// [`AATreeMap::is_in_first_half`] for details.
+	pub fn is_in_first_half<Q>(&self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.estimate_rank(value).estimate() < self.len - self.len / 2
+	}
+
+	/// Creates an iterator that visits the values that are in both `self` and `other` in
+	/// ascending order. Both sets are merged in a single pass in O(n + m).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::AATreeSet;
+	/// let a = AATreeSet::from([1, 2, 3, 5, 8]);
+	/// let b = AATreeSet::from([2, 4, 6, 8]);
+	/// assert_eq!(a.intersection(&b).collect::<Vec<_>>(), [&2, &8]);
+	/// ```
+	pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T> {
+		Intersection {
+			a: self.iter().peekable(),
+			b: other.iter().peekable()
+		}
+	}
+}
+
+/// An iterator over a range of values of an [`AATreeSet`]. Returned by
+/// [`AATreeSet::range`].
+pub struct Range<'a, T>(RawRange<'a, T>);
+
+impl<'a, T> Range<'a, T> {
+	pub(crate) fn empty() -> Self {
+		Self(RawRange::empty())
+	}
+}
+
+impl<T> Debug for Range<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Range").finish_non_exhaustive()
+	}
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<&'a T> {
+		self.0.next()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.0.size_hint()
+	}
+}
+
+impl<T> FusedIterator for Range<'_, T> {}
+
+impl<T> SortedIterator for Range<'_, T> {}
+
+/// An iterator over the intersection of two sets. Returned by
+/// [`AATreeSet::intersection`].
+pub struct Intersection<'a, T> {
+	a: Peekable<AAIter<'a, T, &'a T>>,
+	b: Peekable<AAIter<'a, T, &'a T>>
+}
+
+impl<T> Debug for Intersection<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Intersection").finish_non_exhaustive()
+	}
+}
+
+impl<'a, T: Ord> Iterator for Intersection<'a, T> {
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<&'a T> {
+		loop {
+			let (a, b) = (*self.a.peek()?, *self.b.peek()?);
+			match a.cmp(b) {
+				Ordering::Less => {
+					self.a.next();
+				},
+				Ordering::Greater => {
+					self.b.next();
+				},
+				Ordering::Equal => {
+					self.b.next();
+					return self.a.next();
+				}
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let max = self.a.len().min(self.b.len());
+		(0, Some(max))
 	}
 }
 
+impl<T: Ord> FusedIterator for Intersection<'_, T> {}
+
+impl<T: Ord> SortedIterator for Intersection<'_, T> {}
+
 impl<T: Ord> FromIterator<T> for AATreeSet<T> {
 	fn from_iter<I>(iter: I) -> Self
 	where
@@ -401,9 +1004,7 @@ impl<T: Ord> From<Vec<T>> for AATreeSet<T> {
 
 impl<T: Ord> Extend<T> for AATreeSet<T> {
 	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-		for value in iter {
-			self.insert(value);
-		}
+		self.len = self.root.extend(self.len, iter.into_iter(), Keep::Existing);
 	}
 }
 
@@ -430,3 +1031,75 @@ impl<'a, T> IntoIterator for &'a AATreeSet<T> {
 		AAIter::new(&self.root, self.len)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::AATreeSet;
+	use alloc::{boxed::Box, rc::Rc, string::String, sync::Arc};
+
+	#[test]
+	fn test_len_after_removal() {
+		let mut set: AATreeSet<u32> = (0 .. 10).collect();
+		assert!(set.remove(&3));
+		assert!(!set.remove(&3));
+		assert_eq!(set.take(&4), Some(4));
+		assert_eq!(set.pop_first(), Some(0));
+		assert_eq!(set.pop_last(), Some(9));
+		assert_eq!(set.len(), 6);
+		assert_eq!(set.iter().count(), 6);
+	}
+
+	macro_rules! test_borrowed_lookups {
+		($name:ident : $value:ty, $query:ty, $conv:expr) => {
+			#[test]
+			fn $name() {
+				let conv = $conv;
+				let mut set: AATreeSet<$value> =
+					["b", "d", "f", "h"].into_iter().map(conv).collect();
+				let q = |value: &'static str| -> &$query { value.as_ref() };
+
+				assert!(set.contains(q("d")));
+				assert!(!set.contains(q("e")));
+				assert_eq!(set.get(q("f")), Some(&conv("f")));
+				assert_eq!(set.first_at_or_after(q("c")), Some(&conv("d")));
+				assert_eq!(set.last_at_or_before(q("g")), Some(&conv("f")));
+
+				assert!(set.remove(q("d")));
+				assert!(!set.remove(q("d")));
+				assert_eq!(set.take(q("f")), Some(conv("f")));
+				assert_eq!(set.len(), 2);
+			}
+		};
+	}
+
+	test_borrowed_lookups!(test_string_by_str: String, str, String::from);
+	test_borrowed_lookups!(test_box_str_by_str: Box<str>, str, Box::<str>::from);
+	test_borrowed_lookups!(test_arc_str_by_str: Arc<str>, str, Arc::<str>::from);
+	test_borrowed_lookups!(test_rc_bytes_by_slice: Rc<[u8]>, [u8], |value: &str| Rc::<[u8]>::from(value.as_bytes()));
+
+	#[test]
+	fn test_append_and_extend() {
+		use alloc::collections::BTreeSet;
+
+		for (n, m) in [(0, 0), (0, 100), (100, 0), (10, 100), (100, 10), (1000, 30)] {
+			let mut set: AATreeSet<u32> = (0 .. n).map(|x| x * 3).collect();
+			let mut other: AATreeSet<u32> = (0 .. m).map(|x| x * 2).collect();
+			let expected: BTreeSet<u32> = set.iter().chain(&other).copied().collect();
+
+			let mut extended = set.clone();
+			extended.extend((0 .. m).rev().map(|x| x * 2 % 50));
+			let expected_extended: BTreeSet<u32> = set
+				.iter()
+				.copied()
+				.chain((0 .. m).map(|x| x * 2 % 50))
+				.collect();
+			assert!(extended.iter().eq(expected_extended.iter()));
+			assert_eq!(extended.len(), expected_extended.len());
+
+			set.append(&mut other);
+			assert!(other.is_empty());
+			assert_eq!(set.len(), expected.len());
+			assert!(set.iter().eq(expected.iter()));
+		}
+	}
+}
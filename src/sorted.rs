@@ -0,0 +1,276 @@
+//! A marker trait for iterators that are known to yield their items in strictly ascending
+//! order, and collections that can be built from them in O(n).
+//!
+//! All ascending iterators of this crate, the iterators of
+//! [`BTreeMap`] and [`BTreeSet`],
+//! and all adapters from [`core::iter`] that preserve the order of their items implement
+//! [`SortedIterator`]. [`AATreeMap::from_sorted_iter`](crate::AATreeMap::from_sorted_iter)
+//! and [`AATreeSet::from_sorted_iter`](crate::AATreeSet::from_sorted_iter) accept these
+//! iterators as a trusted source and build a balanced tree in O(n) without comparing any
+//! elements. The same is available for all collections implementing [`FromSortedIterator`]
+//! through [`SortedIterator::collect_sorted`], the sorted counterpart of
+//! [`Iterator::collect`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::{sorted::SortedIterator, AATreeMap, AATreeSet};
+//!
+//! let map: AATreeMap<u32, char> = (0 .. 10).zip('a' ..).collect();
+//! let evens =
+//! 	AATreeMap::from_sorted_iter(map.range(2 .. 8).filter(|(k, _)| *k % 2 == 0));
+//! assert_eq!(evens.len(), 3);
+//!
+//! let set: AATreeSet<u32> = (0 .. 10).collect();
+//! let tail = AATreeSet::from_sorted_iter(set.range(7 ..).copied());
+//! assert_eq!(tail.into_iter().collect::<Vec<_>>(), [7, 8, 9]);
+//!
+//! let odd: AATreeSet<u32> = set.iter().copied().filter(|x| x % 2 == 1).collect_sorted();
+//! assert_eq!(odd.len(), 5);
+//! ```
+
+use crate::{AATreeMap, AATreeSet, FrozenAATreeMap};
+use alloc::collections::{btree_map, btree_set, BTreeMap, BTreeSet};
+use core::iter::{
+	Cloned, Copied, Empty, Filter, Fuse, Inspect, Once, Peekable, Skip, SkipWhile,
+	StepBy, Take, TakeWhile
+};
+
+/// An iterator that yields its items in strictly ascending order. For iterators over
+/// `(key, value)` pairs, only the keys are ordered, and every key appears at most once.
+///
+/// This trait is not `unsafe` to implement. However, trees that are built from an
+/// implementation that does not uphold this order are not sorted, and their behavior is
+/// unspecified, just like with a key type whose [`Ord`] implementation is inconsistent.
+/// The order is checked in debug builds.
+pub trait SortedIterator: Iterator {
+	/// Transforms this iterator into a collection using the O(n) bulk builder of the
+	/// collection, like [`Iterator::collect`] but without comparing any items.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::{sorted::SortedIterator, AATreeMap};
+	/// let map: AATreeMap<u32, char> = (0 .. 10).zip('a' ..).collect();
+	/// let range: AATreeMap<_, _> = map.range(2 .. 5).collect_sorted();
+	/// assert_eq!(range.values().copied().collect::<String>(), "cde");
+	/// ```
+	fn collect_sorted<B>(self) -> B
+	where
+		Self: Sized,
+		B: FromSortedIterator<Self::Item>
+	{
+		B::from_sorted_iter(self)
+	}
+}
+
+/// Conversion from a [`SortedIterator`], the sorted counterpart of
+/// [`FromIterator`]. Implementations can rely on the order of
+/// the items to build the collection in O(n).
+pub trait FromSortedIterator<A>: Sized {
+	/// Creates a collection from an iterator that yields its items in strictly ascending
+	/// order.
+	fn from_sorted_iter<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = A>,
+		I::IntoIter: SortedIterator;
+}
+
+impl<K: Ord, V> FromSortedIterator<(K, V)> for AATreeMap<K, V> {
+	fn from_sorted_iter<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = (K, V)>,
+		I::IntoIter: SortedIterator
+	{
+		AATreeMap::from_sorted_iter(iter)
+	}
+}
+
+impl<T: Ord> FromSortedIterator<T> for AATreeSet<T> {
+	fn from_sorted_iter<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = T>,
+		I::IntoIter: SortedIterator
+	{
+		AATreeSet::from_sorted_iter(iter)
+	}
+}
+
+impl<K: Ord, V> FromSortedIterator<(K, V)> for FrozenAATreeMap<K, V> {
+	fn from_sorted_iter<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = (K, V)>,
+		I::IntoIter: SortedIterator
+	{
+		let map = FrozenAATreeMap::from_sorted_unchecked(iter);
+		debug_assert!(
+			map.keys().windows(2).all(|w| w[0] < w[1]),
+			"keys are not strictly ascending"
+		);
+		map
+	}
+}
+
+/// [`BTreeMap`] already builds itself in O(n) from sorted input, so this is equivalent
+/// to [`Iterator::collect`].
+impl<K: Ord, V> FromSortedIterator<(K, V)> for BTreeMap<K, V> {
+	fn from_sorted_iter<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = (K, V)>,
+		I::IntoIter: SortedIterator
+	{
+		iter.into_iter().collect()
+	}
+}
+
+/// [`BTreeSet`] already builds itself in O(n) from sorted input, so this is equivalent
+/// to [`Iterator::collect`].
+impl<T: Ord> FromSortedIterator<T> for BTreeSet<T> {
+	fn from_sorted_iter<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = T>,
+		I::IntoIter: SortedIterator
+	{
+		iter.into_iter().collect()
+	}
+}
+
+impl<I: SortedIterator + ?Sized> SortedIterator for &mut I {}
+
+impl<'a, T, I> SortedIterator for Cloned<I>
+where
+	T: Clone + 'a,
+	I: SortedIterator<Item = &'a T>
+{
+}
+
+impl<'a, T, I> SortedIterator for Copied<I>
+where
+	T: Copy + 'a,
+	I: SortedIterator<Item = &'a T>
+{
+}
+
+impl<T> SortedIterator for Empty<T> {}
+
+impl<I, P> SortedIterator for Filter<I, P>
+where
+	I: SortedIterator,
+	P: FnMut(&I::Item) -> bool
+{
+}
+
+impl<I: SortedIterator> SortedIterator for Fuse<I> {}
+
+impl<I, F> SortedIterator for Inspect<I, F>
+where
+	I: SortedIterator,
+	F: FnMut(&I::Item)
+{
+}
+
+impl<T> SortedIterator for Once<T> {}
+
+impl<I: SortedIterator> SortedIterator for Peekable<I> {}
+
+impl<I: SortedIterator> SortedIterator for Skip<I> {}
+
+impl<I, P> SortedIterator for SkipWhile<I, P>
+where
+	I: SortedIterator,
+	P: FnMut(&I::Item) -> bool
+{
+}
+
+impl<I: SortedIterator> SortedIterator for StepBy<I> {}
+
+impl<I: SortedIterator> SortedIterator for Take<I> {}
+
+impl<I, P> SortedIterator for TakeWhile<I, P>
+where
+	I: SortedIterator,
+	P: FnMut(&I::Item) -> bool
+{
+}
+
+impl<K, V> SortedIterator for btree_map::IntoIter<K, V> {}
+impl<K, V> SortedIterator for btree_map::IntoKeys<K, V> {}
+impl<K, V> SortedIterator for btree_map::Iter<'_, K, V> {}
+impl<K, V> SortedIterator for btree_map::Keys<'_, K, V> {}
+impl<K, V> SortedIterator for btree_map::Range<'_, K, V> {}
+
+impl<T> SortedIterator for btree_set::IntoIter<T> {}
+impl<T> SortedIterator for btree_set::Iter<'_, T> {}
+impl<T> SortedIterator for btree_set::Range<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+	use crate::{AATreeMap, AATreeSet};
+	use alloc::{collections::BTreeMap, vec::Vec};
+
+	#[test]
+	fn test_from_sorted_iter() {
+		for len in [0, 1, 2, 3, 10, 100] {
+			let map: AATreeMap<u32, u32> = (0 .. len).map(|x| (x, x * 2)).collect();
+			let copy = AATreeMap::from_sorted_iter(map.iter());
+			copy.assert_valid();
+			assert_eq!(copy.len(), map.len());
+			drop(copy);
+
+			let set: AATreeSet<u32> = (0 .. len).collect();
+			let odd = AATreeSet::from_sorted_iter(set.iter().filter(|k| *k % 2 == 1));
+			assert_eq!(odd.len(), len as usize / 2);
+			let odd = AATreeSet::from_sorted_iter(odd.iter().copied().skip(1));
+			assert_eq!(odd.len(), (len as usize / 2).saturating_sub(1));
+
+			let btree: BTreeMap<u32, u32> = map.into_iter().collect();
+			let map = AATreeMap::from_sorted_iter(btree.into_iter().step_by(3));
+			map.assert_valid();
+			let keys: Vec<_> = map.into_iter().map(|(k, _)| k).collect();
+			assert_eq!(keys, (0 .. len).step_by(3).collect::<Vec<_>>());
+		}
+	}
+
+	#[test]
+	fn test_collect_sorted() {
+		use super::SortedIterator;
+		use crate::FrozenAATreeMap;
+		use alloc::collections::BTreeSet;
+
+		let map: AATreeMap<u32, u32> = (0 .. 100).map(|x| (x, x * 2)).collect();
+		let frozen: FrozenAATreeMap<_, _> = map.range(10 .. 20).collect_sorted();
+		assert_eq!(frozen.len(), 10);
+		assert_eq!(frozen.get(&&15), Some(&&30));
+
+		let set: AATreeSet<u32> = (10 .. 20).collect();
+		let btree: BTreeSet<u32> = set.iter().copied().collect_sorted();
+		let set: AATreeSet<u32> = btree.iter().copied().collect_sorted();
+		assert_eq!(
+			set.into_iter().collect::<Vec<_>>(),
+			(10 .. 20).collect::<Vec<_>>()
+		);
+
+		let copy: AATreeMap<_, _> = map.into_iter().skip(90).collect_sorted();
+		copy.assert_valid();
+		assert_eq!(copy.first_key_value(), Some((&90, &180)));
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic]
+	fn test_from_sorted_iter_unsorted() {
+		struct Unsorted<I>(I);
+
+		impl<I: Iterator> Iterator for Unsorted<I> {
+			type Item = I::Item;
+
+			fn next(&mut self) -> Option<I::Item> {
+				self.0.next()
+			}
+		}
+
+		impl<I: Iterator> super::SortedIterator for Unsorted<I> {}
+
+		AATreeSet::from_sorted_iter(Unsorted([1, 3, 2].into_iter()));
+	}
+}
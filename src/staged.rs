@@ -0,0 +1,375 @@
+//! A mutable map that stages its changes on top of a [`FrozenAATreeMap`].
+
+use crate::{
+	frozen::{self, FrozenAATreeMap},
+	iter::AAIter,
+	map::KeyValue,
+	sorted::SortedIterator,
+	AATreeMap
+};
+use core::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt::{self, Debug},
+	iter::{FusedIterator, Peekable}
+};
+
+/// A map that combines an immutable [`FrozenAATreeMap`] with a small mutable
+/// [`AATreeMap`] that records all changes since the last [`compact`](Self::compact).
+///
+/// All lookups and iterators present the merged view of both, where changes recorded in
+/// the delta take precedence over the frozen base. Removing a key that is part of the
+/// base inserts a tombstone into the delta. Calling [`compact`](Self::compact) merges the
+/// delta into a new base in O(n).
+///
+/// This is useful for workloads that are mostly read-only but receive occasional
+/// updates: Reads are served from the compact base while the delta stays small.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::{AATreeMap, StagedMap};
+/// let base = AATreeMap::from([(1, "a"), (2, "b"), (3, "c")]).freeze();
+/// let mut map = StagedMap::from(base);
+/// map.insert(4, "d");
+/// map.remove(1);
+/// map.insert(2, "B");
+/// assert_eq!(map.len(), 3);
+/// assert_eq!(map.delta_len(), 3);
+/// assert_eq!(map.iter().collect::<Vec<_>>(), [
+/// 	(&2, &"B"),
+/// 	(&3, &"c"),
+/// 	(&4, &"d")
+/// ]);
+///
+/// map.compact();
+/// assert_eq!(map.delta_len(), 0);
+/// assert_eq!(map.base().values(), ["B", "c", "d"]);
+/// ```
+#[derive(Clone)]
+pub struct StagedMap<K, V> {
+	base: FrozenAATreeMap<K, V>,
+	delta: AATreeMap<K, Option<V>>,
+	len: usize
+}
+
+impl<K, V> Default for StagedMap<K, V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K: Ord + Debug, V: Debug> Debug for StagedMap<K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+impl<K, V> From<FrozenAATreeMap<K, V>> for StagedMap<K, V> {
+	fn from(base: FrozenAATreeMap<K, V>) -> Self {
+		Self {
+			len: base.len(),
+			base,
+			delta: AATreeMap::new()
+		}
+	}
+}
+
+impl<K, V> From<AATreeMap<K, V>> for StagedMap<K, V> {
+	fn from(map: AATreeMap<K, V>) -> Self {
+		map.freeze().into()
+	}
+}
+
+impl<K, V> StagedMap<K, V> {
+	/// Construct a new, empty staged map.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # use aatree::StagedMap;
+	/// const EMPTY: StagedMap<u32, &str> = StagedMap::new();
+	/// let mut map = EMPTY;
+	/// map.insert(1, "a");
+	/// assert_eq!(map.len(), 1);
+	/// ```
+	pub const fn new() -> Self {
+		Self {
+			base: FrozenAATreeMap::new(),
+			delta: AATreeMap::new(),
+			len: 0
+		}
+	}
+
+	/// Returns the number of elements in the merged view of this map.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns `true` if the merged view of this map contains no elements.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the frozen base of this map, which does not include any changes since the
+	/// last [`compact`](Self::compact).
+	pub fn base(&self) -> &FrozenAATreeMap<K, V> {
+		&self.base
+	}
+
+	/// Returns the number of changes (including tombstones) that have been recorded since
+	/// the last [`compact`](Self::compact). This can be used to decide when to compact.
+	pub fn delta_len(&self) -> usize {
+		self.delta.len()
+	}
+}
+
+impl<K: Ord, V> StagedMap<K, V> {
+	/// Returns a reference to the value corresponding to the key.
+	pub fn get<Q>(&self, key: &Q) -> Option<&V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		match self.delta.get(key) {
+			Some(value) => value.as_ref(),
+			None => self.base.get(key)
+		}
+	}
+
+	/// Check if a key is contained within the merged view of this map.
+	pub fn contains_key<Q>(&self, key: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.get(key).is_some()
+	}
+
+	/// Insert a new element into the map, or overwrite an existing element with the same
+	/// key. Returns `true` if the key was not present before.
+	///
+	/// Unlike [`AATreeMap::insert`], this does not return the old value, as it might be
+	/// part of the immutable base.
+	pub fn insert(&mut self, key: K, value: V) -> bool {
+		let inserted = match self.delta.get(&key) {
+			Some(old) => old.is_none(),
+			None => !self.base.contains_key(&key)
+		};
+		self.delta.insert(key, Some(value));
+		if inserted {
+			self.len += 1;
+		}
+		inserted
+	}
+
+	/// Remove a key from the map. Returns `true` if the key was present before.
+	///
+	/// The key is taken by value since it needs to be recorded as a tombstone if it is
+	/// part of the immutable base.
+	pub fn remove(&mut self, key: K) -> bool {
+		let removed = if self.base.contains_key(&key) {
+			!matches!(self.delta.insert(key, None), Some(None))
+		} else {
+			matches!(self.delta.remove(&key), Some(Some(_)))
+		};
+		if removed {
+			self.len -= 1;
+		}
+		removed
+	}
+
+	/// Creates an iterator over the merged view of this map that visits all entries with
+	/// the keys in ascending order.
+	pub fn iter(&self) -> Iter<'_, K, V> {
+		Iter {
+			merge: Merge {
+				base: self.base.iter().peekable(),
+				delta: self.delta.iter().map(delta_entry as _).peekable(),
+				len: self.len
+			}
+		}
+	}
+
+	/// Merge all changes into a new frozen base in O(n).
+	pub fn compact(&mut self) {
+		if self.delta.is_empty() {
+			return;
+		}
+		let base = core::mem::take(&mut self.base);
+		let delta = core::mem::take(&mut self.delta);
+		let merge = Merge {
+			base: base.into_iter().peekable(),
+			delta: delta.into_iter().peekable(),
+			len: self.len
+		};
+		self.base = FrozenAATreeMap::from_sorted_unchecked(merge);
+	}
+
+	/// Compact this map and return its frozen base.
+	pub fn into_frozen(mut self) -> FrozenAATreeMap<K, V> {
+		self.compact();
+		self.base
+	}
+}
+
+impl<K: Ord, V> From<StagedMap<K, V>> for FrozenAATreeMap<K, V> {
+	fn from(map: StagedMap<K, V>) -> Self {
+		map.into_frozen()
+	}
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a StagedMap<K, V> {
+	type Item = (&'a K, &'a V);
+	type IntoIter = Iter<'a, K, V>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+fn delta_entry<'a, K, V>((key, value): (&'a K, &'a Option<V>)) -> (&'a K, Option<&'a V>) {
+	(key, value.as_ref())
+}
+
+/// Merges the entries of a base with the changes of a delta, where the delta wins and
+/// `None` values in the delta mark removed entries.
+struct Merge<B: Iterator, D: Iterator> {
+	base: Peekable<B>,
+	delta: Peekable<D>,
+	/// The number of entries that have not been returned yet. The staged map keeps track
+	/// of the length of its merged view, so this is always exact, which lets `compact`
+	/// allocate the new base up front.
+	len: usize
+}
+
+impl<K: Ord, V, B, D> Iterator for Merge<B, D>
+where
+	B: Iterator<Item = (K, V)>,
+	D: Iterator<Item = (K, Option<V>)>
+{
+	type Item = (K, V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let next = self.next_entry();
+		if next.is_some() {
+			self.len -= 1;
+		}
+		next
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<K: Ord, V, B, D> Merge<B, D>
+where
+	B: Iterator<Item = (K, V)>,
+	D: Iterator<Item = (K, Option<V>)>
+{
+	fn next_entry(&mut self) -> Option<(K, V)> {
+		loop {
+			let ord = match (self.base.peek(), self.delta.peek()) {
+				(_, None) => return self.base.next(),
+				(None, Some(_)) => Ordering::Greater,
+				(Some((base_key, _)), Some((delta_key, _))) => base_key.cmp(delta_key)
+			};
+			if ord == Ordering::Less {
+				return self.base.next();
+			}
+			if ord == Ordering::Equal {
+				self.base.next();
+			}
+			if let Some((key, Some(value))) = self.delta.next() {
+				return Some((key, value));
+			}
+		}
+	}
+}
+
+impl<K: Ord, V, B, D> ExactSizeIterator for Merge<B, D>
+where
+	B: Iterator<Item = (K, V)>,
+	D: Iterator<Item = (K, Option<V>)>
+{
+}
+
+type DeltaIter<'a, K, V> = core::iter::Map<
+	AAIter<'a, KeyValue<K, Option<V>>, (&'a K, &'a Option<V>)>,
+	fn((&'a K, &'a Option<V>)) -> (&'a K, Option<&'a V>)
+>;
+
+/// An iterator over the merged view of a [`StagedMap`].
+pub struct Iter<'a, K, V> {
+	merge: Merge<frozen::Iter<'a, K, V>, DeltaIter<'a, K, V>>
+}
+
+impl<K, V> Debug for Iter<'_, K, V> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Iter").finish_non_exhaustive()
+	}
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+	type Item = (&'a K, &'a V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.merge.next()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.merge.size_hint()
+	}
+}
+
+impl<K: Ord, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K: Ord, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K: Ord, V> SortedIterator for Iter<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+	use super::StagedMap;
+	use crate::AATreeMap;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_matches_map() {
+		let mut map: AATreeMap<u8, u8> = (0 .. 100).step_by(3).map(|i| (i, i)).collect();
+		let mut staged = StagedMap::from(map.clone());
+		let mut state = 7u32;
+		for step in 0 .. 2000 {
+			state = state.wrapping_mul(1103515245).wrapping_add(12345);
+			let key = (state >> 16) as u8 % 120;
+			if state & 1 == 0 {
+				assert_eq!(
+					staged.insert(key, step as u8),
+					map.insert(key, step as u8).is_none()
+				);
+			} else {
+				assert_eq!(staged.remove(key), map.remove(&key).is_some());
+			}
+			assert_eq!(staged.len(), map.len());
+			assert_eq!(staged.get(&key), map.get(&key));
+			if step % 100 == 0 {
+				assert!(staged.iter().eq(map.iter()));
+				let mut iter = staged.iter();
+				assert_eq!(iter.len(), map.len());
+				iter.next();
+				assert_eq!(iter.len(), map.len().saturating_sub(1));
+			}
+			if step % 300 == 0 {
+				staged.compact();
+				assert_eq!(staged.delta_len(), 0);
+			}
+		}
+		assert!(staged.iter().eq(map.iter()));
+		let frozen = staged.into_frozen();
+		assert_eq!(
+			frozen.iter().collect::<Vec<_>>(),
+			map.iter().collect::<Vec<_>>()
+		);
+	}
+}
@@ -0,0 +1,91 @@
+//! A global allocator that counts allocations, to assert how often an operation allocates
+//! in tests.
+//!
+//! Install [`CountingAlloc`] as the global allocator of a test binary, and use
+//! [`assert_allocs!`](crate::assert_allocs) to check the number of allocations performed
+//! by an expression. Allocations are counted per thread, so tests running in parallel do
+//! not influence each other.
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::{assert_allocs, testalloc::CountingAlloc, AATreeMap};
+//!
+//! #[global_allocator]
+//! static GLOBAL: CountingAlloc = CountingAlloc::system();
+//!
+//! let mut map = AATreeMap::new();
+//! assert_allocs!(map.insert(1, "a"), 1);
+//! assert_allocs!(map.get(&1), 0);
+//! ```
+
+use core::{
+	alloc::{GlobalAlloc, Layout},
+	cell::Cell
+};
+use std::{alloc::System, thread_local};
+
+thread_local! {
+	static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A global allocator that counts all allocations performed by the current thread and
+/// forwards them to another allocator.
+#[derive(Debug)]
+pub struct CountingAlloc<A = System>(A);
+
+impl CountingAlloc {
+	/// Count allocations and forward them to the [`System`] allocator.
+	pub const fn system() -> Self {
+		Self(System)
+	}
+}
+
+impl<A> CountingAlloc<A> {
+	/// Count allocations and forward them to `inner`.
+	pub const fn new(inner: A) -> Self {
+		Self(inner)
+	}
+}
+
+#[allow(unsafe_code)]
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAlloc<A> {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		// the counter might already be destroyed when a thread exits
+		let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+		self.0.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		self.0.dealloc(ptr, layout)
+	}
+}
+
+/// Returns the number of allocations performed by the current thread so far. This is
+/// always zero unless [`CountingAlloc`] is installed as the global allocator.
+pub fn allocations() -> usize {
+	ALLOCATIONS.with(Cell::get)
+}
+
+/// Assert that an expression performs exactly the given number of allocations on the
+/// current thread, and return the value of the expression. Requires [`CountingAlloc`]
+/// to be installed as the global allocator.
+///
+/// See the [`testalloc`](crate::testalloc) module for an example.
+#[macro_export]
+macro_rules! assert_allocs {
+	($expr:expr, $n:expr) => {{
+		let before = $crate::testalloc::allocations();
+		let result = $expr;
+		let allocs = $crate::testalloc::allocations() - before;
+		assert_eq!(
+			allocs,
+			$n,
+			"expected {} allocations in `{}`, but found {}",
+			$n,
+			stringify!($expr),
+			allocs
+		);
+		result
+	}};
+}
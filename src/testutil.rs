@@ -0,0 +1,378 @@
+//! Utilities for differential testing of [`AATreeMap`] against a simple reference model.
+//! Only available with the `testutil` feature.
+//!
+//! A trace of [`Op`]erations is executed against both the tree and a [`Model`] backed by a
+//! sorted [`Vec`]. After every operation, the outputs and the full contents are compared.
+//! When they diverge, the trace is minimized before it is reported.
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::{
+//! 	testutil::{check_trace, Op},
+//! 	AATreeMap
+//! };
+//!
+//! let trace = [
+//! 	Op::Insert(2, 'b'),
+//! 	Op::Insert(1, 'a'),
+//! 	Op::Remove(2),
+//! 	Op::PopFirst
+//! ];
+//! check_trace::<AATreeMap<_, _>, _, _>(&trace).unwrap();
+//! ```
+
+use crate::AATreeMap;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// An operation that can be applied to a map.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Op<K, V> {
+	Insert(K, V),
+	Remove(K),
+	Get(K),
+	ContainsKey(K),
+	PopFirst,
+	PopLast,
+	FirstAtOrAfter(K),
+	LastAtOrBefore(K),
+	Clear
+}
+
+/// The observable output of applying an [`Op`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Output<K, V> {
+	None,
+	Bool(bool),
+	Value(Option<V>),
+	Entry(Option<(K, V)>)
+}
+
+/// A map that [`Op`]s can be applied to.
+pub trait Apply<K, V> {
+	/// Apply an operation and return its observable output.
+	fn apply(&mut self, op: &Op<K, V>) -> Output<K, V>;
+
+	/// Return all entries of this map in ascending key order.
+	fn entries(&self) -> Vec<(K, V)>;
+}
+
+/// A reference implementation of an ordered map, backed by a sorted [`Vec`].
+#[derive(Clone, Debug)]
+pub struct Model<K, V> {
+	entries: Vec<(K, V)>
+}
+
+impl<K, V> Default for Model<K, V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K, V> Model<K, V> {
+	/// Create a new, empty model.
+	pub const fn new() -> Self {
+		Self {
+			entries: Vec::new()
+		}
+	}
+
+	/// Return the number of entries in the model.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Return `true` if the model contains no entries.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Return an iterator over all entries in ascending key order.
+	pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+		self.entries.iter().map(|(k, v)| (k, v))
+	}
+}
+
+impl<K: Ord, V> Model<K, V> {
+	fn search(&self, key: &K) -> Result<usize, usize> {
+		self.entries.binary_search_by(|(k, _)| k.cmp(key))
+	}
+
+	/// Insert an entry, returning the previous value of that key.
+	pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+		match self.search(&key) {
+			Ok(idx) => Some(core::mem::replace(&mut self.entries[idx].1, value)),
+			Err(idx) => {
+				self.entries.insert(idx, (key, value));
+				None
+			}
+		}
+	}
+
+	/// Remove an entry, returning its value.
+	pub fn remove(&mut self, key: &K) -> Option<V> {
+		let idx = self.search(key).ok()?;
+		Some(self.entries.remove(idx).1)
+	}
+
+	/// Return the value of an entry.
+	pub fn get(&self, key: &K) -> Option<&V> {
+		let idx = self.search(key).ok()?;
+		Some(&self.entries[idx].1)
+	}
+
+	/// Remove and return the entry with the smallest key.
+	pub fn pop_first(&mut self) -> Option<(K, V)> {
+		(!self.entries.is_empty()).then(|| self.entries.remove(0))
+	}
+
+	/// Remove and return the entry with the largest key.
+	pub fn pop_last(&mut self) -> Option<(K, V)> {
+		self.entries.pop()
+	}
+
+	/// Return the first entry with a key greater than or equal to `key`.
+	pub fn first_key_value_at_or_after(&self, key: &K) -> Option<(&K, &V)> {
+		let idx = self.search(key).unwrap_or_else(|idx| idx);
+		self.entries.get(idx).map(|(k, v)| (k, v))
+	}
+
+	/// Return the last entry with a key less than or equal to `key`.
+	pub fn last_key_value_at_or_before(&self, key: &K) -> Option<(&K, &V)> {
+		let idx = match self.search(key) {
+			Ok(idx) => idx,
+			Err(idx) => idx.checked_sub(1)?
+		};
+		self.entries.get(idx).map(|(k, v)| (k, v))
+	}
+}
+
+fn cloned<K: Clone, V: Clone>(entry: Option<(&K, &V)>) -> Output<K, V> {
+	Output::Entry(entry.map(|(k, v)| (k.clone(), v.clone())))
+}
+
+impl<K: Ord + Clone, V: Clone> Apply<K, V> for Model<K, V> {
+	fn apply(&mut self, op: &Op<K, V>) -> Output<K, V> {
+		match op {
+			Op::Insert(k, v) => Output::Value(self.insert(k.clone(), v.clone())),
+			Op::Remove(k) => Output::Value(self.remove(k)),
+			Op::Get(k) => Output::Value(self.get(k).cloned()),
+			Op::ContainsKey(k) => Output::Bool(self.get(k).is_some()),
+			Op::PopFirst => Output::Entry(self.pop_first()),
+			Op::PopLast => Output::Entry(self.pop_last()),
+			Op::FirstAtOrAfter(k) => cloned(self.first_key_value_at_or_after(k)),
+			Op::LastAtOrBefore(k) => cloned(self.last_key_value_at_or_before(k)),
+			Op::Clear => {
+				self.entries.clear();
+				Output::None
+			}
+		}
+	}
+
+	fn entries(&self) -> Vec<(K, V)> {
+		self.entries.clone()
+	}
+}
+
+impl<K: Ord + Clone, V: Clone> Apply<K, V> for AATreeMap<K, V> {
+	fn apply(&mut self, op: &Op<K, V>) -> Output<K, V> {
+		match op {
+			Op::Insert(k, v) => Output::Value(self.insert(k.clone(), v.clone())),
+			Op::Remove(k) => Output::Value(self.remove(k)),
+			Op::Get(k) => Output::Value(self.get(k).cloned()),
+			Op::ContainsKey(k) => Output::Bool(self.contains_key(k)),
+			Op::PopFirst => Output::Entry(self.pop_first()),
+			Op::PopLast => Output::Entry(self.pop_last()),
+			Op::FirstAtOrAfter(k) => cloned(self.first_key_value_at_or_after(k)),
+			Op::LastAtOrBefore(k) => cloned(self.last_key_value_at_or_before(k)),
+			Op::Clear => {
+				self.clear();
+				Output::None
+			}
+		}
+	}
+
+	fn entries(&self) -> Vec<(K, V)> {
+		self.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+	}
+}
+
+/// Describes where a map diverged from the [`Model`].
+#[derive(Clone, Debug)]
+pub struct Divergence<K, V> {
+	/// The minimized trace that leads to the divergence.
+	pub trace: Vec<Op<K, V>>,
+	/// The index of the operation in the minimized trace after which the divergence
+	/// was detected.
+	pub step: usize,
+	/// The kind of divergence.
+	pub kind: DivergenceKind<K, V>
+}
+
+/// The kind of a [`Divergence`].
+#[derive(Clone, Debug)]
+pub enum DivergenceKind<K, V> {
+	/// The outputs of the operation differ.
+	Output {
+		expected: Output<K, V>,
+		actual: Output<K, V>
+	},
+	/// The contents after the operation differ.
+	Contents {
+		expected: Vec<(K, V)>,
+		actual: Vec<(K, V)>
+	}
+}
+
+impl<K: Debug, V: Debug> Display for Divergence<K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		writeln!(f, "Divergence after step {} of trace:", self.step)?;
+		for (i, op) in self.trace.iter().enumerate() {
+			writeln!(f, "  {i}: {op:?}")?;
+		}
+		match &self.kind {
+			DivergenceKind::Output { expected, actual } => {
+				write!(f, "Expected output {expected:?}, but got {actual:?}")
+			},
+			DivergenceKind::Contents { expected, actual } => {
+				write!(f, "Expected contents {expected:?}, but got {actual:?}")
+			}
+		}
+	}
+}
+
+fn run<M, K, V>(ops: &[Op<K, V>]) -> Option<(usize, DivergenceKind<K, V>)>
+where
+	M: Apply<K, V> + Default,
+	K: Ord + Clone,
+	V: Clone + PartialEq
+{
+	let mut map = M::default();
+	let mut model = Model::new();
+	for (step, op) in ops.iter().enumerate() {
+		let expected = model.apply(op);
+		let actual = map.apply(op);
+		if expected != actual {
+			return Some((step, DivergenceKind::Output { expected, actual }));
+		}
+		let expected = model.entries();
+		let actual = map.entries();
+		if expected != actual {
+			return Some((step, DivergenceKind::Contents { expected, actual }));
+		}
+	}
+	None
+}
+
+/// Execute a trace of operations against both `M` and the [`Model`], comparing the output
+/// of each operation as well as the contents after each operation. If a divergence is
+/// found, the trace is minimized by removing all operations that are not required to
+/// reproduce a divergence.
+pub fn check_trace<M, K, V>(ops: &[Op<K, V>]) -> Result<(), Divergence<K, V>>
+where
+	M: Apply<K, V> + Default,
+	K: Ord + Clone,
+	V: Clone + PartialEq
+{
+	let (step, kind) = match run::<M, K, V>(ops) {
+		Some(divergence) => divergence,
+		None => return Ok(())
+	};
+
+	let mut trace: Vec<_> = ops[..= step].to_vec();
+	let mut divergence = (step, kind);
+	let mut idx = trace.len();
+	while idx > 0 {
+		idx -= 1;
+		let mut candidate = trace.clone();
+		candidate.remove(idx);
+		if let Some(found) = run::<M, K, V>(&candidate) {
+			candidate.truncate(found.0 + 1);
+			trace = candidate;
+			divergence = found;
+			idx = idx.min(trace.len());
+		}
+	}
+
+	Err(Divergence {
+		trace,
+		step: divergence.0,
+		kind: divergence.1
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A map that forgets every value inserted for key 3.
+	#[derive(Default)]
+	struct Buggy(AATreeMap<u8, u8>);
+
+	impl Apply<u8, u8> for Buggy {
+		fn apply(&mut self, op: &Op<u8, u8>) -> Output<u8, u8> {
+			match op {
+				Op::Insert(3, _) => Output::Value(self.0.get(&3).copied()),
+				op => self.0.apply(op)
+			}
+		}
+
+		fn entries(&self) -> Vec<(u8, u8)> {
+			self.0.entries()
+		}
+	}
+
+	fn random_trace(seed: u64, len: usize) -> Vec<Op<u8, u8>> {
+		let mut state = seed;
+		let mut next = move || {
+			state = state
+				.wrapping_mul(6364136223846793005)
+				.wrapping_add(1442695040888963407);
+			(state >> 33) as u8
+		};
+		(0 .. len)
+			.map(|_| {
+				let key = next() % 64;
+				match next() % 16 {
+					0 ..= 5 => Op::Insert(key, next()),
+					6 ..= 8 => Op::Remove(key),
+					9 => Op::Get(key),
+					10 => Op::ContainsKey(key),
+					11 => Op::PopFirst,
+					12 => Op::PopLast,
+					13 => Op::FirstAtOrAfter(key),
+					14 => Op::LastAtOrBefore(key),
+					_ => Op::Get(key)
+				}
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_random_traces() {
+		for seed in 0 .. 32 {
+			if let Err(divergence) =
+				check_trace::<AATreeMap<_, _>, _, _>(&random_trace(seed, 500))
+			{
+				panic!("{divergence}");
+			}
+		}
+	}
+
+	#[test]
+	fn test_minimize() {
+		let trace = [
+			Op::Insert(1, 1),
+			Op::Insert(2, 2),
+			Op::Remove(1),
+			Op::Insert(3, 3),
+			Op::Insert(4, 4),
+			Op::Get(3)
+		];
+		let divergence = check_trace::<Buggy, _, _>(&trace).unwrap_err();
+		assert_eq!(divergence.trace, [Op::Insert(3, 3)]);
+		assert_eq!(divergence.step, 0);
+		assert!(matches!(divergence.kind, DivergenceKind::Contents { .. }));
+	}
+}
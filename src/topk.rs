@@ -0,0 +1,197 @@
+//! A bounded set that keeps only the k largest or smallest elements.
+
+use crate::{iter::AAIter, AATreeSet};
+use core::fmt::{self, Debug};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Keep {
+	Largest,
+	Smallest
+}
+
+/// A set that keeps only the `k` largest (or smallest) elements inserted into it, and
+/// evicts all others automatically.
+///
+/// Like [`AATreeSet`], equal elements are stored only once. To keep the top `k` scores
+/// including duplicates, insert tuples like `(score, id)` instead.
+///
+/// # Example
+///
+/// ```rust
+/// # use aatree::AATopK;
+/// let mut top = AATopK::largest(3);
+/// for x in [5, 1, 9, 3, 7, 2] {
+/// 	top.insert(x);
+/// }
+/// assert_eq!(top.iter_sorted().copied().collect::<Vec<_>>(), [5, 7, 9]);
+/// assert_eq!(top.threshold(), Some(&5));
+///
+/// // 4 is smaller than the threshold and therefore rejected
+/// assert_eq!(top.insert(4), Some(4));
+/// // 8 evicts the current threshold
+/// assert_eq!(top.insert(8), Some(5));
+/// assert_eq!(top.threshold(), Some(&7));
+/// ```
+#[derive(Clone)]
+pub struct AATopK<T> {
+	set: AATreeSet<T>,
+	k: usize,
+	keep: Keep
+}
+
+impl<T: Debug> Debug for AATopK<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("AATopK")
+			.field("k", &self.k)
+			.field("keep", &self.keep)
+			.field("set", &self.set)
+			.finish()
+	}
+}
+
+impl<T> AATopK<T> {
+	/// Construct a new, empty container that keeps the `k` largest elements.
+	pub const fn largest(k: usize) -> Self {
+		Self {
+			set: AATreeSet::new(),
+			k,
+			keep: Keep::Largest
+		}
+	}
+
+	/// Construct a new, empty container that keeps the `k` smallest elements.
+	pub const fn smallest(k: usize) -> Self {
+		Self {
+			set: AATreeSet::new(),
+			k,
+			keep: Keep::Smallest
+		}
+	}
+
+	/// Returns the maximum number of elements kept by this container.
+	pub fn k(&self) -> usize {
+		self.k
+	}
+
+	/// Returns the number of elements currently kept by this container.
+	pub fn len(&self) -> usize {
+		self.set.len()
+	}
+
+	/// Returns `true` if the container contains no elements.
+	pub fn is_empty(&self) -> bool {
+		self.set.is_empty()
+	}
+
+	/// Removes all elements from the container.
+	pub fn clear(&mut self) {
+		self.set.clear();
+	}
+
+	/// Creates an iterator that visits all kept elements in ascending order.
+	pub fn iter_sorted(&self) -> AAIter<'_, T, &T> {
+		self.set.iter()
+	}
+
+	/// Returns the underlying set of kept elements.
+	pub fn into_set(self) -> AATreeSet<T> {
+		self.set
+	}
+}
+
+impl<T: Ord> AATopK<T> {
+	/// Returns the current cutoff, that is, the element that will be evicted next. A new
+	/// element needs to be greater (or smaller, if this container keeps the smallest
+	/// elements) than the threshold to be kept. Returns `None` while fewer than `k`
+	/// elements are kept, as every new element will be kept.
+	pub fn threshold(&self) -> Option<&T> {
+		if self.set.len() < self.k {
+			return None;
+		}
+		match self.keep {
+			Keep::Largest => self.set.first(),
+			Keep::Smallest => self.set.last()
+		}
+	}
+
+	/// Insert an element into the container. Returns the element that is not part of the
+	/// container afterwards, if any: This is either an evicted element, or `value` itself
+	/// if it did not make the cut or was already part of the container.
+	pub fn insert(&mut self, value: T) -> Option<T> {
+		if let Some(threshold) = self.threshold() {
+			let rejected = match self.keep {
+				Keep::Largest => value <= *threshold,
+				Keep::Smallest => value >= *threshold
+			};
+			if rejected {
+				return Some(value);
+			}
+		}
+		if self.k == 0 || self.set.contains(&value) {
+			return Some(value);
+		}
+		self.set.insert(value);
+		if self.set.len() <= self.k {
+			return None;
+		}
+		match self.keep {
+			Keep::Largest => self.set.pop_first(),
+			Keep::Smallest => self.set.pop_last()
+		}
+	}
+}
+
+impl<T: Ord> Extend<T> for AATopK<T> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+		for value in iter {
+			self.insert(value);
+		}
+	}
+}
+
+impl<'a, T> IntoIterator for &'a AATopK<T> {
+	type Item = &'a T;
+	type IntoIter = AAIter<'a, T, &'a T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_sorted()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AATopK;
+	use alloc::vec::Vec;
+
+	#[test]
+	fn test_topk() {
+		// a simple permutation of 0 .. 100
+		let values = (0 .. 100u32).map(|x| x * 37 % 100);
+
+		let mut largest = AATopK::largest(10);
+		largest.extend(values.clone());
+		assert_eq!(
+			largest.iter_sorted().copied().collect::<Vec<_>>(),
+			(90 .. 100).collect::<Vec<_>>()
+		);
+		assert_eq!(largest.threshold(), Some(&90));
+
+		let mut smallest = AATopK::smallest(10);
+		smallest.extend(values);
+		assert_eq!(
+			smallest.iter_sorted().copied().collect::<Vec<_>>(),
+			(0 .. 10).collect::<Vec<_>>()
+		);
+		assert_eq!(smallest.threshold(), Some(&9));
+		assert_eq!(smallest.insert(5), Some(5));
+		assert_eq!(smallest.len(), 10);
+	}
+
+	#[test]
+	fn test_topk_zero() {
+		let mut top = AATopK::largest(0);
+		assert_eq!(top.threshold(), None);
+		assert_eq!(top.insert(1), Some(1));
+		assert!(top.is_empty());
+	}
+}
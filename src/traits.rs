@@ -0,0 +1,721 @@
+//! Traits that abstract over ordered maps and sets, so that algorithms can be written once
+//! and be used with both the AA-Tree based collections of this crate and the B-Tree based
+//! collections from [`alloc`].
+//!
+//! Iteration is not part of these traits. Instead, add a bound like
+//! `for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>` where iteration is needed.
+//!
+//! # Example
+//!
+//! ```rust
+//! use aatree::{traits::OrderedMap, AATreeMap};
+//! use std::collections::BTreeMap;
+//!
+//! fn count_words<M: OrderedMap<&'static str, usize> + Default>(
+//! 	text: &'static str
+//! ) -> M {
+//! 	let mut map = M::default();
+//! 	for word in text.split_whitespace() {
+//! 		let count = map.get(word).copied().unwrap_or(0);
+//! 		map.insert(word, count + 1);
+//! 	}
+//! 	map
+//! }
+//!
+//! let text = "a b a c b a";
+//! let aa: AATreeMap<_, _> = count_words(text);
+//! let b: BTreeMap<_, _> = count_words(text);
+//! assert_eq!(aa.get("a"), Some(&3));
+//! assert_eq!(aa.first_key_value(), b.first_key_value());
+//! ```
+//!
+//! [`OrderedMap`] has generic methods and can therefore not be used as a trait object.
+//! Use [`DynOrderedMap`] instead to pass ordered maps across `dyn` boundaries.
+
+use crate::{AATreeMap, AATreeSet};
+use alloc::{
+	boxed::Box,
+	collections::{BTreeMap, BTreeSet}
+};
+use core::{
+	any::Any,
+	borrow::Borrow,
+	fmt::{self, Debug, Formatter},
+	ops::Bound
+};
+
+/// An ordered map, implemented by [`AATreeMap`] and [`BTreeMap`].
+pub trait OrderedMap<K, V> {
+	/// Returns the number of elements in the map.
+	fn len(&self) -> usize;
+
+	/// Returns `true` if the map contains no elements.
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Clears the map, removing all elements.
+	fn clear(&mut self);
+
+	/// Returns a reference to the value corresponding to the key.
+	fn get<Q>(&self, key: &Q) -> Option<&V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized;
+
+	/// Returns a mutable reference to the value corresponding to the key.
+	fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized;
+
+	/// Check if a key is contained within this map.
+	fn contains_key<Q>(&self, key: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.get(key).is_some()
+	}
+
+	/// Insert a new element into the map, or overwrite an existing element with the same
+	/// key. If a value was overwritten, the old value will be returned.
+	fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+	/// Remove a key from the map if it exists, and return the value that was previously
+	/// stored in the map for that key.
+	fn remove<Q>(&mut self, key: &Q) -> Option<V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized;
+
+	/// Returns a reference to the first entry (that is, with the smallest key) in the map.
+	fn first_key_value(&self) -> Option<(&K, &V)>;
+
+	/// Returns a reference to the last entry (that is, with the largest key) in the map.
+	fn last_key_value(&self) -> Option<(&K, &V)>;
+
+	/// Returns a reference to the first entry with a key greater than or equal to `key`.
+	fn first_key_value_at_or_after<Q>(&self, key: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized;
+
+	/// Returns a reference to the last entry with a key smaller than or equal to `key`.
+	fn last_key_value_at_or_before<Q>(&self, key: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized;
+}
+
+/// An ordered set, implemented by [`AATreeSet`] and [`BTreeSet`].
+pub trait OrderedSet<T> {
+	/// Returns the number of elements in the set.
+	fn len(&self) -> usize;
+
+	/// Returns `true` if the set contains no elements.
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Clears the set, removing all elements.
+	fn clear(&mut self);
+
+	/// Returns `true` if the set contains an element equal to `value`.
+	fn contains<Q>(&self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized;
+
+	/// Adds a value to the set. Returns `true` if the value was not present before.
+	fn insert(&mut self, value: T) -> bool;
+
+	/// Removes a value from the set. Returns `true` if it was present before.
+	fn remove<Q>(&mut self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized;
+
+	/// Returns the smallest element of the set.
+	fn first(&self) -> Option<&T>;
+
+	/// Returns the largest element of the set.
+	fn last(&self) -> Option<&T>;
+
+	/// Returns the smallest element of the set that is greater than or equal to `value`.
+	fn first_at_or_after<Q>(&self, value: &Q) -> Option<&T>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized;
+
+	/// Returns the largest element of the set that is smaller than or equal to `value`.
+	fn last_at_or_before<Q>(&self, value: &Q) -> Option<&T>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized;
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for AATreeMap<K, V> {
+	fn len(&self) -> usize {
+		self.len()
+	}
+
+	fn clear(&mut self) {
+		self.clear()
+	}
+
+	fn get<Q>(&self, key: &Q) -> Option<&V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.get(key)
+	}
+
+	fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.get_mut(key)
+	}
+
+	fn contains_key<Q>(&self, key: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.contains_key(key)
+	}
+
+	fn insert(&mut self, key: K, value: V) -> Option<V> {
+		self.insert(key, value)
+	}
+
+	fn remove<Q>(&mut self, key: &Q) -> Option<V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.remove(key)
+	}
+
+	fn first_key_value(&self) -> Option<(&K, &V)> {
+		self.first_key_value()
+	}
+
+	fn last_key_value(&self) -> Option<(&K, &V)> {
+		self.last_key_value()
+	}
+
+	fn first_key_value_at_or_after<Q>(&self, key: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.first_key_value_at_or_after(key)
+	}
+
+	fn last_key_value_at_or_before<Q>(&self, key: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.last_key_value_at_or_before(key)
+	}
+}
+
+impl<T: Ord> OrderedSet<T> for AATreeSet<T> {
+	fn len(&self) -> usize {
+		self.len()
+	}
+
+	fn clear(&mut self) {
+		self.clear()
+	}
+
+	fn contains<Q>(&self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.contains(value)
+	}
+
+	fn insert(&mut self, value: T) -> bool {
+		self.insert(value)
+	}
+
+	fn remove<Q>(&mut self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.remove(value)
+	}
+
+	fn first(&self) -> Option<&T> {
+		self.first()
+	}
+
+	fn last(&self) -> Option<&T> {
+		self.last()
+	}
+
+	fn first_at_or_after<Q>(&self, value: &Q) -> Option<&T>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.first_at_or_after(value)
+	}
+
+	fn last_at_or_before<Q>(&self, value: &Q) -> Option<&T>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.last_at_or_before(value)
+	}
+}
+
+// Note that BTreeMap::first_key_value and friends were only stabilized after our MSRV,
+// so we use its iterators instead.
+
+impl<K: Ord, V> OrderedMap<K, V> for BTreeMap<K, V> {
+	fn len(&self) -> usize {
+		self.len()
+	}
+
+	fn clear(&mut self) {
+		self.clear()
+	}
+
+	fn get<Q>(&self, key: &Q) -> Option<&V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.get(key)
+	}
+
+	fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.get_mut(key)
+	}
+
+	fn contains_key<Q>(&self, key: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.contains_key(key)
+	}
+
+	fn insert(&mut self, key: K, value: V) -> Option<V> {
+		self.insert(key, value)
+	}
+
+	fn remove<Q>(&mut self, key: &Q) -> Option<V>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.remove(key)
+	}
+
+	fn first_key_value(&self) -> Option<(&K, &V)> {
+		self.iter().next()
+	}
+
+	fn last_key_value(&self) -> Option<(&K, &V)> {
+		self.iter().next_back()
+	}
+
+	fn first_key_value_at_or_after<Q>(&self, key: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.range::<Q, _>((Bound::Included(key), Bound::Unbounded))
+			.next()
+	}
+
+	fn last_key_value_at_or_before<Q>(&self, key: &Q) -> Option<(&K, &V)>
+	where
+		K: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.range::<Q, _>((Bound::Unbounded, Bound::Included(key)))
+			.next_back()
+	}
+}
+
+impl<T: Ord> OrderedSet<T> for BTreeSet<T> {
+	fn len(&self) -> usize {
+		self.len()
+	}
+
+	fn clear(&mut self) {
+		self.clear()
+	}
+
+	fn contains<Q>(&self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.contains(value)
+	}
+
+	fn insert(&mut self, value: T) -> bool {
+		self.insert(value)
+	}
+
+	fn remove<Q>(&mut self, value: &Q) -> bool
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.remove(value)
+	}
+
+	fn first(&self) -> Option<&T> {
+		self.iter().next()
+	}
+
+	fn last(&self) -> Option<&T> {
+		self.iter().next_back()
+	}
+
+	fn first_at_or_after<Q>(&self, value: &Q) -> Option<&T>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.range::<Q, _>((Bound::Included(value), Bound::Unbounded))
+			.next()
+	}
+
+	fn last_at_or_before<Q>(&self, value: &Q) -> Option<&T>
+	where
+		T: Borrow<Q>,
+		Q: Ord + ?Sized
+	{
+		self.range::<Q, _>((Bound::Unbounded, Bound::Included(value)))
+			.next_back()
+	}
+}
+
+/// An object-safe variant of [`OrderedMap`] with boxed iterators, implemented by
+/// [`AATreeMap`] and [`BTreeMap`], so that ordered maps can be used as
+/// `dyn DynOrderedMap<K, V>` without making all code generic over the map type. Lookups
+/// take a `&K` instead of any borrowed form of the key.
+///
+/// A `dyn DynOrderedMap<K, V>` can be turned back into the concrete map type using
+/// [`downcast_ref`](#method.downcast_ref), [`downcast_mut`](#method.downcast_mut) or
+/// [`downcast`](#method.downcast).
+///
+/// # Example
+///
+/// ```rust
+/// use aatree::{traits::DynOrderedMap, AATreeMap};
+/// use std::collections::BTreeMap;
+///
+/// let mut maps: Vec<Box<dyn DynOrderedMap<u32, &str>>> =
+/// 	vec![Box::new(AATreeMap::new()), Box::new(BTreeMap::new())];
+/// for map in &mut maps {
+/// 	map.insert(2, "b");
+/// 	map.insert(1, "a");
+/// 	assert_eq!(map.iter().collect::<Vec<_>>(), [(&1, &"a"), (&2, &"b")]);
+/// }
+///
+/// let map = maps.remove(0).downcast::<AATreeMap<u32, &str>>().unwrap();
+/// assert_eq!(map.len(), 2);
+/// assert!(maps[0].downcast_ref::<AATreeMap<u32, &str>>().is_none());
+/// ```
+pub trait DynOrderedMap<K, V>: Any {
+	/// Returns the number of elements in the map.
+	fn len(&self) -> usize;
+
+	/// Returns `true` if the map contains no elements.
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Clears the map, removing all elements.
+	fn clear(&mut self);
+
+	/// Returns a reference to the value corresponding to the key.
+	fn get(&self, key: &K) -> Option<&V>;
+
+	/// Returns a mutable reference to the value corresponding to the key.
+	fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+
+	/// Check if a key is contained within this map.
+	fn contains_key(&self, key: &K) -> bool {
+		self.get(key).is_some()
+	}
+
+	/// Insert a new element into the map, or overwrite an existing element with the same
+	/// key. If a value was overwritten, the old value will be returned.
+	fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+	/// Remove a key from the map if it exists, and return the value that was previously
+	/// stored in the map for that key.
+	fn remove(&mut self, key: &K) -> Option<V>;
+
+	/// Returns a reference to the first entry (that is, with the smallest key) in the map.
+	fn first_key_value(&self) -> Option<(&K, &V)>;
+
+	/// Returns a reference to the last entry (that is, with the largest key) in the map.
+	fn last_key_value(&self) -> Option<(&K, &V)>;
+
+	/// Returns a reference to the first entry with a key greater than or equal to `key`.
+	fn first_key_value_at_or_after(&self, key: &K) -> Option<(&K, &V)>;
+
+	/// Returns a reference to the last entry with a key smaller than or equal to `key`.
+	fn last_key_value_at_or_before(&self, key: &K) -> Option<(&K, &V)>;
+
+	/// Creates an iterator over all entries of the map, with the keys in ascending order.
+	fn iter(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_>;
+
+	/// Creates an iterator over all entries with keys between `start` and `end`, in
+	/// ascending order.
+	///
+	/// # Panics
+	///
+	/// The [`BTreeMap`] implementation panics if `start > end`, or if `start == end` and
+	/// both bounds are excluded.
+	fn range<'a>(
+		&'a self,
+		start: Bound<&'a K>,
+		end: Bound<&'a K>
+	) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>;
+
+	/// Returns this map as [`Any`], which is used for downcasting.
+	fn as_any(&self) -> &dyn Any;
+
+	/// Returns this map as [`Any`], which is used for downcasting.
+	fn as_any_mut(&mut self) -> &mut dyn Any;
+
+	/// Returns this map as [`Any`], which is used for downcasting.
+	fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<K: 'static, V: 'static> dyn DynOrderedMap<K, V> {
+	/// Returns `true` if the concrete type of this map is `M`.
+	pub fn is<M: DynOrderedMap<K, V>>(&self) -> bool {
+		self.as_any().is::<M>()
+	}
+
+	/// Returns a reference to the concrete map if it is of type `M`.
+	pub fn downcast_ref<M: DynOrderedMap<K, V>>(&self) -> Option<&M> {
+		self.as_any().downcast_ref()
+	}
+
+	/// Returns a mutable reference to the concrete map if it is of type `M`.
+	pub fn downcast_mut<M: DynOrderedMap<K, V>>(&mut self) -> Option<&mut M> {
+		self.as_any_mut().downcast_mut()
+	}
+
+	/// Turns this map back into the concrete map if it is of type `M`, or returns it
+	/// unchanged otherwise.
+	pub fn downcast<M: DynOrderedMap<K, V>>(
+		self: Box<Self>
+	) -> Result<Box<M>, Box<Self>> {
+		if self.is::<M>() {
+			Ok(self.into_any().downcast().expect("type was checked"))
+		} else {
+			Err(self)
+		}
+	}
+}
+
+impl<K: Debug + 'static, V: Debug + 'static> Debug for dyn DynOrderedMap<K, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+macro_rules! impl_dyn_ordered_map {
+	($($map:ident),*) => {
+		$(
+			impl<K: Ord + 'static, V: 'static> DynOrderedMap<K, V> for $map<K, V> {
+				fn len(&self) -> usize {
+					OrderedMap::len(self)
+				}
+
+				fn clear(&mut self) {
+					OrderedMap::clear(self)
+				}
+
+				fn get(&self, key: &K) -> Option<&V> {
+					OrderedMap::get(self, key)
+				}
+
+				fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+					OrderedMap::get_mut(self, key)
+				}
+
+				fn contains_key(&self, key: &K) -> bool {
+					OrderedMap::contains_key(self, key)
+				}
+
+				fn insert(&mut self, key: K, value: V) -> Option<V> {
+					OrderedMap::insert(self, key, value)
+				}
+
+				fn remove(&mut self, key: &K) -> Option<V> {
+					OrderedMap::remove(self, key)
+				}
+
+				fn first_key_value(&self) -> Option<(&K, &V)> {
+					OrderedMap::first_key_value(self)
+				}
+
+				fn last_key_value(&self) -> Option<(&K, &V)> {
+					OrderedMap::last_key_value(self)
+				}
+
+				fn first_key_value_at_or_after(&self, key: &K) -> Option<(&K, &V)> {
+					OrderedMap::first_key_value_at_or_after(self, key)
+				}
+
+				fn last_key_value_at_or_before(&self, key: &K) -> Option<(&K, &V)> {
+					OrderedMap::last_key_value_at_or_before(self, key)
+				}
+
+				fn iter(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_> {
+					Box::new(self.iter())
+				}
+
+				fn range<'a>(
+					&'a self,
+					start: Bound<&'a K>,
+					end: Bound<&'a K>
+				) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a> {
+					Box::new(self.range::<K, _>((start, end)))
+				}
+
+				fn as_any(&self) -> &dyn Any {
+					self
+				}
+
+				fn as_any_mut(&mut self) -> &mut dyn Any {
+					self
+				}
+
+				fn into_any(self: Box<Self>) -> Box<dyn Any> {
+					self
+				}
+			}
+		)*
+	};
+}
+
+impl_dyn_ordered_map!(AATreeMap, BTreeMap);
+
+#[cfg(test)]
+mod tests {
+	use super::{OrderedMap, OrderedSet};
+	use crate::{AATreeMap, AATreeSet};
+	use alloc::{
+		collections::{BTreeMap, BTreeSet},
+		vec::Vec
+	};
+
+	fn map_trace<M: OrderedMap<u8, u8> + Default>() -> Vec<Option<(u8, u8)>> {
+		let mut map = M::default();
+		let mut out = Vec::new();
+		for i in 0 .. 50u8 {
+			map.insert(i.wrapping_mul(37) % 64, i);
+			if i % 3 == 0 {
+				map.remove(&(i.wrapping_mul(11) % 64));
+			}
+			let q = i.wrapping_mul(13) % 64;
+			for entry in [
+				map.first_key_value(),
+				map.last_key_value(),
+				map.first_key_value_at_or_after(&q),
+				map.last_key_value_at_or_before(&q)
+			] {
+				out.push(entry.map(|(k, v)| (*k, *v)));
+			}
+		}
+		out
+	}
+
+	fn set_trace<S: OrderedSet<u8> + Default>() -> Vec<Option<u8>> {
+		let mut set = S::default();
+		let mut out = Vec::new();
+		for i in 0 .. 50u8 {
+			set.insert(i.wrapping_mul(37) % 64);
+			if i % 3 == 0 {
+				set.remove(&(i.wrapping_mul(11) % 64));
+			}
+			let q = i.wrapping_mul(13) % 64;
+			for value in [
+				set.first(),
+				set.last(),
+				set.first_at_or_after(&q),
+				set.last_at_or_before(&q)
+			] {
+				out.push(value.copied());
+			}
+		}
+		out
+	}
+
+	#[test]
+	fn test_map_impls_agree() {
+		assert_eq!(
+			map_trace::<AATreeMap<_, _>>(),
+			map_trace::<BTreeMap<_, _>>()
+		);
+	}
+
+	#[test]
+	fn test_dyn_map_impls_agree() {
+		use super::DynOrderedMap;
+		use alloc::boxed::Box;
+		use core::ops::Bound::{Excluded, Included, Unbounded};
+
+		let mut maps: [Box<dyn DynOrderedMap<u8, u8>>; 2] =
+			[Box::new(AATreeMap::new()), Box::new(BTreeMap::new())];
+		for map in &mut maps {
+			for i in 0 .. 50u8 {
+				map.insert(i.wrapping_mul(37) % 64, i);
+			}
+			map.remove(&0);
+		}
+		let [aa, b] = &maps;
+		assert!(aa.iter().eq(b.iter()));
+		for q in 0 .. 64 {
+			for (start, end) in [(Included(&q), Unbounded), (Unbounded, Excluded(&q))] {
+				assert!(aa.range(start, end).eq(b.range(start, end)));
+			}
+			assert_eq!(aa.get(&q), b.get(&q));
+			assert_eq!(
+				aa.first_key_value_at_or_after(&q),
+				b.first_key_value_at_or_after(&q)
+			);
+		}
+
+		let [mut aa, b] = maps;
+		assert!(aa.is::<AATreeMap<u8, u8>>());
+		aa.downcast_mut::<AATreeMap<u8, u8>>()
+			.unwrap()
+			.assert_valid();
+		let b = b.downcast::<AATreeMap<u8, u8>>().unwrap_err();
+		assert_eq!(b.downcast::<BTreeMap<u8, u8>>().unwrap().len(), aa.len());
+	}
+
+	#[test]
+	fn test_set_impls_agree() {
+		assert_eq!(set_trace::<AATreeSet<_>>(), set_trace::<BTreeSet<_>>());
+	}
+}
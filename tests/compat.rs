@@ -0,0 +1,46 @@
+//! Compatibility fixtures for the stable encodings of this crate. Every fixture was
+//! written by a previous release and must still load into the current version. The
+//! current version must also reproduce the fixtures byte for byte, as the encodings are
+//! deterministic.
+//!
+//! When a format changes incompatibly, add new fixtures for the new format version and
+//! keep the old ones.
+
+use aatree::{
+	frozen::{self, FrozenView},
+	AATreeMap, AATreeSet
+};
+
+const FROZEN_V1: &[u8] = include_bytes!("fixtures/frozen_v1_u32_u64.bin");
+const MAP_JSON: &str = include_str!("fixtures/map_u32_string.json");
+const SET_JSON: &str = include_str!("fixtures/set_i32.json");
+
+#[test]
+fn test_frozen_v1() {
+	let expected = AATreeMap::from([(1u32, 10u64), (2, 20), (3, 30), (1000, 123456789)]);
+	assert_eq!(frozen::format_version(FROZEN_V1).unwrap(), 1);
+	let view = FrozenView::<u32, u64>::from_bytes(FROZEN_V1).unwrap();
+	assert_eq!(AATreeMap::from(view.to_frozen()), expected);
+	assert_eq!(expected.freeze().to_bytes(), FROZEN_V1);
+}
+
+#[test]
+fn test_serde_map() {
+	let expected = AATreeMap::from([
+		(1u32, "a".to_owned()),
+		(2, "b".to_owned()),
+		(10, "c".to_owned()),
+		(200, "d".to_owned())
+	]);
+	let map: AATreeMap<u32, String> = serde_json::from_str(MAP_JSON).unwrap();
+	assert_eq!(map, expected);
+	assert_eq!(serde_json::to_string(&expected).unwrap(), MAP_JSON);
+}
+
+#[test]
+fn test_serde_set() {
+	let expected = AATreeSet::from([42, 3, 0, -5]);
+	let set: AATreeSet<i32> = serde_json::from_str(SET_JSON).unwrap();
+	assert_eq!(set, expected);
+	assert_eq!(serde_json::to_string(&expected).unwrap(), SET_JSON);
+}
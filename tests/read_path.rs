@@ -0,0 +1,89 @@
+//! Checks that the read path of [`AATreeMap`] and [`AATreeSet`] does not allocate, as
+//! documented in the crate root, and that inserting allocates exactly once. This lives
+//! in its own test binary because it replaces the global allocator.
+
+use aatree::{assert_allocs, testalloc::CountingAlloc, AATreeMap, AATreeSet};
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc::system();
+
+fn random_keys(len: usize) -> impl Iterator<Item = u32> {
+	let mut state = 7u32;
+	(0 .. len).map(move |_| {
+		state = state.wrapping_mul(1103515245).wrapping_add(12345);
+		state % 10000
+	})
+}
+
+#[test]
+fn test_read_path_does_not_allocate() {
+	for len in [0, 1, 2, 3, 10, 100, 1000, 5000] {
+		let mut map = AATreeMap::new();
+		let mut set = AATreeSet::new();
+		for key in random_keys(len) {
+			map.insert(key, key);
+			set.insert(key);
+		}
+
+		for key in (0 .. 10000).step_by(7) {
+			assert_allocs!(map.get(&key), 0);
+			assert_allocs!(map.get_key_value(&key), 0);
+			assert_allocs!(map.contains_key(&key), 0);
+			assert_allocs!(map.first_key_value_at_or_after(&key), 0);
+			assert_allocs!(map.last_key_value_at_or_before(&key), 0);
+			assert_allocs!(set.contains(&key), 0);
+			assert_allocs!(set.first_at_or_after(&key), 0);
+			assert_allocs!(set.last_at_or_before(&key), 0);
+		}
+		assert_allocs!(map.first_key_value(), 0);
+		assert_allocs!(map.last_key_value(), 0);
+		assert_allocs!(set.first(), 0);
+		assert_allocs!(set.last(), 0);
+
+		let mut iter = map.iter();
+		let count = assert_allocs!(iter.by_ref().count(), 0);
+		assert_eq!(count, map.len());
+		let mut iter = set.iter();
+		let count = assert_allocs!(iter.by_ref().count(), 0);
+		assert_eq!(count, set.len());
+	}
+}
+
+#[test]
+fn test_insert_allocates_once() {
+	let mut map = AATreeMap::new();
+	let mut set = AATreeSet::new();
+	for key in random_keys(5000) {
+		if map.contains_key(&key) {
+			assert_allocs!(map.insert(key, key), 0);
+			assert_allocs!(set.insert(key), 0);
+		} else {
+			assert_allocs!(map.insert(key, key), 1);
+			assert_allocs!(set.insert(key), 1);
+		}
+	}
+}
+
+#[test]
+fn test_entry_ref_allocates_only_when_inserting() {
+	let mut map: AATreeMap<String, usize> = AATreeMap::new();
+	// one allocation for the key, one for the node
+	assert_allocs!(*map.entry_ref("a").or_insert(0) += 1, 2);
+	assert_allocs!(*map.entry_ref("a").or_insert(0) += 1, 0);
+	assert_allocs!(map.entry_ref("b").and_modify(|count| *count += 1), 0);
+	assert_eq!(map.get("a"), Some(&2));
+	assert_eq!(map.get("b"), None);
+}
+
+#[test]
+fn test_vacant_entry_allocates_once() {
+	let mut map: AATreeMap<String, usize> = AATreeMap::new();
+	for key in random_keys(1000) {
+		let key = key.to_string();
+		if map.contains_key(&key) {
+			assert_allocs!(*map.entry(key).or_insert(0) += 1, 0);
+		} else {
+			assert_allocs!(*map.entry(key).or_insert(0) += 1, 1);
+		}
+	}
+}